@@ -3,7 +3,7 @@ alias LambdaEthereumConsensus.Execution.ExecutionChain
 # The --mode db flag is needed to run this benchmark.
 
 compressed_tree = File.read!("deposit_tree_file")
-{:ok, encoded_tree} = :snappyer.decompress(compressed_tree)
+{:ok, encoded_tree} = Snappy.decompress_raw(compressed_tree)
 deposit_tree = :erlang.binary_to_term(encoded_tree)
 
 Benchee.run(
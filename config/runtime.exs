@@ -24,7 +24,9 @@ switches = [
   discovery_port: :integer,
   boot_nodes: :string,
   keystore_dir: :string,
-  keystore_pass_dir: :string
+  keystore_pass_dir: :string,
+  verification_workers: :integer,
+  kzg_trusted_setup: :string
 ]
 
 is_testing = Config.config_env() == :test
@@ -56,6 +58,8 @@ discovery_port = Keyword.get(args, :discovery_port, 9000)
 cli_bootnodes = Keyword.get(args, :boot_nodes, "")
 keystore_dir = Keyword.get(args, :keystore_dir)
 keystore_pass_dir = Keyword.get(args, :keystore_pass_dir)
+verification_workers = Keyword.get(args, :verification_workers)
+kzg_trusted_setup = Keyword.get(args, :kzg_trusted_setup)
 
 if not is_nil(testnet_dir) and not is_nil(checkpoint_sync_url) do
   IO.puts("Both checkpoint sync and testnet url specified (only one should be specified).")
@@ -197,6 +201,11 @@ config :lambda_ethereum_consensus, LambdaEthereumConsensus.ValidatorSet,
   keystore_dir: keystore_dir,
   keystore_pass_dir: keystore_pass_dir
 
+config :lambda_ethereum_consensus, VerificationPool, workers: verification_workers
+
+# When nil, the mainnet trusted setup embedded in the NIF is used
+config :lambda_ethereum_consensus, Kzg, trusted_setup: kzg_trusted_setup
+
 # TODO: we should set this dynamically
 block_time_ms =
   case network do
@@ -0,0 +1,82 @@
+defmodule AttestationPool do
+  @moduledoc """
+  Naive aggregation pool for Electra attestations, backed by a native resource.
+
+  Attestations are grouped by the root of their `Types.AttestationData`. An
+  attestation whose aggregation bits are disjoint from an existing aggregate with
+  the same committee bits is merged into it, aggregating the signatures as it
+  arrives. Signatures aren't verified, so only valid attestations should be added.
+
+  The validator uses it to build the aggregates it publishes. Aggregates received
+  over gossip are kept as-is by the operations collector, which persists them in the
+  database, so they aren't merged here.
+  """
+
+  alias LambdaEthereumConsensus.StateTransition.Accessors
+
+  @type t :: Ssz.attestation_pool()
+  @type insertion :: :new | :aggregated | :already_known
+
+  @spec new() :: t()
+  def new(), do: Ssz.attestation_pool_new()
+
+  @doc """
+  Adds an aggregated attestation to the pool.
+  """
+  @spec insert(t(), Types.Attestation.t()) :: {:ok, insertion()} | {:error, Ssz.error()}
+  def insert(pool, %Types.Attestation{} = attestation) do
+    with {:ok, bin} <- Ssz.to_ssz(attestation) do
+      Ssz.attestation_pool_insert(pool, bin)
+    end
+  end
+
+  @doc """
+  Adds a single attestation to the pool. `index_in_committee` is the position of
+  the attester in its committee, which has `committee_length` members. See
+  `insert_single_from_state/3` to look them up.
+  """
+  @spec insert_single(t(), Types.SingleAttestation.t(), non_neg_integer(), pos_integer()) ::
+          {:ok, insertion()} | {:error, Ssz.error()}
+  def insert_single(pool, %Types.SingleAttestation{} = attestation, index, committee_length) do
+    with {:ok, bin} <- Ssz.to_ssz(attestation) do
+      Ssz.attestation_pool_insert_single(pool, bin, index, committee_length)
+    end
+  end
+
+  @doc """
+  Adds a single attestation to the pool, looking up the attester's committee in
+  `state`, which must be recent enough to compute the committees of its slot.
+  """
+  @spec insert_single_from_state(t(), Types.SingleAttestation.t(), Types.BeaconState.t()) ::
+          {:ok, insertion()} | {:error, Ssz.error() | String.t()}
+  def insert_single_from_state(pool, %Types.SingleAttestation{} = attestation, state) do
+    %{data: data, committee_index: committee_index, attester_index: attester_index} = attestation
+
+    with {:ok, committee} <- Accessors.get_beacon_committee(state, data.slot, committee_index) do
+      case Enum.find_index(committee, &(&1 == attester_index)) do
+        nil -> {:error, "Attester #{attester_index} isn't in committee #{committee_index}"}
+        index -> insert_single(pool, attestation, index, length(committee))
+      end
+    end
+  end
+
+  @doc """
+  Returns the aggregate with the most participants for the given data root.
+  """
+  @spec get_aggregate(t(), Types.root()) ::
+          {:ok, Types.Attestation.t() | nil} | {:error, Ssz.error()}
+  def get_aggregate(pool, data_root) do
+    case Ssz.attestation_pool_get_aggregate(pool, data_root) do
+      {:ok, nil} -> {:ok, nil}
+      {:ok, bin} -> Ssz.from_ssz(bin, Types.Attestation)
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
+  @doc """
+  Removes the attestations for slots before `min_slot`. Returns the number of
+  data roots removed.
+  """
+  @spec prune(t(), Types.slot()) :: non_neg_integer()
+  def prune(pool, min_slot), do: Ssz.attestation_pool_prune(pool, min_slot)
+end
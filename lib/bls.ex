@@ -2,63 +2,392 @@ defmodule Bls do
   @moduledoc """
   BLS signature verification.
   """
-  use Rustler, otp_app: :lambda_ethereum_consensus, crate: "bls_nif"
+  use Rustler,
+    otp_app: :lambda_ethereum_consensus,
+    crate: "bls_nif",
+    load_data_fun: {VerificationPool, :workers}
 
-  @type privkey() :: <<_::256>>
+  @typedoc """
+  A handle to a secret key held in native memory. See `secret_key_from_bytes/1`.
+  """
+  @opaque privkey :: reference()
   @type pubkey :: <<_::384>>
   @type signature :: <<_::768>>
+  @type signature_set :: {[pubkey()], binary(), signature()}
+  @type indexed_signature_set :: {[non_neg_integer()], binary(), signature()}
+  @type signature_sets_result :: :valid | {:invalid, non_neg_integer()} | :batch_invalid
+  @typedoc """
+  Errors returned by the signing, verification, key derivation and pubkey cache
+  functions. Length errors carry the received and expected lengths. See
+  `docs/nif_errors.md`.
+  """
+  @type error ::
+          :infinity_pubkey
+          | :zero_secret_key
+          | :empty_pubkeys
+          | :empty_signatures
+          | :poisoned_lock
+          | {:invalid_pubkey_encoding, String.t()}
+          | {:invalid_signature_encoding, String.t()}
+          | {:invalid_secret_key, String.t()}
+          | {:pubkey_length, non_neg_integer(), 48}
+          | {:signature_length, non_neg_integer(), 96}
+          | {:secret_key_length, non_neg_integer(), 32}
+          | {:message_length, non_neg_integer(), 32}
+          | {:unknown_validator_index, non_neg_integer()}
+          | {:invalid_cached_pubkey, non_neg_integer()}
+          | {:non_contiguous_extend, non_neg_integer(), non_neg_integer()}
+          | {:invalid_derivation_path, String.t()}
+          | {:seed_too_short, non_neg_integer()}
+  @opaque pubkey_cache :: reference()
+  @type kdf_params ::
+          {:scrypt, %{n: pos_integer(), r: pos_integer(), p: pos_integer()}}
+          | {:pbkdf2, %{c: pos_integer()}}
+  @type keystore_error ::
+          :invalid_password
+          | :invalid_checksum
+          | {:unsupported_kdf, String.t()}
+          | {:invalid_keystore, String.t()}
+          | {:encryption_failed, String.t()}
+  @opaque slashing_protection_db :: reference()
+  @type slashing_error ::
+          :double_block_proposal
+          | :block_slot_too_low
+          | :double_vote
+          | :surrounding_vote
+          | :surrounded_vote
+          | :source_exceeds_target
+          | :attestation_source_too_low
+          | :attestation_target_too_low
+          | :genesis_validators_root_mismatch
+          | {:invalid_input, String.t()}
+          | {:invalid_interchange, String.t()}
+          | {:database_error, String.t()}
+
+  ##### Secret keys #####
+  # Secret keys never leave native memory, unless explicitly exported.
+
+  @doc """
+  Imports a 32-byte secret key, returning a handle to it.
+  The key is zeroized once the handle is garbage collected.
+  """
+  @spec secret_key_from_bytes(<<_::256>>) :: {:ok, privkey()} | {:error, error()}
+  def secret_key_from_bytes(_private_key) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
 
-  @spec sign(privkey(), binary()) :: {:ok, signature()} | {:error, any()}
+  @doc """
+  Returns the secret key's bytes. This is the only way to get them back.
+  """
+  @spec export_secret_key(privkey()) :: <<_::256>>
+  def export_secret_key(_private_key) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @spec sign(privkey(), binary()) :: {:ok, signature()} | {:error, error()}
   def sign(_private_key, _message) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
-  @spec aggregate([signature()]) :: {:ok, signature()} | {:error, any()}
+  @spec aggregate([signature()]) :: {:ok, signature()} | {:error, error()}
   def aggregate(_signatures) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
   @spec verify(pubkey(), binary(), signature()) ::
-          {:ok, boolean} | {:error, binary()}
+          {:ok, boolean} | {:error, error()}
   def verify(_public_key, _message, _signature) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
   @spec fast_aggregate_verify([pubkey()], binary(), signature()) ::
-          {:ok, boolean} | {:error, binary()}
+          {:ok, boolean} | {:error, error()}
   def fast_aggregate_verify(_public_keys, _message, _signature) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
   @spec eth_fast_aggregate_verify([pubkey()], binary(), signature()) ::
-          {:ok, boolean} | {:error, binary()}
+          {:ok, boolean} | {:error, error()}
   def eth_fast_aggregate_verify(_public_keys, _message, _signature) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
   @spec aggregate_verify([pubkey()], [binary()], signature()) ::
-          {:ok, boolean} | {:error, binary()}
+          {:ok, boolean} | {:error, error()}
   def aggregate_verify(_public_keys, _messages, _signature) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
+  @doc """
+  Verifies a batch of signature sets, each one a `{pubkeys, message, signature}` tuple
+  checked as in ``Bls.fast_aggregate_verify``. If the batch is invalid, the index of
+  the first invalid set is returned, or `:batch_invalid` if no set fails on its own.
+  """
+  @spec verify_signature_sets([signature_set()]) ::
+          {:ok, signature_sets_result()} | {:error, error()}
+  def verify_signature_sets(_signature_sets) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
   @spec eth_aggregate_pubkeys([pubkey()]) ::
-          {:ok, pubkey()} | {:error, any()}
+          {:ok, pubkey()} | {:error, error()}
   def eth_aggregate_pubkeys(_public_keys) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
   @spec key_validate(pubkey()) ::
-          {:ok, boolean} | {:error, binary()}
+          {:ok, boolean} | {:error, error()}
   def key_validate(_public_key) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
-  @spec derive_pubkey(privkey()) :: {:ok, pubkey()} | {:error, any()}
+  @spec derive_pubkey(privkey()) :: {:ok, pubkey()} | {:error, error()}
   def derive_pubkey(_private_key) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
+  ##### Asynchronous verification #####
+  # These run in the `VerificationPool` and send `{job, result}` to the caller, where
+  # `result` is what the synchronous version returns.
+
+  @spec verify_async(pubkey(), binary(), signature()) :: VerificationPool.job()
+  def verify_async(_public_key, _message, _signature) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @spec fast_aggregate_verify_async([pubkey()], binary(), signature()) ::
+          VerificationPool.job()
+  def fast_aggregate_verify_async(_public_keys, _message, _signature) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @spec verify_signature_sets_async([signature_set()]) :: VerificationPool.job()
+  def verify_signature_sets_async(_signature_sets) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  ##### Key derivation (EIP-2333/EIP-2334) #####
+
+  @doc """
+  Derives the master secret key from a seed of at least 32 bytes.
+  """
+  @spec derive_master_sk(binary()) :: {:ok, privkey()} | {:error, error()}
+  def derive_master_sk(_seed) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Derives the secret and public keys found at `path` (e.g. "m/12381/3600/0/0/0") from a seed.
+  The path is validated as in `parse_derivation_path/1`.
+  """
+  @spec derive_key_from_path(binary(), String.t()) ::
+          {:ok, {privkey(), pubkey()}} | {:error, error()}
+  def derive_key_from_path(_seed, _path) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Parses and validates an EIP-2334 path, returning its indices.
+  """
+  @spec parse_derivation_path(String.t()) :: {:ok, [non_neg_integer()]} | {:error, error()}
+  def parse_derivation_path(_path) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  ##### Keystores (EIP-2335) #####
+
+  @doc """
+  Decrypts a version 4 keystore, returning its secret and public keys.
+  The password must be already normalized.
+  """
+  @spec decrypt_keystore(String.t(), binary()) ::
+          {:ok, {privkey(), pubkey()}} | {:error, keystore_error()}
+  def decrypt_keystore(_json, _password) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Encrypts a secret key into a new version 4 keystore, returned as JSON.
+  """
+  @spec encrypt_keystore(privkey(), binary(), String.t(), kdf_params()) ::
+          {:ok, String.t()} | {:error, keystore_error()}
+  def encrypt_keystore(_private_key, _password, _path, _kdf_params) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  ##### Slashing protection (EIP-3076) #####
+  # Every signed block and attestation is persisted before its signature is returned.
+
+  @doc """
+  Opens the slashing protection database at `path`, creating it if needed.
+  Only one handle should be open for a given path at any time.
+  """
+  @spec slashing_protection_open(Path.t(), Types.root()) ::
+          {:ok, slashing_protection_db()} | {:error, slashing_error()}
+  def slashing_protection_open(_path, _genesis_validators_root) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Checks that signing the block is safe, and records it.
+  `:same_data` means the exact same block was already signed.
+  """
+  @spec slashing_protection_check_block(
+          slashing_protection_db(),
+          pubkey(),
+          Types.slot(),
+          Types.root()
+        ) :: {:ok, :valid | :same_data} | {:error, slashing_error()}
+  def slashing_protection_check_block(_db, _public_key, _slot, _signing_root) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Checks that signing the attestation is safe, and records it.
+  `:same_data` means the exact same attestation was already signed.
+  """
+  @spec slashing_protection_check_attestation(
+          slashing_protection_db(),
+          pubkey(),
+          Types.epoch(),
+          Types.epoch(),
+          Types.root()
+        ) :: {:ok, :valid | :same_data} | {:error, slashing_error()}
+  def slashing_protection_check_attestation(
+        _db,
+        _public_key,
+        _source_epoch,
+        _target_epoch,
+        _signing_root
+      ) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Checks and records the block, and only then signs its signing root.
+  """
+  @spec slashing_protection_sign_block(
+          slashing_protection_db(),
+          privkey(),
+          Types.slot(),
+          Types.root()
+        ) :: {:ok, signature()} | {:error, slashing_error()}
+  def slashing_protection_sign_block(_db, _private_key, _slot, _signing_root) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Checks and records the attestation, and only then signs its signing root.
+  """
+  @spec slashing_protection_sign_attestation(
+          slashing_protection_db(),
+          privkey(),
+          Types.epoch(),
+          Types.epoch(),
+          Types.root()
+        ) :: {:ok, signature()} | {:error, slashing_error()}
+  def slashing_protection_sign_attestation(
+        _db,
+        _private_key,
+        _source_epoch,
+        _target_epoch,
+        _signing_root
+      ) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Imports an interchange JSON, merging it with the stored data.
+  Returns the number of validators in it.
+  """
+  @spec slashing_protection_import(slashing_protection_db(), String.t()) ::
+          {:ok, non_neg_integer()} | {:error, slashing_error()}
+  def slashing_protection_import(_db, _json) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Exports all the stored data as an interchange JSON.
+  """
+  @spec slashing_protection_export(slashing_protection_db()) ::
+          {:ok, String.t()} | {:error, slashing_error()}
+  def slashing_protection_export(_db) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  ##### Pubkey cache #####
+  # Decompressed public keys held in native memory, addressed by validator index.
+
+  @spec pubkey_cache_new() :: pubkey_cache()
+  def pubkey_cache_new() do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Appends the given public keys, in validator registry order, starting at `from_index`.
+  Keys already in the cache are skipped. Returns the new cache length.
+  """
+  @spec pubkey_cache_extend(pubkey_cache(), non_neg_integer(), [pubkey()]) ::
+          {:ok, non_neg_integer()} | {:error, error()}
+  def pubkey_cache_extend(_cache, _from_index, _public_keys) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @spec pubkey_cache_len(pubkey_cache()) :: {:ok, non_neg_integer()} | {:error, error()}
+  def pubkey_cache_len(_cache) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @spec pubkey_cache_get(pubkey_cache(), non_neg_integer()) ::
+          {:ok, pubkey()} | {:error, error()}
+  def pubkey_cache_get(_cache, _index) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @spec pubkey_cache_index_of(pubkey_cache(), pubkey()) ::
+          {:ok, non_neg_integer() | nil} | {:error, error()}
+  def pubkey_cache_index_of(_cache, _public_key) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Same as ``Bls.fast_aggregate_verify``, but takes validator indices into the cache.
+  """
+  @spec fast_aggregate_verify_indices(
+          pubkey_cache(),
+          [non_neg_integer()],
+          binary(),
+          signature()
+        ) ::
+          {:ok, boolean} | {:error, error()}
+  def fast_aggregate_verify_indices(_cache, _indices, _message, _signature) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Same as ``Bls.eth_fast_aggregate_verify``, but takes validator indices into the cache.
+  """
+  @spec eth_fast_aggregate_verify_indices(
+          pubkey_cache(),
+          [non_neg_integer()],
+          binary(),
+          signature()
+        ) ::
+          {:ok, boolean} | {:error, error()}
+  def eth_fast_aggregate_verify_indices(_cache, _indices, _message, _signature) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Same as ``Bls.verify_signature_sets``, but the sets take validator indices into the
+  cache instead of public keys.
+  """
+  @spec verify_signature_sets_indices(pubkey_cache(), [indexed_signature_set()]) ::
+          {:ok, signature_sets_result()} | {:error, error()}
+  def verify_signature_sets_indices(_cache, _signature_sets) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
   ##### Helpers #####
   @doc """
   Same as ``Bls.verify``, but treats errors as invalid signatures.
@@ -154,4 +154,18 @@ defmodule Constants do
 
   @spec g2_point_at_infinity() :: Types.bls_signature()
   def g2_point_at_infinity(), do: <<0xC0, 0::8*95>>
+
+  ## Light client
+
+  @spec execution_payload_gindex() :: non_neg_integer()
+  def execution_payload_gindex(), do: 25
+
+  @spec current_sync_committee_gindex() :: non_neg_integer()
+  def current_sync_committee_gindex(), do: 86
+
+  @spec next_sync_committee_gindex() :: non_neg_integer()
+  def next_sync_committee_gindex(), do: 87
+
+  @spec finalized_root_gindex() :: non_neg_integer()
+  def finalized_root_gindex(), do: 169
 end
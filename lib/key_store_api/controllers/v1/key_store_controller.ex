@@ -51,20 +51,10 @@ defmodule KeyStoreApi.V1.KeyStoreController do
     results =
       Enum.zip(body_params.keystores, body_params.passwords)
       |> Enum.map(fn {keystore_str, password_str} ->
-        # TODO (#1268): handle bad requests
-        keystore = Keystore.decode_str!(keystore_str, password_str)
-
-        base_name = keystore.pubkey |> Utils.hex_encode()
-
-        # This overrides any existing credential with the same pubkey.
-        File.write!(Keystore.keystore_file(base_name), keystore_str)
-        File.write!(Keystore.keystore_pass_file(base_name), password_str)
-        Libp2pPort.add_validator(keystore)
-
-        %{
-          status: "imported",
-          message: "Pubkey: #{inspect(keystore.pubkey)}"
-        }
+        case Keystore.decode_str(keystore_str, password_str) do
+          {:ok, keystore} -> import_keystore(keystore, keystore_str, password_str)
+          {:error, reason} -> %{status: "error", message: "Invalid keystore: #{inspect(reason)}"}
+        end
       end)
 
     conn
@@ -73,6 +63,20 @@ defmodule KeyStoreApi.V1.KeyStoreController do
     })
   end
 
+  defp import_keystore(keystore, keystore_str, password_str) do
+    base_name = keystore.pubkey |> Utils.hex_encode()
+
+    # This overrides any existing credential with the same pubkey.
+    File.write!(Keystore.keystore_file(base_name), keystore_str)
+    File.write!(Keystore.keystore_pass_file(base_name), password_str)
+    Libp2pPort.add_validator(keystore)
+
+    %{
+      status: "imported",
+      message: "Pubkey: #{inspect(keystore.pubkey)}"
+    }
+  end
+
   @doc """
   For each pubkey received:
   - Removes the associated validator from Libp2pPort.
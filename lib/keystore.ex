@@ -3,11 +3,7 @@ defmodule Keystore do
   [ERC-2335](https://eips.ethereum.org/EIPS/eip-2335) compliant keystore.
   """
 
-  @secret_key_bytes 32
-  @salt_bytes 32
-  @derived_key_size 32
-  @iv_size 16
-  @checksum_message_size 32
+  @default_kdf_params {:scrypt, %{n: 262_144, r: 8, p: 1}}
 
   fields = [
     :pubkey,
@@ -81,115 +77,69 @@ defmodule Keystore do
 
   @spec decode_str!(String.t(), String.t()) :: t()
   def decode_str!(json, password) do
-    decoded_json = Jason.decode!(json)
-    # We only support version 4 (the only one)
-    %{"version" => 4} = decoded_json
-    path = decoded_json["path"]
-    validate_empty_path!(path)
-
-    privkey = decrypt!(decoded_json["crypto"], password)
-
-    {:ok, derived_pubkey} = Bls.derive_pubkey(privkey)
-
-    pubkey =
-      case Map.has_key?(decoded_json, "pubkey") do
-        true -> Map.get(decoded_json, "pubkey") |> parse_binary!()
-        false -> derived_pubkey
-      end
-
-    if derived_pubkey != pubkey do
-      raise("Keystore secret and public keys don't form a valid pair")
+    case decode_str(json, password) do
+      {:ok, keystore} -> keystore
+      {:error, reason} -> raise("Failed to decode keystore: #{inspect(reason)}")
     end
-
-    %__MODULE__{pubkey: pubkey, privkey: privkey, path: path, readonly: false}
   end
 
-  # TODO: support keystore paths
-  defp validate_empty_path!(path) when byte_size(path) > 0,
-    do: raise("Only empty-paths are supported")
-
-  defp validate_empty_path!(_), do: :ok
-
-  defp decrypt!(%{"kdf" => kdf, "checksum" => checksum, "cipher" => cipher}, password) do
-    password = sanitize_password(password)
-    derived_key = derive_key!(kdf, password)
-
-    {iv, cipher_message} = parse_cipher!(cipher)
-    checksum_message = parse_checksum!(checksum)
-    verify_password!(derived_key, cipher_message, checksum_message)
-    secret = decrypt_secret(derived_key, iv, cipher_message)
-
-    if byte_size(secret) != @secret_key_bytes do
-      raise "Invalid secret length: #{byte_size(secret)}"
+  @spec decode_str(String.t(), String.t()) :: {:ok, t()} | {:error, any()}
+  def decode_str(json, password) do
+    with {:ok, decoded_json} <- decode_json(json),
+         # We only support version 4 (the only one)
+         :ok <- validate_version(decoded_json["version"]),
+         path = decoded_json["path"],
+         :ok <- validate_path(path),
+         {:ok, {privkey, derived_pubkey}} <-
+           Bls.decrypt_keystore(json, sanitize_password(password)),
+         :ok <- validate_pubkey(decoded_json["pubkey"], derived_pubkey) do
+      {:ok, %__MODULE__{pubkey: derived_pubkey, privkey: privkey, path: path, readonly: false}}
     end
-
-    secret
   end
 
-  defp derive_key!(%{"function" => "scrypt", "params" => params}, password) do
-    %{"dklen" => @derived_key_size, "salt" => hex_salt, "n" => n, "p" => p, "r" => r} = params
-    salt = parse_binary!(hex_salt)
-
-    if byte_size(salt) != @salt_bytes do
-      raise "Invalid salt size: #{byte_size(salt)}"
-    end
-
-    log_n = n |> :math.log2() |> trunc()
-    Scrypt.hash(password, salt, log_n, r, p, @derived_key_size)
+  @doc """
+  Encrypts a secret key into a new keystore, returned as JSON.
+  Uses scrypt with the EIP-2335 parameters, unless other KDF parameters are given.
+  """
+  @spec encode(Bls.privkey(), String.t(), String.t(), Bls.kdf_params()) ::
+          {:ok, String.t()} | {:error, Bls.keystore_error()}
+  def encode(privkey, password, path \\ "", kdf_params \\ @default_kdf_params) do
+    Bls.encrypt_keystore(privkey, sanitize_password(password), path, kdf_params)
   end
 
-  defp derive_key!(%{"function" => "pbkdf2", "params" => params}, password) do
-    %{"dklen" => dklen, "salt" => hex_salt, "c" => c, "prf" => "hmac-sha256"} = params
-    salt = parse_binary!(hex_salt)
-
-    if byte_size(salt) != @salt_bytes do
-      raise "Invalid salt size: #{byte_size(salt)}"
+  defp decode_json(json) do
+    case Jason.decode(json) do
+      {:ok, decoded_json} when is_map(decoded_json) -> {:ok, decoded_json}
+      {:ok, _} -> {:error, {:invalid_keystore, "The keystore isn't a JSON object"}}
+      {:error, _} = error -> error
     end
-
-    :crypto.pbkdf2_hmac(:sha256, password, salt, c, dklen)
-  end
-
-  defp decrypt_secret(derived_key, iv, cipher_message) do
-    <<key::binary-size(16), _::binary>> = derived_key
-    :crypto.crypto_one_time(:aes_128_ctr, key, iv, cipher_message, false)
   end
 
-  defp verify_password!(derived_key, cipher_message, checksum_message) do
-    dk_slice = derived_key |> binary_part(16, 16)
+  defp validate_version(4), do: :ok
+  defp validate_version(version), do: {:error, {:unsupported_version, version}}
 
-    pre_image = dk_slice <> cipher_message
-    checksum = :crypto.hash(:sha256, pre_image)
-
-    if checksum != checksum_message do
-      raise "Invalid password"
+  # The path is only informative, but it must be a valid EIP-2334 path if present
+  defp validate_path(path) when is_binary(path) and byte_size(path) > 0 do
+    case Bls.parse_derivation_path(path) do
+      {:ok, _} -> :ok
+      {:error, reason} -> {:error, {:invalid_path, reason}}
     end
   end
 
-  defp parse_checksum!(%{"function" => "sha256", "message" => hex_message}) do
-    message = parse_binary!(hex_message)
-
-    if byte_size(message) != @checksum_message_size do
-      "Invalid checksum size: #{byte_size(message)}"
-    end
-
-    message
-  end
+  defp validate_path(_), do: :ok
 
-  defp parse_cipher!(%{
-         "function" => "aes-128-ctr",
-         "params" => %{"iv" => hex_iv},
-         "message" => hex_message
-       }) do
-    iv = parse_binary!(hex_iv)
+  defp validate_pubkey(nil, _derived_pubkey), do: :ok
 
-    if byte_size(iv) != @iv_size do
-      raise "Invalid IV size: #{byte_size(iv)}"
+  defp validate_pubkey(hex_pubkey, derived_pubkey) when is_binary(hex_pubkey) do
+    with {:ok, pubkey} <- Base.decode16(hex_pubkey, case: :mixed),
+         true <- pubkey == derived_pubkey do
+      :ok
+    else
+      _ -> {:error, :pubkey_mismatch}
     end
-
-    {iv, parse_binary!(hex_message)}
   end
 
-  defp parse_binary!(hex), do: Base.decode16!(hex, case: :mixed)
+  defp validate_pubkey(_hex_pubkey, _derived_pubkey), do: {:error, :pubkey_mismatch}
 
   defp sanitize_password(password),
     do: password |> String.normalize(:nfkd) |> String.replace(~r/[\x00-\x1f\x80-\x9f\x7f]/, "")
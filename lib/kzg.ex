@@ -2,18 +2,57 @@ defmodule Kzg do
   @moduledoc """
   KZG functions
   """
-  use Rustler, otp_app: :lambda_ethereum_consensus, crate: "kzg_nif"
+  use Rustler,
+    otp_app: :lambda_ethereum_consensus,
+    crate: "kzg_nif",
+    load_data_fun: {Kzg, :load_data}
 
   @type commitment :: <<_::384>>
   @type proof :: <<_::768>>
+  @type cell :: <<_::16_384>>
+  @typedoc """
+  Length errors carry the received and expected lengths. See `docs/nif_errors.md`.
+  """
+  @type error ::
+          {:blob_length, non_neg_integer(), 131_072}
+          | {:commitment_length, non_neg_integer(), 48}
+          | {:proof_length, non_neg_integer(), 48}
+          | {:field_element_length, non_neg_integer(), 32}
+          | {:cell_length, non_neg_integer(), 2048}
+          | {:length_mismatch, String.t()}
+          | {:invalid_trusted_setup, String.t()}
+          | {:invalid_blob_encoding, String.t()}
+          | {:kzg_error, String.t()}
+
+  @doc false
+  # Load data of the NIF: the number of verification workers and the path of the
+  # trusted setup, if one is configured.
+  @spec load_data() :: {pos_integer() | nil, String.t() | nil}
+  def load_data() do
+    trusted_setup =
+      Application.get_env(:lambda_ethereum_consensus, __MODULE__, [])
+      |> Keyword.get(:trusted_setup)
+
+    {VerificationPool.workers(), trusted_setup}
+  end
+
+  @doc """
+  Replaces the trusted setup used by all the functions with the one in the file
+  at `path`, in the c-kzg text format. On error, the current one is kept. When
+  no file is configured, the mainnet setup embedded in c-kzg is used.
+  """
+  @spec load_trusted_setup(Path.t()) :: :ok | {:error, error()}
+  def load_trusted_setup(_path) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
 
-  @spec blob_to_kzg_commitment(Types.blob()) :: {:ok, commitment()} | {:error, binary()}
+  @spec blob_to_kzg_commitment(Types.blob()) :: {:ok, commitment()} | {:error, error()}
   def blob_to_kzg_commitment(_blob) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
   @spec compute_kzg_proof(Types.blob(), Types.bytes32()) ::
-          {:ok, {proof(), Types.bytes32()}} | {:error, binary()}
+          {:ok, {proof(), Types.bytes32()}} | {:error, error()}
   def compute_kzg_proof(_blob, _z) do
     :erlang.nif_error(:nif_not_loaded)
   end
@@ -24,37 +63,152 @@ defmodule Kzg do
           Types.bytes32(),
           proof()
         ) ::
-          {:ok, boolean} | {:error, binary()}
+          {:ok, boolean} | {:error, error()}
   def verify_kzg_proof(_kzg_commitment, _z, _y, _kzg_proof) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
   @spec compute_blob_kzg_proof(Types.blob(), commitment()) ::
-          {:ok, proof()} | {:error, binary()}
+          {:ok, proof()} | {:error, error()}
   def compute_blob_kzg_proof(_blob, _kzg_commitment) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
   @spec verify_blob_kzg_proof(Types.blob(), commitment(), proof()) ::
-          {:ok, boolean} | {:error, binary()}
+          {:ok, boolean} | {:error, error()}
   def verify_blob_kzg_proof(_blob, _kzg_commitment, _kzg_proof) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
+  @doc """
+  Verifies the KZG proof of an SSZ-encoded `Types.BlobSidecar` against its blob
+  and commitment, reading them straight from the encoding. The rest of the
+  sidecar, including its inclusion proof, is checked by `Ssz.decode_blob_sidecar/2`.
+  """
+  @spec verify_blob_sidecar_kzg_proof(binary) :: {:ok, boolean} | {:error, error()}
+  def verify_blob_sidecar_kzg_proof(_sidecar) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
   @spec verify_blob_kzg_proof_batch(
           list(Types.blob()),
           list(commitment()),
           list(proof())
         ) ::
-          {:ok, boolean} | {:error, binary()}
+          {:ok, boolean} | {:error, error()}
   def verify_blob_kzg_proof_batch(_blobs, _kzg_commitments, _kzg_proofs) do
     :erlang.nif_error(:nif_not_loaded)
   end
 
+  @doc """
+  Computes the commitment and proof of each blob. Blobs are processed in
+  parallel, in a native thread pool separate from `VerificationPool`.
+  """
+  @spec compute_blob_commitments_and_proofs(list(Types.blob())) ::
+          {:ok, {list(commitment()), list(proof())}} | {:error, error()}
+  def compute_blob_commitments_and_proofs(_blobs) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  ##### PeerDAS (EIP-7594)
+
+  @doc """
+  Computes the cells of the extended blob, in order.
+  """
+  @spec compute_cells(Types.blob()) :: {:ok, list(cell())} | {:error, error()}
+  def compute_cells(_blob) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @spec compute_cells_and_kzg_proofs(Types.blob()) ::
+          {:ok, {list(cell()), list(proof())}} | {:error, error()}
+  def compute_cells_and_kzg_proofs(_blob) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Verifies that each cell, at the given index of its extended blob, matches the
+  commitment of that blob. All the lists must have the same length.
+  """
+  @spec verify_cell_kzg_proof_batch(
+          list(commitment()),
+          list(non_neg_integer()),
+          list(cell()),
+          list(proof())
+        ) :: {:ok, boolean} | {:error, error()}
+  def verify_cell_kzg_proof_batch(_kzg_commitments, _cell_indices, _cells, _kzg_proofs) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Recovers all the cells and proofs of an extended blob from at least half of
+  its cells, given with their indices.
+  """
+  @spec recover_cells_and_kzg_proofs(list(non_neg_integer()), list(cell())) ::
+          {:ok, {list(cell()), list(proof())}} | {:error, error()}
+  def recover_cells_and_kzg_proofs(_cell_indices, _cells) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  ##### Packing
+
+  @doc """
+  Packs arbitrary data into as few canonical blobs as possible. The data is
+  prefixed with its length and stored 31 bytes per field element, so every
+  element is below the BLS modulus.
+  """
+  @spec pack_blobs(binary) :: {:ok, list(Types.blob())} | {:error, error()}
+  def pack_blobs(_data) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @doc """
+  Returns the data packed into the blobs by `pack_blobs/1`.
+  """
+  @spec unpack_blobs(list(Types.blob())) :: {:ok, binary} | {:error, error()}
+  def unpack_blobs(_blobs) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  # Asynchronous versions, which run in the `VerificationPool` and send
+  # `{job, result}` to the caller, where `result` is what the synchronous version returns.
+
+  @spec verify_blob_kzg_proof_async(Types.blob(), commitment(), proof()) ::
+          VerificationPool.job()
+  def verify_blob_kzg_proof_async(_blob, _kzg_commitment, _kzg_proof) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
+  @spec verify_blob_kzg_proof_batch_async(
+          list(Types.blob()),
+          list(commitment()),
+          list(proof())
+        ) :: VerificationPool.job()
+  def verify_blob_kzg_proof_batch_async(_blobs, _kzg_commitments, _kzg_proofs) do
+    :erlang.nif_error(:nif_not_loaded)
+  end
+
   ################
   ### Wrappers ###
   ################
 
+  @spec blobs_bundle(list(Types.blob())) :: {:ok, Types.BlobsBundle.t()} | {:error, error()}
+  def blobs_bundle(blobs) do
+    with {:ok, {commitments, proofs}} <- compute_blob_commitments_and_proofs(blobs) do
+      {:ok, %Types.BlobsBundle{blobs: blobs, commitments: commitments, proofs: proofs}}
+    end
+  end
+
+  @doc """
+  Packs the data into blobs, with their commitments and proofs.
+  """
+  @spec blobs_bundle_from_data(binary) :: {:ok, Types.BlobsBundle.t()} | {:error, error()}
+  def blobs_bundle_from_data(data) do
+    with {:ok, blobs} <- pack_blobs(data) do
+      blobs_bundle(blobs)
+    end
+  end
+
   @spec blob_kzg_proof_batch_valid?(
           list(Types.blob()),
           list(commitment()),
@@ -0,0 +1,83 @@
+defmodule LambdaEthereumConsensus.Beacon.LightClient do
+  @moduledoc """
+  Builds the data served to light clients, as in the light client full node
+  specs. Bootstraps and updates are built natively, along with their proofs.
+  """
+
+  alias Types.LightClientBootstrap
+  alias Types.LightClientFinalityUpdate
+  alias Types.LightClientOptimisticUpdate
+  alias Types.LightClientUpdate
+  alias Types.SignedBeaconBlock
+  alias Types.StateInfo
+
+  @doc """
+  Builds the bootstrap of a block, given its post-state.
+  """
+  @spec create_bootstrap(StateInfo.t(), SignedBeaconBlock.t()) ::
+          {:ok, LightClientBootstrap.t()} | {:error, Ssz.error()}
+  def create_bootstrap(%StateInfo{} = state_info, %SignedBeaconBlock{} = block) do
+    with {:ok, encoded_block} <- Ssz.to_ssz(block),
+         {:ok, bootstrap} <-
+           Ssz.build_light_client_bootstrap(state_info.encoded, encoded_block) do
+      Ssz.from_ssz(bootstrap, LightClientBootstrap)
+    end
+  end
+
+  @doc """
+  Builds the update signed by the sync aggregate of `block`, attesting to its
+  parent `attested_block`. Each block comes with its post-state. `finalized_block`
+  is the block of the attested state's finalized checkpoint, or `nil` if unknown.
+  """
+  @spec create_update(
+          StateInfo.t(),
+          SignedBeaconBlock.t(),
+          StateInfo.t(),
+          SignedBeaconBlock.t(),
+          SignedBeaconBlock.t() | nil
+        ) :: {:ok, LightClientUpdate.t()} | {:error, Ssz.error()}
+  def create_update(state_info, block, attested_state_info, attested_block, finalized_block) do
+    with {:ok, encoded_block} <- Ssz.to_ssz(block),
+         {:ok, encoded_attested_block} <- Ssz.to_ssz(attested_block),
+         {:ok, encoded_finalized_block} <- encode_optional(finalized_block),
+         {:ok, update} <-
+           Ssz.build_light_client_update(
+             state_info.encoded,
+             encoded_block,
+             attested_state_info.encoded,
+             encoded_attested_block,
+             encoded_finalized_block
+           ) do
+      Ssz.from_ssz(update, LightClientUpdate)
+    end
+  end
+
+  @doc """
+  Returns the finality update of an update.
+  """
+  @spec create_finality_update(LightClientUpdate.t()) :: LightClientFinalityUpdate.t()
+  def create_finality_update(%LightClientUpdate{} = update) do
+    %LightClientFinalityUpdate{
+      attested_header: update.attested_header,
+      finalized_header: update.finalized_header,
+      finality_branch: update.finality_branch,
+      sync_aggregate: update.sync_aggregate,
+      signature_slot: update.signature_slot
+    }
+  end
+
+  @doc """
+  Returns the optimistic update of an update.
+  """
+  @spec create_optimistic_update(LightClientUpdate.t()) :: LightClientOptimisticUpdate.t()
+  def create_optimistic_update(%LightClientUpdate{} = update) do
+    %LightClientOptimisticUpdate{
+      attested_header: update.attested_header,
+      sync_aggregate: update.sync_aggregate,
+      signature_slot: update.signature_slot
+    }
+  end
+
+  defp encode_optional(nil), do: {:ok, nil}
+  defp encode_optional(block), do: Ssz.to_ssz(block)
+end
@@ -91,7 +91,7 @@ defmodule LambdaEthereumConsensus.Beacon.PendingBlocks do
 
       {:error, reason} ->
         Logger.error(
-          "[Pending Blocks] Failed to get pending blocks to process. Reason: #{reason}"
+          "[Pending Blocks] Failed to get pending blocks to process. Reason: #{inspect(reason)}"
         )
 
         store
@@ -100,8 +100,12 @@ defmodule LambdaEthereumConsensus.Beacon.PendingBlocks do
 
   @doc """
   Process incoming blobs if the block can be processed does so immediately.
+  Blobs may come with the root of their block, as in `Blobs.add_blobs/1`.
   """
-  @spec process_blobs(Store.t(), {:ok, [Types.BlobSidecar.t()]}) :: {:ok, Store.t()}
+  @spec process_blobs(
+          Store.t(),
+          {:ok, [Types.BlobSidecar.t() | {Types.BlobSidecar.t(), Types.root()}]}
+        ) :: {:ok, Store.t()}
   def process_blobs(store, {:ok, blobs}) do
     blobs
     |> Blobs.add_blobs()
@@ -196,7 +200,7 @@ defmodule LambdaEthereumConsensus.Beacon.PendingBlocks do
 
           {:error, reason, store} ->
             Logger.error(
-              "[PendingBlocks] Saving block as invalid after ForkChoice.on_block/2 error: #{reason}",
+              "[PendingBlocks] Saving block as invalid after ForkChoice.on_block/2 error: #{inspect(reason)}",
               log_md
             )
 
@@ -92,8 +92,24 @@ defmodule LambdaEthereumConsensus.Execution.ExecutionClient do
   """
   @spec valid_versioned_hashes?(NewPayloadRequest.t()) ::
           {:ok, execution_status()} | {:error, String.t()}
-  # TODO: implement
-  def valid_versioned_hashes?(_new_payload_request), do: {:ok, :valid}
+  def valid_versioned_hashes?(%NewPayloadRequest{versioned_hashes: nil}), do: {:ok, :valid}
+
+  def valid_versioned_hashes?(%NewPayloadRequest{
+        execution_payload: execution_payload,
+        versioned_hashes: versioned_hashes
+      }) do
+    case Ssz.verify_blob_versioned_hashes(execution_payload.transactions, versioned_hashes) do
+      {:ok, true} ->
+        {:ok, :valid}
+
+      {:ok, false} ->
+        {:ok, :invalid}
+
+      {:error, reason} ->
+        Logger.warning("Invalid blob transaction in payload: #{inspect(reason)}")
+        {:ok, :invalid}
+    end
+  end
 
   @doc """
   Same as `notify_new_payload`, but with additional checks.
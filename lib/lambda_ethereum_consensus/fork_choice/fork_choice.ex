@@ -76,7 +76,10 @@ defmodule LambdaEthereumConsensus.ForkChoice do
         |> then(&{:ok, &1})
 
       {:error, reason} ->
-        Logger.error("[Fork choice] Failed to add block: #{reason}", slot: slot, root: block_root)
+        Logger.error("[Fork choice] Failed to add block: #{inspect(reason)}",
+          slot: slot,
+          root: block_root
+        )
         {:error, reason, store}
     end
   end
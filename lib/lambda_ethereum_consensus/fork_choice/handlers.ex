@@ -206,9 +206,8 @@ defmodule LambdaEthereumConsensus.ForkChoice.Handlers do
     # Make it a task so it runs concurrently with the state transition
     payload_verification_task =
       Task.async(fn ->
-        versioned_hashes =
-          block.body.blob_kzg_commitments
-          |> Enum.map(&Misc.kzg_commitment_to_versioned_hash/1)
+        {:ok, versioned_hashes} =
+          Ssz.kzg_commitments_to_versioned_hashes(block.body.blob_kzg_commitments)
 
         %NewPayloadRequest{
           execution_payload: payload,
@@ -445,5 +444,5 @@ defmodule LambdaEthereumConsensus.ForkChoice.Handlers do
   defp handle_verify_payload_result({:ok, :invalid}), do: {:error, "Invalid execution payload"}
 
   defp handle_verify_payload_result({:error, error}),
-    do: {:error, "Error when calling execution client: #{error}"}
+    do: {:error, "Error when calling execution client: #{inspect(error)}"}
 end
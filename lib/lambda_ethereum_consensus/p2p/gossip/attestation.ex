@@ -31,7 +31,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.Attestation do
   def handle_gossip_message(topic, msg_id, message) do
     subnet_id = extract_subnet_id(topic)
 
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
          {:ok, attestation} <- Ssz.from_ssz(uncompressed, Types.Attestation) do
       # TODO: (#1291) validate before accepting
       Libp2pPort.validate_message(msg_id, :accept)
@@ -55,7 +55,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.Attestation do
   def publish(subnet_id, %Types.Attestation{} = attestation) do
     topic = topic(subnet_id)
     {:ok, encoded} = SszEx.encode(attestation, Types.Attestation)
-    {:ok, message} = :snappyer.compress(encoded)
+    {:ok, message} = Snappy.compress_raw(encoded)
     Libp2pPort.publish(topic, message)
   end
 
@@ -63,7 +63,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.Attestation do
     fork_context = ForkChoice.get_fork_digest() |> Base.encode16(case: :lower)
     topic = "/eth2/#{fork_context}/beacon_aggregate_and_proof/ssz_snappy"
     {:ok, encoded} = SszEx.encode(signed_aggregate, Types.SignedAggregateAndProof)
-    {:ok, message} = :snappyer.compress(encoded)
+    {:ok, message} = Snappy.compress_raw(encoded)
     Libp2pPort.publish(topic, message)
   end
 
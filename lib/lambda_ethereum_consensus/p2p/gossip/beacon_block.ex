@@ -5,6 +5,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.BeaconBlock do
   alias LambdaEthereumConsensus.Beacon.PendingBlocks
   alias LambdaEthereumConsensus.ForkChoice
   alias LambdaEthereumConsensus.Libp2pPort
+  alias LambdaEthereumConsensus.P2P
   alias LambdaEthereumConsensus.P2P.Gossip.Handler
   alias Types.SignedBeaconBlock
 
@@ -17,7 +18,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.BeaconBlock do
 
   @impl true
   def handle_gossip_message(store, _topic, msg_id, message) do
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
          {:ok, signed_block} <- Ssz.from_ssz(uncompressed, SignedBeaconBlock),
          :ok <- validate(signed_block, store) do
       Logger.info("[Gossip] Block received, block.slot: #{signed_block.message.slot}.")
@@ -1,10 +1,16 @@
 defmodule LambdaEthereumConsensus.P2P.Gossip.BlobSideCar do
   @moduledoc """
   This module handles blob sidecar gossipsub topics.
+
+  A sidecar is validated with two native calls on its encoding: one to ssz_nif,
+  which decodes it and checks its inclusion proof, and one to kzg_nif, which
+  checks its KZG proof with the trusted setup it loaded. The setup can't be
+  shared between the two libraries, so they can't be merged into a single call.
   """
   alias LambdaEthereumConsensus.Beacon.PendingBlocks
   alias LambdaEthereumConsensus.ForkChoice
   alias LambdaEthereumConsensus.Libp2pPort
+  alias LambdaEthereumConsensus.P2P
   alias LambdaEthereumConsensus.P2P.Gossip.Handler
 
   require Logger
@@ -13,13 +19,12 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.BlobSideCar do
 
   @impl Handler
   def handle_gossip_message(store, _topic, msg_id, message) do
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
-         {:ok, %Types.BlobSidecar{index: blob_index} = blob} <-
-           Ssz.from_ssz(uncompressed, Types.BlobSidecar) do
-      Logger.debug("[Gossip] Blob sidecar received, with index #{blob_index}")
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
+         {:ok, {blob, block_root}} <- decode_and_validate(uncompressed) do
+      Logger.debug("[Gossip] Blob sidecar received, with index #{blob.index}")
       Libp2pPort.validate_message(msg_id, :accept)
       # TODO: (#1406) Enhance the API to reduce unnecessary wrappers (:ok + list)
-      PendingBlocks.process_blobs(store, {:ok, [blob]}) |> then(&elem(&1, 1))
+      PendingBlocks.process_blobs(store, {:ok, [{blob, block_root}]}) |> then(&elem(&1, 1))
     else
       {:error, reason} ->
         Logger.warning("[Gossip] Blob rejected, reason: #{inspect(reason)}")
@@ -28,6 +33,17 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.BlobSideCar do
     end
   end
 
+  defp decode_and_validate(uncompressed) do
+    with {:ok, {blob, block_root, true}} <- Ssz.decode_blob_sidecar(uncompressed),
+         {:ok, true} <- Kzg.verify_blob_sidecar_kzg_proof(uncompressed) do
+      {:ok, {blob, block_root}}
+    else
+      {:ok, {_blob, _block_root, false}} -> {:error, :invalid_inclusion_proof}
+      {:ok, false} -> {:error, :invalid_kzg_proof}
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
   @spec subscribe_to_topics() :: :ok | {:error, String.t()}
   def subscribe_to_topics() do
     Enum.each(topics(), fn topic ->
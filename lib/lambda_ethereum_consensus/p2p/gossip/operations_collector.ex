@@ -5,6 +5,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.OperationsCollector do
 
   alias LambdaEthereumConsensus.ForkChoice
   alias LambdaEthereumConsensus.Libp2pPort
+  alias LambdaEthereumConsensus.P2P
   alias LambdaEthereumConsensus.P2P.Gossip.Handler
   alias LambdaEthereumConsensus.StateTransition.Misc
   alias LambdaEthereumConsensus.Store.Db
@@ -169,7 +170,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.OperationsCollector do
         _msg_id,
         message
       ) do
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
          {:ok,
           %Types.SignedAggregateAndProof{message: %Types.AggregateAndProof{aggregate: aggregate}}} <-
            Ssz.from_ssz(uncompressed, Types.SignedAggregateAndProof) do
@@ -190,7 +191,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.OperationsCollector do
         _msg_id,
         message
       ) do
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
          {:ok, %Types.SignedVoluntaryExit{} = signed_voluntary_exit} <-
            Ssz.from_ssz(uncompressed, Types.SignedVoluntaryExit) do
       handle_msg({:voluntary_exit, signed_voluntary_exit})
@@ -202,7 +203,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.OperationsCollector do
         _msg_id,
         message
       ) do
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
          {:ok, %Types.ProposerSlashing{} = proposer_slashing} <-
            Ssz.from_ssz(uncompressed, Types.ProposerSlashing) do
       handle_msg({:proposer_slashing, proposer_slashing})
@@ -214,7 +215,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.OperationsCollector do
         _msg_id,
         message
       ) do
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
          {:ok, %Types.AttesterSlashing{} = attester_slashing} <-
            Ssz.from_ssz(uncompressed, Types.AttesterSlashing) do
       handle_msg({:attester_slashing, attester_slashing})
@@ -226,7 +227,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.OperationsCollector do
         _msg_id,
         message
       ) do
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
          {:ok, %Types.SignedBLSToExecutionChange{} = bls_to_execution_change} <-
            Ssz.from_ssz(uncompressed, Types.SignedBLSToExecutionChange) do
       handle_msg({:bls_to_execution_change, bls_to_execution_change})
@@ -238,7 +239,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.OperationsCollector do
         msg_id,
         message
       ) do
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
          {:ok, %Types.SignedContributionAndProof{} = contribution_and_proof} <-
            Ssz.from_ssz(uncompressed, Types.SignedContributionAndProof) do
       # TODO: (#1291) validate before accepting
@@ -39,7 +39,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.SyncCommittee do
   def handle_gossip_message(topic, msg_id, message) do
     subnet_id = extract_subnet_id(topic)
 
-    with {:ok, uncompressed} <- :snappyer.decompress(message),
+    with {:ok, uncompressed} <- P2P.Utils.decompress_gossip(message),
          {:ok, sync_committee_msg} <- Ssz.from_ssz(uncompressed, Types.SyncCommitteeMessage) do
       # TODO: (#1291) validate before accepting
       Libp2pPort.validate_message(msg_id, :accept)
@@ -56,7 +56,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.SyncCommittee do
       topic = topic(subnet_id)
 
       {:ok, encoded} = SszEx.encode(sync_committee_msg, Types.SyncCommitteeMessage)
-      {:ok, message} = :snappyer.compress(encoded)
+      {:ok, message} = Snappy.compress_raw(encoded)
       Libp2pPort.publish(topic, message)
     end
 
@@ -68,7 +68,7 @@ defmodule LambdaEthereumConsensus.P2P.Gossip.SyncCommittee do
     fork_context = ForkChoice.get_fork_digest() |> Base.encode16(case: :lower)
     topic = "/eth2/#{fork_context}/sync_committee_contribution_and_proof/ssz_snappy"
     {:ok, encoded} = SszEx.encode(signed_contribution, Types.SignedContributionAndProof)
-    {:ok, message} = :snappyer.compress(encoded)
+    {:ok, message} = Snappy.compress_raw(encoded)
     Libp2pPort.publish(topic, message)
   end
 
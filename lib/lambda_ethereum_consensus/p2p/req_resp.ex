@@ -3,7 +3,6 @@ defmodule LambdaEthereumConsensus.P2P.ReqResp do
   Functions for encoding and decoding Req/Resp domain messages.
   """
   alias LambdaEthereumConsensus.ForkChoice
-  alias LambdaEthereumConsensus.P2P
 
   defmodule Error do
     @moduledoc """
@@ -37,95 +36,96 @@ defmodule LambdaEthereumConsensus.P2P.ReqResp do
 
   @spec encode_response([response_payload()]) :: binary()
   def encode_response(responses) do
-    Enum.map_join(responses, fn
-      {:ok, {response, context_bytes}} -> encode_ok(response, context_bytes)
-      {:error, {code, message}} -> encode_error(code, message)
+    responses
+    |> Enum.map(fn
+      {:ok, {response, context_bytes}} -> to_chunk(<<0>>, context_bytes, response)
+      {:error, {code, message}} -> error_chunk(code, message)
     end)
+    |> encode_chunks()
   end
 
   @spec encode_ok(encodable(), context_bytes()) :: binary()
-  def encode_ok(response, context_bytes \\ <<>>)
-
-  def encode_ok(%ssz_schema{} = response, context_bytes),
-    do: encode(<<0>>, context_bytes, {response, ssz_schema})
-
-  def encode_ok({response, ssz_schema}, context_bytes),
-    do: encode(<<0>>, context_bytes, {response, ssz_schema})
+  def encode_ok(response, context_bytes \\ <<>>),
+    do: encode_chunks([to_chunk(<<0>>, context_bytes, response)])
 
   @spec encode_error(Error.t()) :: binary()
   def encode_error(%Error{code: code, message: message}), do: encode_error(code, message)
 
   @spec encode_error(1..255, binary()) :: binary()
   def encode_error(status_code, error_message),
-    do: encode(<<status_code>>, <<>>, {error_message, TypeAliases.error_message()})
+    do: encode_chunks([error_chunk(status_code, error_message)])
 
-  defp encode(result, context_bytes, {response, ssz_schema}) do
-    {:ok, ssz_response} = SszEx.encode(response, ssz_schema)
-    size_header = byte_size(ssz_response) |> P2P.Utils.encode_varint()
-    {:ok, ssz_snappy_response} = Snappy.compress(ssz_response)
-    Enum.join([result, context_bytes, size_header, ssz_snappy_response])
+  defp error_chunk(code, message),
+    do: to_chunk(<<code>>, <<>>, {message, TypeAliases.error_message()})
+
+  defp to_chunk(<<code>>, context_bytes, response) do
+    {:ok, ssz_response} = encode_ssz(response)
+    {code, context_bytes, ssz_response}
   end
 
+  defp encode_chunks(chunks) do
+    {:ok, encoded} = Snappy.encode_response_chunks(chunks)
+    encoded
+  end
+
+  defp encode_ssz(%ssz_schema{} = value), do: SszEx.encode(value, ssz_schema)
+  defp encode_ssz({value, ssz_schema}), do: SszEx.encode(value, ssz_schema)
+
   @spec encode_request(encodable()) :: binary()
-  def encode_request(%ssz_schema{} = request), do: encode_request({request, ssz_schema})
-  def encode_request({request, ssz_schema}), do: encode(<<>>, <<>>, {request, ssz_schema})
+  def encode_request(request) do
+    {:ok, ssz_request} = encode_ssz(request)
+    {:ok, encoded} = Snappy.encode_request(ssz_request)
+    encoded
+  end
 
   ## Decoding
 
   @spec decode_response(binary(), SszEx.schema()) ::
-          {:ok, [any()]} | {:error, String.t()} | {:error, Error.t()}
-  def decode_response(response_chunk, ssz_schema) do
-    with {:ok, chunks} <- split_response(response_chunk) do
-      # TODO: handle errors
-      chunks
-      |> Stream.map(&decode_request(&1, ssz_schema))
-      |> Enum.flat_map(fn
-        {:ok, block} -> [block]
-        {:error, _reason} -> []
-      end)
-      |> then(fn
-        [] -> {:error, "all blocks decoding failed"}
-        blocks -> {:ok, blocks}
-      end)
-    end
-  end
+          {:ok, [any()]} | {:error, String.t() | Snappy.error()} | {:error, Error.t()}
+  def decode_response(<<>>, _ssz_schema), do: {:error, "unexpected EOF"}
 
-  @spec split_response(binary) :: {:ok, [binary()]} | {:error, String.t()} | {:error, Error.t()}
-  def split_response(response_chunk) do
+  def decode_response(response, ssz_schema) do
     # TODO: the fork_context should be computed depending on the block's slot
     fork_context = ForkChoice.get_fork_digest()
+    {min_length, max_length} = length_bounds(ssz_schema)
 
-    case response_chunk do
-      <<>> ->
-        {:error, "unexpected EOF"}
-
-      # TODO: take into account multiple chunks with intermixed errors
-      <<0, ^fork_context::binary-size(4)>> <> rest ->
-        chunks = rest |> :binary.split(<<0, fork_context::binary-size(4)>>, [:global])
-        {:ok, chunks}
+    with {:ok, chunks} <-
+           Snappy.decode_response_chunks(response, 4, min_length, max_length) do
+      chunks
+      |> Enum.map(fn
+        {0, ^fork_context, ssz} -> SszEx.decode(ssz, ssz_schema)
+        {0, context, _} -> {:error, "wrong context: #{Base.encode16(context)}"}
+        {code, _, message} -> {:error, %Error{code: code, message: message}}
+      end)
+      |> collect_chunks()
+    end
+  end
 
-      <<0, wrong_context::binary-size(4)>> <> _ ->
-        {:error, "wrong context: #{Base.encode16(wrong_context)}"}
+  # Chunks that fail to decode are skipped, and the stream ends at the first
+  # error chunk
+  defp collect_chunks(results) do
+    {results, rest} = Enum.split_while(results, &(not match?({:error, %Error{}}, &1)))
+    decoded = for {:ok, value} <- results, do: value
 
-      <<error_code>> <> message ->
-        decode_error(error_code, message)
+    case {decoded, rest} do
+      {[], [error | _]} -> error
+      {[], []} when results != [] -> {:error, "all blocks decoding failed"}
+      _ -> {:ok, decoded}
     end
   end
 
-  defp decode_error_message(error_message),
-    do: decode_request(error_message, TypeAliases.error_message())
-
   @doc """
   Decodes a `request` according to an SSZ schema.
   """
-  @spec decode_request(binary(), SszEx.schema()) :: {:ok, any()} | {:error, String.t()}
+  @spec decode_request(binary(), SszEx.schema()) ::
+          {:ok, any()} | {:error, String.t() | Snappy.error()}
   def decode_request(chunk, ssz_schema) do
-    # TODO: limit size
-    {_size, rest} = P2P.Utils.decode_varint(chunk)
+    {min_length, max_length} = length_bounds(ssz_schema)
 
-    with {:ok, decompressed} <- Snappy.decompress(rest),
-         {:ok, decoded} <- SszEx.decode(decompressed, ssz_schema) do
-      {:ok, decoded}
+    # The length prefix bounds decompression, so a small chunk can't expand
+    # beyond what the peer announced
+    with {:ok, ssz} <- Snappy.decode_request(chunk, min_length, max_length) do
+      SszEx.decode(ssz, ssz_schema)
     end
   end
 
@@ -134,16 +134,41 @@ defmodule LambdaEthereumConsensus.P2P.ReqResp do
   """
   @spec decode_response_chunk(binary(), SszEx.schema()) ::
           {:ok, any()}
-          | {:error, String.t()}
+          | {:error, String.t() | Snappy.error()}
           | {:error, Error.t()}
-  def decode_response_chunk(<<0>> <> chunk, ssz_schema), do: decode_request(chunk, ssz_schema)
+  def decode_response_chunk(response_chunk, ssz_schema) do
+    {min_length, max_length} = length_bounds(ssz_schema)
+
+    case Snappy.decode_response_chunks(response_chunk, 0, min_length, max_length) do
+      {:ok, [{0, <<>>, ssz}]} -> SszEx.decode(ssz, ssz_schema)
+      {:ok, [{code, _, message}]} -> {:error, %Error{code: code, message: message}}
+      {:ok, chunks} -> {:error, "expected a single chunk, got #{length(chunks)}"}
+      {:error, reason} -> {:error, reason}
+    end
+  end
 
-  def decode_response_chunk(<<code>> <> message, _), do: decode_error(code, message)
+  # Lengths the SSZ payload of a message can have. Fixed-size types have a
+  # single valid length, and others are bounded by their maximum SSZ length
+  # and the maximum payload size.
+  defp length_bounds(ssz_schema) do
+    case fixed_length(ssz_schema) do
+      nil -> {0, min(SszEx.Utils.max_size(ssz_schema), ChainSpec.get("MAX_PAYLOAD_SIZE"))}
+      length -> {length, length}
+    end
+  end
 
-  @spec decode_error(1..255, binary()) :: {:error, String.t()} | {:error, Error.t()}
-  defp decode_error(code, encoded_message) do
-    with {:ok, error_message} <- decode_error_message(encoded_message) do
-      {:error, %Error{code: code, message: error_message}}
+  defp fixed_length(ssz_schema) when is_atom(ssz_schema) do
+    case Ssz.fixed_length(ssz_schema) do
+      {:ok, length} -> length
+      {:error, _} -> fixed_length_ex(ssz_schema)
     end
   end
+
+  defp fixed_length(ssz_schema), do: fixed_length_ex(ssz_schema)
+
+  defp fixed_length_ex(ssz_schema) do
+    if SszEx.Utils.variable_size?(ssz_schema),
+      do: nil,
+      else: SszEx.Utils.get_fixed_size(ssz_schema)
+  end
 end
@@ -11,6 +11,14 @@ defmodule LambdaEthereumConsensus.P2P.Utils do
     |> IO.iodata_to_binary()
   end
 
+  @doc """
+  Decompresses a gossip message, which is raw snappy, rejecting it if it's longer
+  than `MAX_PAYLOAD_SIZE`.
+  """
+  @spec decompress_gossip(binary()) :: {:ok, binary()} | {:error, Snappy.error()}
+  def decompress_gossip(message),
+    do: Snappy.decompress_raw(message, ChainSpec.get("MAX_PAYLOAD_SIZE"))
+
   @spec decode_varint(binary()) :: {non_neg_integer(), binary()}
   # PERF: use `Protobuf.Wire.Varint.defdecoderp` macro for this
   # TODO: limit amount of recursive calls
@@ -9,6 +9,7 @@ defmodule LambdaEthereumConsensus.StateTransition.Operations do
   alias LambdaEthereumConsensus.StateTransition.Misc
   alias LambdaEthereumConsensus.StateTransition.Mutators
   alias LambdaEthereumConsensus.StateTransition.Predicates
+  alias LambdaEthereumConsensus.StateTransition.PubkeyCache
   alias LambdaEthereumConsensus.Utils
   alias LambdaEthereumConsensus.Utils.BitList
   alias LambdaEthereumConsensus.Utils.BitVector
@@ -702,34 +703,40 @@ defmodule LambdaEthereumConsensus.StateTransition.Operations do
   end
 
   @spec validate_attestation(BeaconState.t(), Attestation.t()) :: :ok | {:error, String.t()}
-  def validate_attestation(
-        state,
-        %Attestation{data: data, aggregation_bits: aggregation_bits} = attestation
-      ) do
+  def validate_attestation(state, attestation) do
+    with {:ok, indexed_attestation} <- get_validated_indexed_attestation(state, attestation) do
+      check_valid_indexed_attestation(state, indexed_attestation)
+    end
+  end
+
+  defp get_validated_indexed_attestation(
+         state,
+         %Attestation{data: data, aggregation_bits: aggregation_bits} = attestation
+       ) do
     with :ok <- check_valid_target_epoch(data, state),
          :ok <- check_epoch_matches(data),
          :ok <- check_valid_slot_range(data, state),
          :ok <- check_data_index_zero(data),
          {:ok, committee_offset} <- check_committee_indices(attestation, state),
-         :ok <- check_matching_aggregation_bits_length(aggregation_bits, committee_offset),
-         {:ok, indexed_attestation} <- Accessors.get_indexed_attestation(state, attestation) do
-      check_valid_indexed_attestation(state, indexed_attestation)
+         :ok <- check_matching_aggregation_bits_length(aggregation_bits, committee_offset) do
+      Accessors.get_indexed_attestation(state, attestation)
     end
   end
 
   @spec process_attestation_batch(BeaconState.t(), [Attestation.t()]) ::
           {:ok, BeaconState.t()} | {:error, String.t()}
   def process_attestation_batch(state, attestations) do
-    with {:ok, {previous_epoch_updates, current_epoch_updates}} <-
+    with {:ok, {previous_epoch_updates, current_epoch_updates, signature_sets}} <-
            attestations
            |> Stream.with_index()
-           |> Enum.reduce_while({:ok, {Map.new(), Map.new()}}, fn
-             {att, i}, {:ok, {pepu, cepu}} ->
-               {:cont, fast_process_attestation(state, att, pepu, cepu, i)}
-
-             _, {:error, _} = err ->
-               {:halt, err}
-           end) do
+           |> Enum.reduce_while({:ok, {Map.new(), Map.new(), []}}, fn
+             {att, i}, {:ok, {pepu, cepu, sets}} ->
+               case fast_process_attestation(state, att, pepu, cepu, i) do
+                 {:ok, {pepu, cepu}, set} -> {:cont, {:ok, {pepu, cepu, [set | sets]}}}
+                 {:error, _} = err -> {:halt, err}
+               end
+           end),
+         :ok <- verify_attestation_signatures(state, Enum.reverse(signature_sets)) do
       base_reward_per_increment = Accessors.get_base_reward_per_increment(state)
 
       {new_previous_participation, reward_numerators} =
@@ -816,7 +823,8 @@ defmodule LambdaEthereumConsensus.StateTransition.Operations do
         current_epoch_updates,
         attestation_index
       ) do
-    with :ok <- validate_attestation(state, att),
+    with {:ok, indexed_attestation} <- get_validated_indexed_attestation(state, att),
+         {:ok, signature_set} <- get_attestation_signature_set(state, indexed_attestation),
          slot = state.slot - data.slot,
          {:ok, flag_indices} <-
            Accessors.get_attestation_participation_flag_indices(state, data, slot),
@@ -841,8 +849,26 @@ defmodule LambdaEthereumConsensus.StateTransition.Operations do
         end)
 
       if is_current_epoch,
-        do: {:ok, {previous_epoch_updates, new_epoch_updates}},
-        else: {:ok, {new_epoch_updates, current_epoch_updates}}
+        do: {:ok, {previous_epoch_updates, new_epoch_updates}, signature_set},
+        else: {:ok, {new_epoch_updates, current_epoch_updates}, signature_set}
+    end
+  end
+
+  defp get_attestation_signature_set(state, indexed_attestation) do
+    case Predicates.indexed_attestation_signature_set(state, indexed_attestation) do
+      {:ok, signature_set} -> {:ok, signature_set}
+      :error -> {:error, "Invalid signature"}
+    end
+  end
+
+  # All attestation signatures in a block are checked in a single batch, but we still
+  # report which attestation made the batch fail.
+  defp verify_attestation_signatures(state, signature_sets) do
+    case PubkeyCache.verify_signature_sets(state, signature_sets) do
+      {:ok, :valid} -> :ok
+      {:ok, {:invalid, index}} -> {:error, "Invalid signature in attestation #{index}"}
+      {:ok, :batch_invalid} -> {:error, "Invalid attestation signatures"}
+      {:error, reason} -> {:error, "Invalid signature: #{inspect(reason)}"}
     end
   end
 
@@ -5,6 +5,7 @@ defmodule LambdaEthereumConsensus.StateTransition.Predicates do
 
   alias LambdaEthereumConsensus.StateTransition.Accessors
   alias LambdaEthereumConsensus.StateTransition.Misc
+  alias LambdaEthereumConsensus.StateTransition.PubkeyCache
   alias Types.BeaconState
   alias Types.Validator
 
@@ -148,28 +149,51 @@ defmodule LambdaEthereumConsensus.StateTransition.Predicates do
   def valid_indexed_attestation?(state, indexed_attestation) do
     indices = indexed_attestation.attesting_indices
 
-    if Enum.empty?(indices) or not uniq_and_sorted?(indices) do
+    if valid_attesting_indices?(state, indices) do
+      signing_root = attestation_signing_root(state, indexed_attestation)
+
+      PubkeyCache.fast_aggregate_valid?(
+        state,
+        indices,
+        signing_root,
+        indexed_attestation.signature
+      )
+    else
       false
+    end
+  end
+
+  @doc """
+  Same checks as ``valid_indexed_attestation?``, except for the signature, which is
+  returned as a signature set of validator indices to be verified later in a batch
+  with ``PubkeyCache.verify_signature_sets``.
+  """
+  @spec indexed_attestation_signature_set(BeaconState.t(), Types.IndexedAttestation.t()) ::
+          {:ok, Bls.indexed_signature_set()} | :error
+  def indexed_attestation_signature_set(state, indexed_attestation) do
+    indices = indexed_attestation.attesting_indices
+
+    if valid_attesting_indices?(state, indices) do
+      signing_root = attestation_signing_root(state, indexed_attestation)
+      {:ok, {indices, signing_root, indexed_attestation.signature}}
     else
-      domain_type = Constants.domain_beacon_attester()
-      epoch = indexed_attestation.data.target.epoch
-
-      signing_root =
-        Accessors.get_domain(state, domain_type, epoch)
-        |> then(&Misc.compute_signing_root(indexed_attestation.data, &1))
-
-      indices
-      |> Stream.map(&state.validators[&1])
-      |> Enum.map_reduce(true, fn
-        nil, _ -> {nil, false}
-        v, b -> {v.pubkey, b}
-      end)
-      |> then(fn {pks, b} ->
-        b and Bls.fast_aggregate_valid?(pks, signing_root, indexed_attestation.signature)
-      end)
+      :error
     end
   end
 
+  defp valid_attesting_indices?(state, indices) do
+    not Enum.empty?(indices) and uniq_and_sorted?(indices) and
+      List.last(indices) < Aja.Vector.size(state.validators)
+  end
+
+  defp attestation_signing_root(state, indexed_attestation) do
+    domain_type = Constants.domain_beacon_attester()
+    epoch = indexed_attestation.data.target.epoch
+
+    Accessors.get_domain(state, domain_type, epoch)
+    |> then(&Misc.compute_signing_root(indexed_attestation.data, &1))
+  end
+
   defp uniq_and_sorted?([]), do: true
   defp uniq_and_sorted?([a, b | _]) when a >= b, do: false
   defp uniq_and_sorted?([_ | tail]), do: uniq_and_sorted?(tail)
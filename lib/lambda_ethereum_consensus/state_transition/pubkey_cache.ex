@@ -0,0 +1,128 @@
+defmodule LambdaEthereumConsensus.StateTransition.PubkeyCache do
+  @moduledoc """
+  Keeps the decompressed validator public keys in native memory, addressed by
+  validator index, so signature checks don't need to decompress them every time.
+
+  There's a single cache, created on first use. Since the validator registry is
+  append-only, new validators (i.e. processed deposits) of the given state are
+  appended to it. The cached keys are never replaced: if a state doesn't match
+  them, e.g. because its branch processed other deposits, its keys are taken
+  from the state instead, which is slower.
+  """
+  alias Types.BeaconState
+
+  @key __MODULE__
+
+  @doc """
+  Verifies an aggregate signature of the validators with the given indices.
+  """
+  @spec fast_aggregate_valid?(
+          BeaconState.t(),
+          [Types.validator_index()],
+          binary(),
+          Bls.signature()
+        ) :: boolean()
+  def fast_aggregate_valid?(state, indices, message, signature) do
+    case sync(state) do
+      {:ok, cache} -> Bls.fast_aggregate_verify_indices(cache, indices, message, signature)
+      :diverged -> Bls.fast_aggregate_verify(pubkeys(state, indices), message, signature)
+    end
+    |> case do
+      {:ok, bool} -> bool
+      {:error, _} -> false
+    end
+  end
+
+  @doc """
+  Verifies a batch of signature sets whose public keys are given by validator index.
+  See `Bls.verify_signature_sets/1`.
+  """
+  @spec verify_signature_sets(BeaconState.t(), [Bls.indexed_signature_set()]) ::
+          {:ok, Bls.signature_sets_result()} | {:error, Bls.error()}
+  def verify_signature_sets(state, signature_sets) do
+    case sync(state) do
+      {:ok, cache} ->
+        Bls.verify_signature_sets_indices(cache, signature_sets)
+
+      :diverged ->
+        signature_sets
+        |> Enum.map(fn {indices, message, signature} ->
+          {pubkeys(state, indices), message, signature}
+        end)
+        |> Bls.verify_signature_sets()
+    end
+  end
+
+  @doc """
+  Returns the index of the validator with the given pubkey, or `nil` if not present.
+  """
+  @spec fetch_validator_index(BeaconState.t(), Bls.pubkey()) :: Types.validator_index() | nil
+  def fetch_validator_index(state, pubkey) do
+    validator_count = Aja.Vector.size(state.validators)
+
+    with {:ok, cache} <- sync(state),
+         {:ok, index} when is_integer(index) and index < validator_count <-
+           Bls.pubkey_cache_index_of(cache, pubkey) do
+      index
+    else
+      :diverged -> Enum.find_index(state.validators, &(&1.pubkey == pubkey))
+      _ -> nil
+    end
+  end
+
+  @doc """
+  Appends the state's validators missing from the cache, and returns it. Returns
+  `:diverged` if the state's validators don't match the cached ones.
+  """
+  @spec sync(BeaconState.t()) :: {:ok, Bls.pubkey_cache()} | :diverged
+  def sync(%BeaconState{validators: validators}) do
+    cache = get_cache()
+    validator_count = Aja.Vector.size(validators)
+    {:ok, cached_count} = Bls.pubkey_cache_len(cache)
+
+    cond do
+      not consistent?(cache, validators, min(cached_count, validator_count)) ->
+        :diverged
+
+      cached_count < validator_count ->
+        new_pubkeys =
+          validators
+          |> Aja.Vector.slice(cached_count, validator_count - cached_count)
+          |> Aja.Vector.map(& &1.pubkey)
+          |> Aja.Vector.to_list()
+
+        {:ok, _} = Bls.pubkey_cache_extend(cache, cached_count, new_pubkeys)
+        {:ok, cache}
+
+      true ->
+        {:ok, cache}
+    end
+  end
+
+  # The cache is only stored once, as each `:persistent_term.put/2` triggers a
+  # global GC.
+  defp get_cache() do
+    case :persistent_term.get(@key, nil) do
+      nil ->
+        cache = Bls.pubkey_cache_new()
+        :persistent_term.put(@key, cache)
+        cache
+
+      cache ->
+        cache
+    end
+  end
+
+  # Checks the last validator both the state and the cache have in common.
+  defp consistent?(_cache, _validators, 0), do: true
+
+  defp consistent?(cache, validators, common_count) do
+    last_index = common_count - 1
+    last_pubkey = Aja.Vector.at!(validators, last_index).pubkey
+    Bls.pubkey_cache_get(cache, last_index) == {:ok, last_pubkey}
+  end
+
+  defp pubkeys(state, indices) do
+    Enum.map(indices, &Aja.Vector.at!(state.validators, &1).pubkey)
+  end
+end
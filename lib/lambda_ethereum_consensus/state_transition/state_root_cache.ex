@@ -0,0 +1,39 @@
+defmodule LambdaEthereumConsensus.StateTransition.StateRootCache do
+  @moduledoc """
+  Computes the hash tree root of encoded beacon states, keeping the Merkle tree
+  of the last hashed state in native memory.
+
+  Consecutive states share most of their leaves, so only the chunks that changed
+  since the previous call are rehashed. Any state can be given: one unrelated to
+  the cached tree is hashed from scratch, and replaces it.
+
+  There is a single cache for the whole node, behind one lock. This keeps memory
+  bounded to one tree and suits the common case of hashing each new state right
+  after its parent, but concurrent callers are serialized, and alternating
+  between unrelated states (e.g. two forks of the chain) rehashes from scratch
+  every time.
+  """
+
+  @key __MODULE__
+
+  @doc """
+  Returns the hash tree root of an encoded `Types.BeaconState`.
+  """
+  @spec root!(binary()) :: Types.root()
+  def root!(encoded_state) do
+    {:ok, root} = Ssz.state_hash_cache_root(get_cache(), encoded_state)
+    root
+  end
+
+  defp get_cache() do
+    case :persistent_term.get(@key, nil) do
+      nil ->
+        cache = Ssz.state_hash_cache_new()
+        :persistent_term.put(@key, cache)
+        cache
+
+      cache ->
+        cache
+    end
+  end
+end
@@ -104,6 +104,8 @@ defmodule LambdaEthereumConsensus.StateTransition do
         slot_previous_roots.state_root
       else
         Logger.warning("Slot #{state.slot}: no previous state root in cache")
+        # There's no encoding of this state to give to `StateRootCache`, and
+        # encoding it costs as much as hashing it directly.
         Ssz.hash_tree_root!(state)
       end
 
@@ -14,11 +14,19 @@ defmodule LambdaEthereumConsensus.Store.BlobDb do
   @block_root_prefix "block_root"
 
   @doc """
-  Stores a blob sidecar for a certain block. Returns the block root.
+  Stores a blob sidecar for a certain block. Returns the block root, which is
+  computed from the sidecar's header unless given.
   """
-  @spec store_blob(BlobSidecar.t()) :: Types.root()
-  def store_blob(%BlobSidecar{signed_block_header: %{message: block_header}} = blob) do
-    block_root = Ssz.hash_tree_root!(block_header)
+  @spec store_blob(BlobSidecar.t(), Types.root() | nil) :: Types.root()
+  def store_blob(blob, block_root \\ nil)
+
+  def store_blob(%BlobSidecar{signed_block_header: %{message: block_header}} = blob, nil),
+    do: store_blob(blob, Ssz.hash_tree_root!(block_header))
+
+  def store_blob(
+        %BlobSidecar{signed_block_header: %{message: block_header}} = blob,
+        block_root
+      ) do
     {:ok, encoded_blob} = Ssz.to_ssz(blob)
 
     key = blob_sidecar_key(block_root, blob.index)
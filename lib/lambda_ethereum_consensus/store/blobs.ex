@@ -9,12 +9,16 @@ defmodule LambdaEthereumConsensus.Store.Blobs do
   alias Types.BlockInfo
 
   @doc """
-  To be used when a series of blobs are downloaded. Stores each blob.
+  To be used when a series of blobs are downloaded. Stores each blob. Blobs may
+  come with the root of their block, if it's already known.
   """
-  @spec add_blobs([BlobSidecar.t()]) :: [Types.root()]
+  @spec add_blobs([BlobSidecar.t() | {BlobSidecar.t(), Types.root()}]) :: [Types.root()]
   def add_blobs(blobs) do
     blobs
-    |> Enum.map(&BlobDb.store_blob/1)
+    |> Enum.map(fn
+      {blob, block_root} -> BlobDb.store_blob(blob, block_root)
+      blob -> BlobDb.store_blob(blob)
+    end)
     |> Enum.uniq()
   end
 
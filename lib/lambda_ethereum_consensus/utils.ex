@@ -39,6 +39,20 @@ defmodule LambdaEthereumConsensus.Utils do
   def map_err({:error, _}, reason), do: {:error, reason}
   def map_err(v, _), do: v
 
+  @doc """
+  Like ``Map.update!/3``, but leaves the value untouched if it's ``nil``. Used for
+  fields that don't exist in every fork.
+
+  ## Examples
+      iex> Utils.update_non_nil!(%{a: 1}, :a, &(&1 + 1))
+      %{a: 2}
+      iex> Utils.update_non_nil!(%{a: nil}, :a, &(&1 + 1))
+      %{a: nil}
+  """
+  @spec update_non_nil!(map(), any(), (any() -> any())) :: map()
+  def update_non_nil!(map, key, fun),
+    do: Map.update!(map, key, fn value -> value && fun.(value) end)
+
   @doc """
   Format a binary to a shortened hexadecimal representation.
   """
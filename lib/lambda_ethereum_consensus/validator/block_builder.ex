@@ -13,19 +13,18 @@ defmodule LambdaEthereumConsensus.Validator.BlockBuilder do
   alias LambdaEthereumConsensus.Store.BlobDb
   alias LambdaEthereumConsensus.Store.Blocks
   alias LambdaEthereumConsensus.Store.BlockStates
+  alias LambdaEthereumConsensus.Utils
   alias LambdaEthereumConsensus.Utils.BitVector
   alias LambdaEthereumConsensus.Utils.Randao
   alias LambdaEthereumConsensus.Validator.BuildBlockRequest
+  alias LambdaEthereumConsensus.Validator.SlashingProtection
   alias Types.BeaconBlock
-  alias Types.BeaconBlockBody
-  alias Types.BeaconBlockHeader
   alias Types.BeaconState
   alias Types.BlobsBundle
   alias Types.BlobSidecar
   alias Types.Eth1Data
   alias Types.ExecutionPayload
   alias Types.SignedBeaconBlock
-  alias Types.SignedBeaconBlockHeader
 
   require Logger
 
@@ -53,8 +52,9 @@ defmodule LambdaEthereumConsensus.Validator.BlockBuilder do
              execution_payload,
              eth1_vote
            ),
-         {:ok, signed_block} <- seal_block(pre_state, block, block_request.privkey) do
-      sidecars = generate_sidecars(signed_block, blobs_bundle)
+         {:ok, signed_block} <- seal_block(pre_state, block, block_request.privkey),
+         {:ok, sidecars} <- generate_sidecars(signed_block, blobs_bundle) do
+      Enum.each(sidecars, &BlobDb.store_blob/1)
       {:ok, {signed_block, sidecars}}
     end
   end
@@ -153,7 +153,6 @@ defmodule LambdaEthereumConsensus.Validator.BlockBuilder do
     with {:ok, post_state} <- StateTransition.transition(pre_state, wrapped_block) do
       %BeaconBlock{block | state_root: Ssz.hash_tree_root!(post_state)}
       |> sign_block(post_state, privkey)
-      |> then(&{:ok, &1})
     end
   end
 
@@ -192,8 +191,9 @@ defmodule LambdaEthereumConsensus.Validator.BlockBuilder do
   end
 
   defp sign_block(block, state, privkey) do
-    signature = get_block_signature(state, block, privkey)
-    %SignedBeaconBlock{message: block, signature: signature}
+    with {:ok, signature} <- get_block_signature(state, block, privkey) do
+      {:ok, %SignedBeaconBlock{message: block, signature: signature}}
+    end
   end
 
   @spec get_epoch_signature(BeaconState.t(), Types.slot(), Bls.privkey()) ::
@@ -207,12 +207,15 @@ defmodule LambdaEthereumConsensus.Validator.BlockBuilder do
   end
 
   @spec get_block_signature(BeaconState.t(), BeaconBlock.t(), Bls.privkey()) ::
-          Types.bls_signature()
+          {:ok, Types.bls_signature()} | {:error, String.t()}
   defp get_block_signature(state, block, privkey) do
     domain = Accessors.get_domain(state, Constants.domain_beacon_proposer())
     signing_root = Misc.compute_signing_root(block, domain)
-    {:ok, signature} = Bls.sign(privkey, signing_root)
-    signature
+
+    case SlashingProtection.sign_block(privkey, block.slot, signing_root) do
+      {:ok, signature} -> {:ok, signature}
+      {:error, reason} -> {:error, "Refused to sign block: #{inspect(reason)}"}
+    end
   end
 
   defp select_best_aggregates(attestations) do
@@ -328,74 +331,40 @@ defmodule LambdaEthereumConsensus.Validator.BlockBuilder do
     {:ok, eth_vote}
   end
 
-  @spec generate_sidecars(SignedBeaconBlock.t(), BlobsBundle.t()) :: [BlobSidecar.t()]
-  defp generate_sidecars(%SignedBeaconBlock{message: block} = signed_block, blobs_bundle) do
-    block_header = %BeaconBlockHeader{
-      slot: block.slot,
-      proposer_index: block.proposer_index,
-      parent_root: block.parent_root,
-      state_root: block.state_root,
-      body_root: Ssz.hash_tree_root!(block.body)
-    }
-
-    signed_block_header = %SignedBeaconBlockHeader{
-      message: block_header,
-      signature: signed_block.signature
-    }
-
-    %Types.BlobsBundle{blobs: blobs, commitments: commitments, proofs: proofs} = blobs_bundle
-    inclusion_proofs = compute_inclusion_proofs(block.body)
-
-    Stream.zip([blobs, commitments, proofs, inclusion_proofs])
-    |> Stream.with_index()
-    |> Enum.map(fn {{blob, commitment, proof, inclusion_proof}, index} ->
-      %BlobSidecar{
-        index: index,
-        blob: blob,
-        kzg_commitment: commitment,
-        kzg_proof: proof,
-        signed_block_header: signed_block_header,
-        kzg_commitment_inclusion_proof: inclusion_proof
-      }
-      |> tap(&BlobDb.store_blob/1)
-    end)
+  @doc """
+  Builds the sidecars of the blobs of a block, with the proofs of inclusion of
+  their commitments in the block body. If the bundle has no proofs, they're computed.
+  """
+  @spec generate_sidecars(SignedBeaconBlock.t(), BlobsBundle.t()) ::
+          {:ok, [BlobSidecar.t()]} | {:error, Ssz.error() | Kzg.error()}
+  def generate_sidecars(%SignedBeaconBlock{} = signed_block, %BlobsBundle{} = blobs_bundle) do
+    with {:ok, kzg_proofs} <- kzg_proofs(signed_block, blobs_bundle),
+         {:ok, block} <- Ssz.to_ssz(signed_block),
+         {:ok, sidecars} <- Ssz.build_blob_sidecars(block, blobs_bundle.blobs, kzg_proofs) do
+      {:ok, Enum.map(sidecars, &Ssz.from_ssz!(&1, BlobSidecar))}
+    end
   end
 
-  def compute_inclusion_proofs(%BeaconBlockBody{blob_kzg_commitments: []}), do: []
-
-  def compute_inclusion_proofs(%BeaconBlockBody{} = body) do
-    # TODO: maybe generalize and move to a separate module
-    commitments = body.blob_kzg_commitments
-    commitment_number = length(commitments)
-
-    # Compute the proof against the commitments tree root for each commitment
-    commitment_leaves =
-      Enum.map(commitments, &SszEx.hash_tree_root!(&1, TypeAliases.kzg_commitment()))
-
-    commitment_tree_height =
-      ChainSpec.get("MAX_BLOB_COMMITMENTS_PER_BLOCK") |> :math.log2() |> ceil()
-
-    commitment_tree_proofs =
-      0..(commitment_number - 1)
-      |> Enum.map(
-        &SszEx.Merkleization.compute_merkle_proof(commitment_leaves, &1, commitment_tree_height)
-      )
-
-    # Compute the proof against the BeaconBlockBody root for the commitments tree root
-    commitments_tree_index =
-      BeaconBlockBody.schema()
-      |> Enum.find_index(&match?({:blob_kzg_commitments, _}, &1))
-
-    body_height = BeaconBlockBody.schema() |> Enum.count() |> :math.log2() |> ceil()
+  defp kzg_proofs(%SignedBeaconBlock{message: block}, %BlobsBundle{blobs: blobs, proofs: []}) do
+    commitments = block.body.blob_kzg_commitments
 
-    body_proof =
-      BeaconBlockBody.schema()
-      |> Enum.map(fn {name, schema} -> Map.fetch!(body, name) |> SszEx.hash_tree_root!(schema) end)
-      |> SszEx.Merkleization.compute_merkle_proof(commitments_tree_index, body_height)
+    # Bundles that don't match the block are reported when building the sidecars
+    if length(blobs) == length(commitments),
+      do: compute_kzg_proofs(blobs, commitments),
+      else: {:ok, []}
+  end
 
-    mix_in_length = <<commitment_number::little-size(256)>>
+  defp kzg_proofs(_signed_block, %BlobsBundle{proofs: proofs}), do: {:ok, proofs}
 
-    # Concatenate both proofs and the mix-in length for each commitment
-    Enum.map(commitment_tree_proofs, &Enum.concat([&1, [mix_in_length], body_proof]))
+  defp compute_kzg_proofs(blobs, commitments) do
+    blobs
+    |> Enum.zip(commitments)
+    |> Enum.reduce_while({:ok, []}, fn {blob, commitment}, {:ok, proofs} ->
+      case Kzg.compute_blob_kzg_proof(blob, commitment) do
+        {:ok, proof} -> {:cont, {:ok, [proof | proofs]}}
+        {:error, reason} -> {:halt, {:error, reason}}
+      end
+    end)
+    |> Utils.map_ok(&{:ok, Enum.reverse(&1)})
   end
 end
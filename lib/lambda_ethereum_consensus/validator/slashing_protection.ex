@@ -0,0 +1,76 @@
+defmodule LambdaEthereumConsensus.Validator.SlashingProtection do
+  @moduledoc """
+  [EIP-3076](https://eips.ethereum.org/EIPS/eip-3076) slashing protection.
+
+  Blocks and attestations are signed through this module, which refuses to sign
+  double proposals, double votes and surround votes. Every signed message is
+  persisted before its signature is returned, in a database shared by all the
+  validators and stored in the node's data directory.
+  """
+  alias LambdaEthereumConsensus.Store.Db
+
+  @key __MODULE__
+  @file_name "slashing_protection.jsonl"
+
+  @doc """
+  Opens the database in the data directory, unless it's already open.
+  """
+  @spec init(Types.root()) :: :ok | {:error, Bls.slashing_error()}
+  def init(genesis_validators_root) do
+    if :persistent_term.get(@key, nil) == nil do
+      open(default_path(), genesis_validators_root)
+    else
+      :ok
+    end
+  end
+
+  @doc """
+  Opens the database at `path`, replacing the one currently in use.
+  """
+  @spec open(Path.t(), Types.root()) :: :ok | {:error, Bls.slashing_error()}
+  def open(path, genesis_validators_root) do
+    with {:ok, db} <- Bls.slashing_protection_open(path, genesis_validators_root) do
+      :persistent_term.put(@key, db)
+    end
+  end
+
+  @spec sign_block(Bls.privkey(), Types.slot(), Types.root()) ::
+          {:ok, Bls.signature()} | {:error, Bls.slashing_error()}
+  def sign_block(privkey, slot, signing_root) do
+    Bls.slashing_protection_sign_block(get_db(), privkey, slot, signing_root)
+  end
+
+  @spec sign_attestation(Bls.privkey(), Types.AttestationData.t(), Types.root()) ::
+          {:ok, Bls.signature()} | {:error, Bls.slashing_error()}
+  def sign_attestation(privkey, %Types.AttestationData{source: source, target: target}, root) do
+    Bls.slashing_protection_sign_attestation(get_db(), privkey, source.epoch, target.epoch, root)
+  end
+
+  @doc """
+  Imports the signing history of an EIP-3076 interchange JSON.
+  """
+  @spec import_interchange(String.t()) ::
+          {:ok, non_neg_integer()} | {:error, Bls.slashing_error()}
+  def import_interchange(json), do: Bls.slashing_protection_import(get_db(), json)
+
+  @doc """
+  Exports the signing history of all validators as an EIP-3076 interchange JSON.
+  """
+  @spec export_interchange() :: {:ok, String.t()} | {:error, Bls.slashing_error()}
+  def export_interchange(), do: Bls.slashing_protection_export(get_db())
+
+  defp get_db() do
+    case :persistent_term.get(@key, nil) do
+      nil -> raise "Slashing protection database not initialized"
+      db -> db
+    end
+  end
+
+  defp default_path() do
+    Application.fetch_env!(:lambda_ethereum_consensus, Db)
+    |> Keyword.fetch!(:dir)
+    |> Path.expand()
+    |> tap(&File.mkdir_p!/1)
+    |> Path.join(@file_name)
+  end
+end
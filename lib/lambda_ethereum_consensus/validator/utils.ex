@@ -4,6 +4,8 @@ defmodule LambdaEthereumConsensus.Validator.Utils do
   """
   alias LambdaEthereumConsensus.StateTransition.Accessors
   alias LambdaEthereumConsensus.StateTransition.Misc
+  alias LambdaEthereumConsensus.StateTransition.PubkeyCache
+  alias LambdaEthereumConsensus.Validator.SlashingProtection
   alias Types.AttestationData
   alias Types.BeaconState
 
@@ -13,7 +15,7 @@ defmodule LambdaEthereumConsensus.Validator.Utils do
   @spec fetch_validator_index(Types.BeaconState.t(), Bls.pubkey()) ::
           non_neg_integer() | nil
   def fetch_validator_index(state, pubkey) do
-    Enum.find_index(state.validators, &(&1.pubkey == pubkey))
+    PubkeyCache.fetch_validator_index(state, pubkey)
   end
 
   @doc """
@@ -28,15 +30,16 @@ defmodule LambdaEthereumConsensus.Validator.Utils do
     rem(committees_since_epoch_start + committee_index, ChainSpec.get("ATTESTATION_SUBNET_COUNT"))
   end
 
+  @doc """
+  Signs the attestation data, unless it's slashable.
+  """
   @spec get_attestation_signature(BeaconState.t(), AttestationData.t(), Bls.privkey()) ::
-          Types.bls_signature()
+          {:ok, Types.bls_signature()} | {:error, Bls.slashing_error()}
   def get_attestation_signature(%BeaconState{} = state, attestation_data, privkey) do
     domain_beacon_attester = Constants.domain_beacon_attester()
     domain = Accessors.get_domain(state, domain_beacon_attester, attestation_data.target.epoch)
     signing_root = Misc.compute_signing_root(attestation_data, domain)
-    # Can't fail, unless privkey is invalid
-    {:ok, signature} = Bls.sign(privkey, signing_root)
-    signature
+    SlashingProtection.sign_attestation(privkey, attestation_data, signing_root)
   end
 
   @spec get_slot_signature(BeaconState.t(), Types.slot(), Bls.privkey()) ::
@@ -15,8 +15,8 @@ defmodule LambdaEthereumConsensus.Validator do
   alias LambdaEthereumConsensus.P2P.Gossip
   alias LambdaEthereumConsensus.StateTransition.Accessors
   alias LambdaEthereumConsensus.StateTransition.Misc
-  alias LambdaEthereumConsensus.Utils.BitField
   alias LambdaEthereumConsensus.Utils.BitList
+  alias LambdaEthereumConsensus.Utils.BitVector
   alias LambdaEthereumConsensus.Validator.BlockBuilder
   alias LambdaEthereumConsensus.Validator.BuildBlockRequest
   alias LambdaEthereumConsensus.Validator.Duties
@@ -69,8 +69,22 @@ defmodule LambdaEthereumConsensus.Validator do
       ) do
     log_debug(validator_index, "attesting", slot: slot, subnet_id: subnet_id)
 
-    attestation = produce_attestation(current_duty, head_state, slot, head_root, keystore.privkey)
+    case produce_attestation(current_duty, head_state, slot, head_root, keystore.privkey) do
+      {:ok, attestation} ->
+        publish_attestation(validator_index, keystore, current_duty, slot, attestation)
 
+      {:error, reason} ->
+        log_error(validator_index, "sign attestation", reason, slot: slot)
+    end
+  end
+
+  defp publish_attestation(
+         validator_index,
+         keystore,
+         %{subnet_id: subnet_id} = current_duty,
+         slot,
+         attestation
+       ) do
     log_md = [slot: slot, attestation: attestation, subnet_id: subnet_id]
 
     debug_log_msg =
@@ -110,19 +124,13 @@ defmodule LambdaEthereumConsensus.Validator do
     end
   end
 
-  defp aggregate_attestations(attestations) do
-    # TODO: (#1254) We need to check why we are producing duplicate attestations, this was generating invalid signatures
-    unique_attestations = attestations |> Enum.uniq()
-
-    aggregation_bits =
-      unique_attestations
-      |> Stream.map(&Map.fetch!(&1, :aggregation_bits))
-      |> Enum.reduce(&BitField.bitwise_or/2)
-
-    {:ok, signature} =
-      unique_attestations |> Enum.map(&Map.fetch!(&1, :signature)) |> Bls.aggregate()
-
-    %{List.first(attestations) | aggregation_bits: aggregation_bits, signature: signature}
+  # All the collected attestations share the same data. The pool skips duplicates
+  # and doesn't merge overlapping attestations, so the best aggregate is valid.
+  defp aggregate_attestations([%Attestation{data: data} | _] = attestations) do
+    pool = AttestationPool.new()
+    Enum.each(attestations, &({:ok, _} = AttestationPool.insert(pool, &1)))
+    {:ok, aggregate} = AttestationPool.get_aggregate(pool, Ssz.hash_tree_root!(data))
+    aggregate
   end
 
   defp append_proof(aggregate, proof, validator_index) do
@@ -170,15 +178,23 @@ defmodule LambdaEthereumConsensus.Validator do
 
     bits = BitList.zero(committee_length) |> BitList.set(index_in_committee)
 
-    signature = Utils.get_attestation_signature(head_state, attestation_data, privkey)
+    with {:ok, signature} <-
+           Utils.get_attestation_signature(head_state, attestation_data, privkey) do
+      {:ok,
+       %Attestation{
+         data: attestation_data,
+         aggregation_bits: bits,
+         signature: signature,
+         committee_bits: committee_bits(committee_index)
+       }}
+    end
+  end
 
-    %Attestation{
-      data: attestation_data,
-      aggregation_bits: bits,
-      signature: signature,
-      # Not implemented yet, part of EIP7549
-      committee_bits: BitList.zero(committee_length)
-    }
+  # [New in Electra:EIP7549] The committee is given by the committee bits.
+  defp committee_bits(committee_index) do
+    ChainSpec.get("MAX_COMMITTEES_PER_SLOT")
+    |> BitVector.new()
+    |> BitVector.set(committee_index)
   end
 
   ################################
@@ -409,7 +425,7 @@ defmodule LambdaEthereumConsensus.Validator do
   # TODO: there's a lot of repeated code here. We should move this to a separate module
   defp publish_block(validator_index, signed_block) do
     {:ok, ssz_encoded} = Ssz.to_ssz(signed_block)
-    {:ok, encoded_msg} = :snappyer.compress(ssz_encoded)
+    {:ok, encoded_msg} = Snappy.compress_raw(ssz_encoded)
     fork_context = ForkChoice.get_fork_digest() |> Base.encode16(case: :lower)
 
     proposed_slot = signed_block.message.slot
@@ -423,7 +439,7 @@ defmodule LambdaEthereumConsensus.Validator do
 
   defp publish_sidecar(validator_index, %Types.BlobSidecar{index: index} = sidecar) do
     {:ok, ssz_encoded} = Ssz.to_ssz(sidecar)
-    {:ok, encoded_msg} = :snappyer.compress(ssz_encoded)
+    {:ok, encoded_msg} = Snappy.compress_raw(ssz_encoded)
     fork_context = ForkChoice.get_fork_digest() |> Base.encode16(case: :lower)
 
     subnet_id = compute_subnet_for_blob_sidecar(index)
@@ -457,5 +473,12 @@ defmodule LambdaEthereumConsensus.Validator do
     do: Logger.debug("[Validator] #{index} #{message}", metadata)
 
   defp log_error(index, message, reason, metadata \\ []),
-    do: Logger.error("[Validator] #{index} Failed to #{message}. Reason: #{reason}", metadata)
+    do:
+      Logger.error(
+        "[Validator] #{index} Failed to #{message}. Reason: #{format_reason(reason)}",
+        metadata
+      )
+
+  defp format_reason(reason) when is_binary(reason), do: reason
+  defp format_reason(reason), do: inspect(reason)
 end
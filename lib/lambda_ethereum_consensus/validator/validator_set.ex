@@ -20,6 +20,7 @@ defmodule LambdaEthereumConsensus.ValidatorSet do
   alias LambdaEthereumConsensus.Store.CheckpointStates
   alias LambdaEthereumConsensus.Validator
   alias LambdaEthereumConsensus.Validator.Duties
+  alias LambdaEthereumConsensus.Validator.SlashingProtection
 
   @type validators :: %{Validator.index() => Validator.t()}
 
@@ -59,6 +60,20 @@ defmodule LambdaEthereumConsensus.ValidatorSet do
     epoch = Misc.compute_epoch_at_slot(slot)
     beacon = fetch_target_state_and_go_to_slot(epoch, slot, head_root)
 
+    case SlashingProtection.init(beacon.genesis_validators_root) do
+      :ok ->
+        add_validators(set, epoch, slot, head_root, beacon, validator_keystores)
+
+      {:error, reason} ->
+        Logger.error(
+          "[ValidatorSet] Failed to open slashing protection database: #{inspect(reason)}. Validators won't start."
+        )
+
+        set
+    end
+  end
+
+  defp add_validators(set, epoch, slot, head_root, beacon, validator_keystores) do
     new_validators =
       Map.new(validator_keystores, fn keystore ->
         validator = Validator.new(keystore, beacon)
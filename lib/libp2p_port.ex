@@ -909,7 +909,7 @@ defmodule LambdaEthereumConsensus.Libp2pPort do
             {new_requests, new_store}
 
           {:error, reason} ->
-            Logger.warning("Handling response failed with reason: #{reason}")
+            Logger.warning("Handling response failed with reason: #{inspect(reason)}")
             {new_requests, store}
         end
     end
@@ -1,12 +1,113 @@
 defmodule Snappy do
   @moduledoc """
-  Snappy frame compression and decompression.
+  Snappy compression and decompression, in the frame and raw formats.
   """
   use Rustler, otp_app: :lambda_ethereum_consensus, crate: "snappy_nif"
 
-  @spec decompress(binary) :: {:ok | :error, binary}
+  @typedoc """
+  `:corrupt_input` means the input isn't a valid snappy frame stream. See
+  `docs/nif_errors.md`.
+  """
+  @type error ::
+          {:corrupt_input, String.t()}
+          | {:io_error, String.t()}
+          | {:max_length_exceeded, non_neg_integer()}
+          | {:invalid_length_prefix, non_neg_integer(),
+             {non_neg_integer(), non_neg_integer()}}
+
+  @opaque decoder :: reference()
+
+  @spec decompress(binary) :: {:ok, binary} | {:error, error()}
   def decompress(_bin), do: :erlang.nif_error(:nif_not_loaded)
 
-  @spec compress(binary) :: {:ok | :error, binary}
+  @doc """
+  Like `decompress/1`, but fails as soon as the decompressed data is longer than
+  `max_length`, without decompressing the rest.
+  """
+  @spec decompress(binary, non_neg_integer()) :: {:ok, binary} | {:error, error()}
+  def decompress(_bin, _max_length), do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec compress(binary) :: {:ok, binary} | {:error, error()}
   def compress(_bin), do: :erlang.nif_error(:nif_not_loaded)
+
+  ##### Raw format
+
+  @doc """
+  Compresses into the raw (unframed) snappy format, as used in gossip messages.
+  """
+  @spec compress_raw(binary) :: {:ok, binary} | {:error, error()}
+  def compress_raw(_bin), do: :erlang.nif_error(:nif_not_loaded)
+
+  @spec decompress_raw(binary) :: {:ok, binary} | {:error, error()}
+  def decompress_raw(_bin), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Like `decompress_raw/1`, but fails without decompressing if the length in the
+  header is longer than `max_length`.
+  """
+  @spec decompress_raw(binary, non_neg_integer()) :: {:ok, binary} | {:error, error()}
+  def decompress_raw(_bin, _max_length), do: :erlang.nif_error(:nif_not_loaded)
+
+  ##### Req/Resp chunks (ssz_snappy)
+
+  @typedoc """
+  A response chunk: the result code, the context bytes and the SSZ payload. Error
+  chunks have no context bytes, and their payload is the error message.
+  """
+  @type response_chunk :: {0..255, binary, binary}
+
+  @doc """
+  Encodes a request as `<length> <snappy frames>`, where `length` is the varint
+  length of the SSZ payload.
+  """
+  @spec encode_request(binary) :: {:ok, binary} | {:error, error()}
+  def encode_request(_ssz), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Encodes each chunk as `<result> <context bytes> <length> <snappy frames>`. The
+  context bytes of error chunks are left out.
+  """
+  @spec encode_response_chunks(list(response_chunk())) :: {:ok, binary} | {:error, error()}
+  def encode_response_chunks(_chunks), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Decodes a request, whose payload must be between `min_length` and `max_length`
+  bytes long. Decompression stops at the length in the prefix.
+  """
+  @spec decode_request(binary, non_neg_integer(), non_neg_integer()) ::
+          {:ok, binary} | {:error, error()}
+  def decode_request(_bin, _min_length, _max_length), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Decodes a stream of response chunks. Successful chunks have `context_length`
+  context bytes and a payload between `min_length` and `max_length` bytes long.
+  Error messages can be up to 256 bytes long.
+  """
+  @spec decode_response_chunks(binary, non_neg_integer(), non_neg_integer(), non_neg_integer()) ::
+          {:ok, list(response_chunk())} | {:error, error()}
+  def decode_response_chunks(_bin, _context_length, _min_length, _max_length),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  ##### Incremental decoding
+
+  @doc """
+  Creates a decoder for a stream that arrives in pieces, which fails once more
+  than `max_length` bytes are decompressed. After an error, it keeps returning it.
+  """
+  @spec decoder_new(non_neg_integer()) :: decoder()
+  def decoder_new(_max_length), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Adds the next piece of the stream, and returns the data decompressed from the
+  chunks it completes. It may be empty.
+  """
+  @spec decoder_feed(decoder(), binary) :: {:ok, binary} | {:error, error()}
+  def decoder_feed(_decoder, _data), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Checks that the stream didn't end in the middle of a chunk, and returns the
+  total decompressed length.
+  """
+  @spec decoder_finish(decoder()) :: {:ok, non_neg_integer()} | {:error, error()}
+  def decoder_finish(_decoder), do: :erlang.nif_error(:nif_not_loaded)
 end
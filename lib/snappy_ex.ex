@@ -115,7 +115,7 @@ defmodule SnappyEx do
   defp parse_chunk(_acc, id, _data) when id in @ids_reserved_unskippable_chunks,
     do: {:error, "unskippable chunk of type: #{id}"}
 
-  defp decompress_payload(@id_compressed_data, data), do: :snappyer.decompress(data)
+  defp decompress_payload(@id_compressed_data, data), do: Snappy.decompress_raw(data)
   defp decompress_payload(@id_uncompressed_data, data), do: {:ok, data}
 
   defp verify_checksum(data, checksum) do
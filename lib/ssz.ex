@@ -6,8 +6,47 @@ defmodule Ssz do
 
   @max_u256 2 ** 256 - 1
 
+  @typedoc """
+  Schemas are reported without their `Types.` prefix. `:invalid_term` means the
+  term doesn't have the shape of the schema. See `docs/nif_errors.md`.
+  """
+  @type error ::
+          {:unknown_schema, String.t()}
+          | {:unknown_config, String.t()}
+          | {:unknown_fork, String.t()}
+          | {:invalid_ssz, String.t()}
+          | {:invalid_value, String.t()}
+          | {:invalid_signature_encoding, String.t()}
+          | {:invalid_transaction, String.t()}
+          | {:invalid_path, String.t()}
+          | :invalid_term
+
+  @typedoc """
+  Fork whose container layouts are used. Values of earlier forks are the Electra
+  structs, with `nil` in the fields their fork doesn't have.
+  """
+  @type fork :: :phase0 | :altair | :bellatrix | :capella | :deneb | :electra
+
+  @typedoc """
+  Path to a value inside another: field names, positions in vectors and lists,
+  and `:__len__` for the length of a list.
+  """
+  @type path :: [atom | non_neg_integer]
+
+  @typedoc """
+  A Merkle proof of a single value: its generalized index, the chunk with the
+  value and the branch from the chunk to the root, deepest node first.
+  """
+  @type merkle_proof :: {non_neg_integer, Types.root(), list(Types.root())}
+
+  @typedoc """
+  A Merkle proof of several values: their generalized indices, the chunks with
+  the values, and the helper nodes of the proof in decreasing generalized index.
+  """
+  @type merkle_multiproof :: {list(non_neg_integer), list(Types.root()), list(Types.root())}
+
   ##### Functional wrappers
-  @spec to_ssz(struct | list(struct)) :: {:ok, binary} | {:error, String.t()}
+  @spec to_ssz(struct | list(struct)) :: {:ok, binary} | {:error, error()}
   def to_ssz(map)
 
   def to_ssz(%name{} = map), do: to_ssz_typed(map, name)
@@ -21,11 +60,11 @@ defmodule Ssz do
     to_ssz_typed(list, name)
   end
 
-  @spec to_ssz_typed(term, module) :: {:ok, binary} | {:error, String.t()}
-  def to_ssz_typed(term, schema) do
+  @spec to_ssz_typed(term, module, fork) :: {:ok, binary} | {:error, error()}
+  def to_ssz_typed(term, schema, fork \\ :electra) do
     term
     |> encode()
-    |> to_ssz_rs(schema)
+    |> to_ssz_rs(schema, ChainSpec.get_preset(), fork)
   end
 
   @spec from_ssz!(binary, module) :: struct
@@ -34,17 +73,17 @@ defmodule Ssz do
     root
   end
 
-  @spec from_ssz(binary, module) :: {:ok, struct} | {:error, String.t()}
-  def from_ssz(bin, schema) do
-    with {:ok, map} <- from_ssz_rs(bin, schema) do
-      {:ok, decode(map)}
+  @spec from_ssz(binary, module, fork) :: {:ok, struct} | {:error, error()}
+  def from_ssz(bin, schema, fork \\ :electra) do
+    with {:ok, map} <- from_ssz_rs(bin, schema, ChainSpec.get_preset(), fork) do
+      {:ok, map |> add_absent_fields(fork) |> decode()}
     end
   end
 
-  @spec list_from_ssz(binary, module) :: {:ok, struct} | {:error, String.t()}
-  def list_from_ssz(bin, schema) do
-    with {:ok, list} <- list_from_ssz_rs(bin, schema) do
-      {:ok, decode(list)}
+  @spec list_from_ssz(binary, module, fork) :: {:ok, struct} | {:error, error()}
+  def list_from_ssz(bin, schema, fork \\ :electra) do
+    with {:ok, list} <- list_from_ssz_rs(bin, schema, ChainSpec.get_preset(), fork) do
+      {:ok, list |> add_absent_fields(fork) |> decode()}
     end
   end
 
@@ -60,7 +99,7 @@ defmodule Ssz do
     root
   end
 
-  @spec hash_tree_root(struct) :: {:ok, Types.root()} | {:error, String.t()}
+  @spec hash_tree_root(struct) :: {:ok, Types.root()} | {:error, error()}
   def hash_tree_root(map)
 
   def hash_tree_root(%name{} = map) do
@@ -69,15 +108,15 @@ defmodule Ssz do
     |> hash_tree_root_rs(name)
   end
 
-  @spec hash_tree_root(term, module) :: {:ok, Types.root()} | {:error, String.t()}
-  def hash_tree_root(value, schema) do
+  @spec hash_tree_root(term, module, fork) :: {:ok, Types.root()} | {:error, error()}
+  def hash_tree_root(value, schema, fork \\ :electra) do
     value
     |> encode()
-    |> hash_tree_root_rs(schema)
+    |> hash_tree_root_rs(schema, ChainSpec.get_preset(), fork)
   end
 
   @spec hash_list_tree_root(list(struct), integer) ::
-          {:ok, Types.root()} | {:error, String.t()}
+          {:ok, Types.root()} | {:error, error()}
   def hash_list_tree_root(list, max_size)
 
   def hash_list_tree_root([], max_size) do
@@ -90,7 +129,7 @@ defmodule Ssz do
   end
 
   @spec hash_list_tree_root_typed(list(struct), integer, module) ::
-          {:ok, binary} | {:error, String.t()}
+          {:ok, binary} | {:error, error()}
   def hash_list_tree_root_typed(list, max_size, schema) do
     list
     |> encode()
@@ -98,36 +137,260 @@ defmodule Ssz do
   end
 
   @spec hash_vector_tree_root_typed(list(struct), integer, module) ::
-          {:ok, binary} | {:error, String.t()}
+          {:ok, binary} | {:error, error()}
   def hash_vector_tree_root_typed(vector, max_size, schema) do
     vector
     |> encode()
     |> hash_tree_root_vector_rs(max_size, schema)
   end
 
+  @doc """
+  Returns the length of the encoding of the schema, or `nil` if it's variable.
+  """
+  @spec fixed_length(module) :: {:ok, non_neg_integer() | nil} | {:error, error()}
+  def fixed_length(schema), do: fixed_length_rs(schema)
+
+  @doc """
+  Returns the Merkle proof of the value at `path` inside `value`, e.g.
+  `[:validators, 1234, :effective_balance]` in a `Types.BeaconState`. Basic
+  values packed in a chunk with others, like the elements of a list of integers,
+  are proven through their chunk.
+  """
+  @spec merkle_proof(struct, path) :: {:ok, merkle_proof()} | {:error, error()}
+  def merkle_proof(%name{} = value, path), do: merkle_proof(value, path, name)
+
+  @spec merkle_proof(term, path, module, fork) :: {:ok, merkle_proof()} | {:error, error()}
+  def merkle_proof(value, path, schema, fork \\ :electra) do
+    value
+    |> encode()
+    |> merkle_proof_rs(path, schema, ChainSpec.get_preset(), fork)
+  end
+
+  @doc """
+  Like `merkle_proof/2`, for the values at several paths at once.
+  """
+  @spec merkle_multiproof(struct, list(path)) ::
+          {:ok, merkle_multiproof()} | {:error, error()}
+  def merkle_multiproof(%name{} = value, paths), do: merkle_multiproof(value, paths, name)
+
+  @spec merkle_multiproof(term, list(path), module, fork) ::
+          {:ok, merkle_multiproof()} | {:error, error()}
+  def merkle_multiproof(value, paths, schema, fork \\ :electra) do
+    value
+    |> encode()
+    |> merkle_multiproof_rs(paths, schema, ChainSpec.get_preset(), fork)
+  end
+
   ##### Rust-side function stubs
-  @spec to_ssz_rs(map | list, module, module) :: {:ok, binary} | {:error, String.t()}
-  def to_ssz_rs(_term, _schema, _config \\ ChainSpec.get_preset()), do: error()
+  @spec to_ssz_rs(map | list, module, module, fork) :: {:ok, binary} | {:error, error()}
+  def to_ssz_rs(_term, _schema, _config \\ ChainSpec.get_preset(), _fork \\ :electra),
+    do: error()
 
-  @spec from_ssz_rs(binary, module, module) :: {:ok, struct} | {:error, String.t()}
-  def from_ssz_rs(_bin, _schema, _config \\ ChainSpec.get_preset()), do: error()
+  @spec from_ssz_rs(binary, module, module, fork) :: {:ok, struct} | {:error, error()}
+  def from_ssz_rs(_bin, _schema, _config \\ ChainSpec.get_preset(), _fork \\ :electra),
+    do: error()
 
-  @spec list_from_ssz_rs(binary, module, module) :: {:ok, list(struct)} | {:error, String.t()}
-  def list_from_ssz_rs(_bin, _schema, _config \\ ChainSpec.get_preset()), do: error()
+  @spec list_from_ssz_rs(binary, module, module, fork) ::
+          {:ok, list(struct)} | {:error, error()}
+  def list_from_ssz_rs(_bin, _schema, _config \\ ChainSpec.get_preset(), _fork \\ :electra),
+    do: error()
 
-  @spec hash_tree_root_rs(map, module, module) :: {:ok, Types.root()} | {:error, String.t()}
-  def hash_tree_root_rs(_map, _schema, _config \\ ChainSpec.get_preset()), do: error()
+  @spec hash_tree_root_rs(map, module, module, fork) :: {:ok, Types.root()} | {:error, error()}
+  def hash_tree_root_rs(_map, _schema, _config \\ ChainSpec.get_preset(), _fork \\ :electra),
+    do: error()
+
+  @spec merkle_proof_rs(map, path, module, module, fork) ::
+          {:ok, merkle_proof()} | {:error, error()}
+  def merkle_proof_rs(_map, _path, _schema, _config \\ ChainSpec.get_preset(), _fork \\ :electra),
+    do: error()
+
+  @spec merkle_multiproof_rs(map, list(path), module, module, fork) ::
+          {:ok, merkle_multiproof()} | {:error, error()}
+  def merkle_multiproof_rs(
+        _map,
+        _paths,
+        _schema,
+        _config \\ ChainSpec.get_preset(),
+        _fork \\ :electra
+      ),
+      do: error()
 
   @spec hash_tree_root_list_rs(list, integer, module, module) ::
-          {:ok, Types.root()} | {:error, String.t()}
+          {:ok, Types.root()} | {:error, error()}
   def hash_tree_root_list_rs(_list, _max_size, _schema, _config \\ ChainSpec.get_preset()),
     do: error()
 
   @spec hash_tree_root_vector_rs(list, integer, module, module) ::
-          {:ok, Types.root()} | {:error, String.t()}
+          {:ok, Types.root()} | {:error, error()}
   def hash_tree_root_vector_rs(_vector, _max_size, _schema, _config \\ ChainSpec.get_preset()),
     do: error()
 
+  @spec fixed_length_rs(module, module) ::
+          {:ok, non_neg_integer() | nil} | {:error, error()}
+  def fixed_length_rs(_schema, _config \\ ChainSpec.get_preset()), do: error()
+
+  ##### Attestation pool
+  @typedoc """
+  Native aggregation pool. See `AttestationPool`.
+  """
+  @opaque attestation_pool :: reference()
+
+  @spec attestation_pool_new() :: attestation_pool()
+  def attestation_pool_new(), do: error()
+
+  @spec attestation_pool_insert(attestation_pool(), binary, module) ::
+          {:ok, :new | :aggregated | :already_known} | {:error, error()}
+  def attestation_pool_insert(_pool, _attestation, _config \\ ChainSpec.get_preset()),
+    do: error()
+
+  @spec attestation_pool_insert_single(
+          attestation_pool(),
+          binary,
+          non_neg_integer(),
+          pos_integer(),
+          module
+        ) :: {:ok, :new | :aggregated | :already_known} | {:error, error()}
+  def attestation_pool_insert_single(
+        _pool,
+        _attestation,
+        _index_in_committee,
+        _committee_length,
+        _config \\ ChainSpec.get_preset()
+      ),
+      do: error()
+
+  @spec attestation_pool_get_aggregate(attestation_pool(), Types.root(), module) ::
+          {:ok, binary | nil} | {:error, error()}
+  def attestation_pool_get_aggregate(_pool, _data_root, _config \\ ChainSpec.get_preset()),
+    do: error()
+
+  @spec attestation_pool_prune(attestation_pool(), Types.slot()) :: non_neg_integer()
+  def attestation_pool_prune(_pool, _min_slot), do: error()
+
+  ##### Blob sidecars
+  @doc """
+  Decodes a `Types.BlobSidecar` of `fork`. Returns it along with the root of its
+  block and whether its commitment inclusion proof is valid. Its KZG proof is
+  left to `Kzg.verify_blob_sidecar_kzg_proof/1`, which takes the same encoding
+  and uses the loaded trusted setup.
+  """
+  @spec decode_blob_sidecar(binary, fork) ::
+          {:ok, {Types.BlobSidecar.t(), Types.root(), boolean}} | {:error, error()}
+  def decode_blob_sidecar(bin, fork \\ :electra) do
+    with {:ok, {sidecar, block_root, valid?}} <-
+           decode_blob_sidecar_rs(bin, ChainSpec.get_preset(), fork) do
+      {:ok, {sidecar |> add_absent_fields(fork) |> decode(), block_root, valid?}}
+    end
+  end
+
+  @spec decode_blob_sidecar_rs(binary, module, fork) ::
+          {:ok, {struct, Types.root(), boolean}} | {:error, error()}
+  def decode_blob_sidecar_rs(_bin, _config, _fork), do: error()
+
+  @doc """
+  Builds the encoded `Types.BlobSidecar`s of an encoded `Types.SignedBeaconBlock`,
+  one per blob, given the KZG proof of each blob.
+  """
+  @spec build_blob_sidecars(binary, list(Types.blob()), list(Types.kzg_proof()), module) ::
+          {:ok, list(binary)} | {:error, error()}
+  def build_blob_sidecars(
+        _signed_block,
+        _blobs,
+        _kzg_proofs,
+        _config \\ ChainSpec.get_preset()
+      ),
+      do: error()
+
+  ##### Light client
+  @doc """
+  Builds the encoded `Types.LightClientBootstrap` of an encoded `Types.SignedBeaconBlock`,
+  given its encoded post-state.
+  """
+  @spec build_light_client_bootstrap(binary, binary, module) ::
+          {:ok, binary} | {:error, error()}
+  def build_light_client_bootstrap(_state, _signed_block, _config \\ ChainSpec.get_preset()),
+    do: error()
+
+  @doc """
+  Builds the encoded `Types.LightClientUpdate` whose sync aggregate is the one of
+  `signed_block`, attesting to its parent `attested_block`. The blocks come with
+  their encoded post-states. `finalized_block` is the block of the finalized
+  checkpoint of `attested_state`, or `nil` if it's unknown.
+  """
+  @spec build_light_client_update(binary, binary, binary, binary, binary | nil, module) ::
+          {:ok, binary} | {:error, error()}
+  def build_light_client_update(
+        _state,
+        _signed_block,
+        _attested_state,
+        _attested_block,
+        _finalized_block,
+        _config \\ ChainSpec.get_preset()
+      ),
+      do: error()
+
+  ##### Merkle proofs
+  @doc """
+  Checks a proof of `leaf` at generalized index `gindex` against `root`.
+  """
+  @spec verify_merkle_proof(Types.root(), list(Types.root()), non_neg_integer, Types.root()) ::
+          {:ok, boolean} | {:error, error()}
+  def verify_merkle_proof(_leaf, _branch, _gindex, _root), do: error()
+
+  @doc """
+  Checks a multiproof of `leaves` at the generalized indices `gindices` against
+  `root`. `proof` has the helper nodes, as returned by `merkle_multiproof/2`.
+  """
+  @spec verify_merkle_multiproof(
+          list(Types.root()),
+          list(Types.root()),
+          list(non_neg_integer),
+          Types.root()
+        ) :: {:ok, boolean} | {:error, error()}
+  def verify_merkle_multiproof(_leaves, _proof, _gindices, _root), do: error()
+
+  ##### State hash cache
+  @typedoc """
+  Native cache of the Merkle tree of the last hashed state. See `StateRootCache`.
+  """
+  @opaque state_hash_cache :: reference()
+
+  @spec state_hash_cache_new() :: state_hash_cache()
+  def state_hash_cache_new(), do: error()
+
+  @doc """
+  Returns the hash tree root of an encoded `Types.BeaconState`, only rehashing
+  the chunks that differ from the last state given to the cache.
+  """
+  @spec state_hash_cache_root(state_hash_cache(), binary, module) ::
+          {:ok, Types.root()} | {:error, error()}
+  def state_hash_cache_root(_cache, _state, _config \\ ChainSpec.get_preset()), do: error()
+
+  ##### Versioned hashes
+  @doc """
+  Returns the versioned hash of each KZG commitment, as sent to the execution
+  client in `engine_newPayload`.
+  """
+  @spec kzg_commitments_to_versioned_hashes(list(Types.kzg_commitment())) ::
+          {:ok, list(Types.bytes32())} | {:error, error()}
+  def kzg_commitments_to_versioned_hashes(_kzg_commitments), do: error()
+
+  @doc """
+  Decodes the EIP-4844 transactions of a payload and returns their
+  `blob_versioned_hashes`, in order. Other transaction types are skipped.
+  """
+  @spec blob_versioned_hashes(list(Types.transaction())) ::
+          {:ok, list(Types.bytes32())} | {:error, error()}
+  def blob_versioned_hashes(_transactions), do: error()
+
+  @doc """
+  Checks that the `blob_versioned_hashes` of the payload's transactions are,
+  in order, the given ones.
+  """
+  @spec verify_blob_versioned_hashes(list(Types.transaction()), list(Types.bytes32())) ::
+          {:ok, boolean} | {:error, error()}
+  def verify_blob_versioned_hashes(_transactions, _versioned_hashes), do: error()
+
   ##### Utils
   defp error(), do: :erlang.nif_error(:nif_not_loaded)
 
@@ -165,6 +428,27 @@ defmodule Ssz do
   defp decode(list) when is_list(list), do: list |> Enum.map(&decode/1)
   defp decode(non_struct), do: non_struct
 
+  # Structs of earlier forks come without the fields added after them
+  defp add_absent_fields(term, :electra), do: term
+
+  defp add_absent_fields(%name{} = struct, fork) do
+    fields =
+      struct
+      |> Map.from_struct()
+      |> Map.new(fn {k, v} -> {k, add_absent_fields(v, fork)} end)
+
+    name.__struct__()
+    |> Map.from_struct()
+    |> Map.new(fn {k, _} -> {k, nil} end)
+    |> Map.merge(fields)
+    |> Map.put(:__struct__, name)
+  end
+
+  defp add_absent_fields(list, fork) when is_list(list),
+    do: Enum.map(list, &add_absent_fields(&1, fork))
+
+  defp add_absent_fields(non_struct, _fork), do: non_struct
+
   defp exported?(module, function, arity) do
     Code.ensure_loaded!(module)
     function_exported?(module, function, arity)
@@ -9,6 +9,7 @@ defmodule SszEx.Utils do
   @allowed_uints [8, 16, 32, 64, 128, 256]
   @bits_per_byte 8
   @bytes_per_boolean 4
+  @bytes_per_offset 4
 
   @spec validate_schema!(SszEx.schema()) :: :ok
   def validate_schema!(:bool), do: :ok
@@ -101,6 +102,28 @@ defmodule SszEx.Utils do
     |> Enum.any?()
   end
 
+  # Variable-size values are bounded by the maximum length of their lists, and
+  # take an offset in the fixed part of their container
+  @spec max_size(SszEx.schema()) :: non_neg_integer()
+  def max_size({:byte_list, size}), do: size
+  def max_size({:bitlist, size}), do: div(size + @bits_per_byte, @bits_per_byte)
+  def max_size({:list, inner_type, size}), do: size * max_element_size(inner_type)
+  def max_size({:vector, inner_type, size}), do: size * max_element_size(inner_type)
+
+  def max_size(module) when is_atom(module) and module != :bool do
+    module.schema()
+    |> Enum.map(fn {_, schema} -> max_element_size(schema) end)
+    |> Enum.sum()
+  end
+
+  def max_size(schema), do: get_fixed_size(schema)
+
+  defp max_element_size(schema) do
+    if variable_size?(schema),
+      do: @bytes_per_offset + max_size(schema),
+      else: get_fixed_size(schema)
+  end
+
   def basic_type?({:int, _}), do: true
   def basic_type?(:bool), do: true
   def basic_type?({:list, _, _}), do: false
@@ -6,6 +6,7 @@ defmodule Types.Attestation do
   aggregation_bits is a bit list that has the size of a committee. Each individual bit is set if
   the validator corresponding to that bit participated in attesting.
   """
+  alias LambdaEthereumConsensus.Utils
   alias LambdaEthereumConsensus.Utils.BitList
   alias LambdaEthereumConsensus.Utils.BitVector
 
@@ -45,13 +46,14 @@ defmodule Types.Attestation do
   def encode(%__MODULE__{} = map) do
     map
     |> Map.update!(:aggregation_bits, &BitList.to_bytes/1)
-    |> Map.update!(:committee_bits, &BitVector.to_bytes/1)
+    # Committee bits are new in Electra
+    |> Utils.update_non_nil!(:committee_bits, &BitVector.to_bytes/1)
   end
 
   def decode(%__MODULE__{} = map) do
     map
     |> Map.update!(:aggregation_bits, &BitList.new/1)
-    |> Map.update!(:committee_bits, fn bits ->
+    |> Utils.update_non_nil!(:committee_bits, fn bits ->
       BitVector.new(bits, ChainSpec.get("MAX_COMMITTEES_PER_SLOT"))
     end)
   end
@@ -8,8 +8,10 @@ defmodule Types.BeaconState do
 
   alias LambdaEthereumConsensus.StateTransition.Accessors
   alias LambdaEthereumConsensus.StateTransition.Predicates
+  alias LambdaEthereumConsensus.Utils
   alias LambdaEthereumConsensus.Utils.BitVector
   alias Types.ExecutionPayloadHeader
+  alias Types.PendingAttestation
 
   fields = [
     :genesis_time,
@@ -53,7 +55,9 @@ defmodule Types.BeaconState do
   ]
 
   @enforce_keys fields
-  defstruct fields
+  # Only set in states decoded with the phase0 layout, which has these instead
+  # of the participation flags
+  defstruct fields ++ [:previous_epoch_attestations, :current_epoch_attestations]
 
   @type t :: %__MODULE__{
           # Versioning
@@ -133,7 +137,11 @@ defmodule Types.BeaconState do
           # [New in Electra:EIP7251]
           pending_partial_withdrawals: list(Types.PendingPartialWithdrawal.t()),
           # [New in Electra:EIP7251]
-          pending_consolidations: list(Types.PendingConsolidation.t())
+          pending_consolidations: list(Types.PendingConsolidation.t()),
+          # [Removed in Altair]
+          previous_epoch_attestations: list(PendingAttestation.t()) | nil,
+          # [Removed in Altair]
+          current_epoch_attestations: list(PendingAttestation.t()) | nil
         }
 
   @impl LambdaEthereumConsensus.Container
@@ -194,25 +202,35 @@ defmodule Types.BeaconState do
     |> Map.update!(:validators, &Aja.Vector.to_list/1)
     |> Map.update!(:balances, &Aja.Vector.to_list/1)
     |> Map.update!(:randao_mixes, &Aja.Vector.to_list/1)
-    |> Map.update!(:previous_epoch_participation, &Aja.Vector.to_list/1)
-    |> Map.update!(:current_epoch_participation, &Aja.Vector.to_list/1)
-    |> Map.update!(:latest_execution_payload_header, &ExecutionPayloadHeader.encode/1)
+    # States of earlier forks lack some of the fields
+    |> Utils.update_non_nil!(:previous_epoch_participation, &Aja.Vector.to_list/1)
+    |> Utils.update_non_nil!(:current_epoch_participation, &Aja.Vector.to_list/1)
+    |> Utils.update_non_nil!(:previous_epoch_attestations, &encode_attestations/1)
+    |> Utils.update_non_nil!(:current_epoch_attestations, &encode_attestations/1)
+    |> Utils.update_non_nil!(:latest_execution_payload_header, &ExecutionPayloadHeader.encode/1)
     |> Map.update!(:justification_bits, &BitVector.to_bytes/1)
   end
 
   def decode(%__MODULE__{} = map) do
-    map
+    # Layouts after phase0 don't have the pending attestations
+    __MODULE__
+    |> struct(map)
     |> Map.update!(:validators, &Aja.Vector.new/1)
     |> Map.update!(:balances, &Aja.Vector.new/1)
     |> Map.update!(:randao_mixes, &Aja.Vector.new/1)
-    |> Map.update!(:previous_epoch_participation, &Aja.Vector.new/1)
-    |> Map.update!(:current_epoch_participation, &Aja.Vector.new/1)
-    |> Map.update!(:latest_execution_payload_header, &ExecutionPayloadHeader.decode/1)
+    |> Utils.update_non_nil!(:previous_epoch_participation, &Aja.Vector.new/1)
+    |> Utils.update_non_nil!(:current_epoch_participation, &Aja.Vector.new/1)
+    |> Utils.update_non_nil!(:previous_epoch_attestations, &decode_attestations/1)
+    |> Utils.update_non_nil!(:current_epoch_attestations, &decode_attestations/1)
+    |> Utils.update_non_nil!(:latest_execution_payload_header, &ExecutionPayloadHeader.decode/1)
     |> Map.update!(:justification_bits, fn bits ->
       BitVector.new(bits, Constants.justification_bits_length())
     end)
   end
 
+  defp encode_attestations(attestations), do: Enum.map(attestations, &PendingAttestation.encode/1)
+  defp decode_attestations(attestations), do: Enum.map(attestations, &PendingAttestation.decode/1)
+
   def decode_ex(%__MODULE__{} = map) do
     map
     |> Map.update!(:validators, &Aja.Vector.new/1)
@@ -0,0 +1,47 @@
+defmodule Types.DataColumnSidecar do
+  @moduledoc """
+  Struct definition for `DataColumnSidecar`.
+  Related definitions in `native/ssz_nif/src/types/`.
+  """
+  use LambdaEthereumConsensus.Container
+
+  fields = [
+    :index,
+    :column,
+    :kzg_commitments,
+    :kzg_proofs,
+    :signed_block_header,
+    :kzg_commitments_inclusion_proof
+  ]
+
+  @enforce_keys fields
+  defstruct fields
+
+  @type t :: %__MODULE__{
+          index: Types.column_index(),
+          # Max size: MAX_BLOB_COMMITMENTS_PER_BLOCK
+          column: list(Types.cell()),
+          # Max size: MAX_BLOB_COMMITMENTS_PER_BLOCK
+          kzg_commitments: list(Types.kzg_commitment()),
+          # Max size: MAX_BLOB_COMMITMENTS_PER_BLOCK
+          kzg_proofs: list(Types.kzg_proof()),
+          signed_block_header: Types.SignedBeaconBlockHeader.t(),
+          # Size: KZG_COMMITMENTS_INCLUSION_PROOF_DEPTH
+          kzg_commitments_inclusion_proof: list(Types.bytes32())
+        }
+
+  @impl LambdaEthereumConsensus.Container
+  def schema() do
+    max_blob_commitments = ChainSpec.get("MAX_BLOB_COMMITMENTS_PER_BLOCK")
+
+    [
+      index: TypeAliases.column_index(),
+      column: {:list, TypeAliases.cell(), max_blob_commitments},
+      kzg_commitments: {:list, TypeAliases.kzg_commitment(), max_blob_commitments},
+      kzg_proofs: {:list, TypeAliases.kzg_proof(), max_blob_commitments},
+      signed_block_header: Types.SignedBeaconBlockHeader,
+      kzg_commitments_inclusion_proof:
+        {:vector, TypeAliases.bytes32(), ChainSpec.get("KZG_COMMITMENTS_INCLUSION_PROOF_DEPTH")}
+    ]
+  end
+end
@@ -0,0 +1,30 @@
+defmodule Types.DataColumnsByRootIdentifier do
+  @moduledoc """
+  Struct definition for `DataColumnsByRootIdentifier`.
+  Related definitions in `native/ssz_nif/src/types/`.
+  """
+  use LambdaEthereumConsensus.Container
+
+  fields = [
+    :block_root,
+    :columns
+  ]
+
+  @enforce_keys fields
+  defstruct fields
+
+  @type t :: %__MODULE__{
+          block_root: Types.root(),
+          # Max size: NUMBER_OF_COLUMNS
+          columns: list(Types.column_index())
+        }
+
+  @impl LambdaEthereumConsensus.Container
+  def schema() do
+    [
+      {:block_root, TypeAliases.root()},
+      # NUMBER_OF_COLUMNS is defined as CELLS_PER_EXT_BLOB
+      {:columns, {:list, TypeAliases.column_index(), ChainSpec.get("CELLS_PER_EXT_BLOB")}}
+    ]
+  end
+end
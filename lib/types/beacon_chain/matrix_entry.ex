@@ -0,0 +1,34 @@
+defmodule Types.MatrixEntry do
+  @moduledoc """
+  Struct definition for `MatrixEntry`, a cell of the extended blob matrix.
+  Related definitions in `native/ssz_nif/src/types/`.
+  """
+  use LambdaEthereumConsensus.Container
+
+  fields = [
+    :cell,
+    :kzg_proof,
+    :column_index,
+    :row_index
+  ]
+
+  @enforce_keys fields
+  defstruct fields
+
+  @type t :: %__MODULE__{
+          cell: Types.cell(),
+          kzg_proof: Types.kzg_proof(),
+          column_index: Types.column_index(),
+          row_index: Types.row_index()
+        }
+
+  @impl LambdaEthereumConsensus.Container
+  def schema() do
+    [
+      cell: TypeAliases.cell(),
+      kzg_proof: TypeAliases.kzg_proof(),
+      column_index: TypeAliases.column_index(),
+      row_index: TypeAliases.row_index()
+    ]
+  end
+end
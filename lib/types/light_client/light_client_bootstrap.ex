@@ -0,0 +1,32 @@
+defmodule Types.LightClientBootstrap do
+  @moduledoc """
+  Struct definition for `LightClientBootstrap`.
+  Related definitions in `native/ssz_nif/src/types/`.
+  """
+  use LambdaEthereumConsensus.Container
+
+  fields = [
+    :header,
+    :current_sync_committee,
+    :current_sync_committee_branch
+  ]
+
+  @enforce_keys fields
+  defstruct fields
+
+  @type t :: %__MODULE__{
+          header: Types.LightClientHeader.t(),
+          current_sync_committee: Types.SyncCommittee.t(),
+          # Size: floorlog2(CURRENT_SYNC_COMMITTEE_GINDEX_ELECTRA)
+          current_sync_committee_branch: list(Types.bytes32())
+        }
+
+  @impl LambdaEthereumConsensus.Container
+  def schema() do
+    [
+      header: Types.LightClientHeader,
+      current_sync_committee: Types.SyncCommittee,
+      current_sync_committee_branch: TypeAliases.current_sync_committee_branch()
+    ]
+  end
+end
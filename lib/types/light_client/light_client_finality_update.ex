@@ -0,0 +1,38 @@
+defmodule Types.LightClientFinalityUpdate do
+  @moduledoc """
+  Struct definition for `LightClientFinalityUpdate`.
+  Related definitions in `native/ssz_nif/src/types/`.
+  """
+  use LambdaEthereumConsensus.Container
+
+  fields = [
+    :attested_header,
+    :finalized_header,
+    :finality_branch,
+    :sync_aggregate,
+    :signature_slot
+  ]
+
+  @enforce_keys fields
+  defstruct fields
+
+  @type t :: %__MODULE__{
+          attested_header: Types.LightClientHeader.t(),
+          finalized_header: Types.LightClientHeader.t(),
+          # Size: floorlog2(FINALIZED_ROOT_GINDEX_ELECTRA)
+          finality_branch: list(Types.bytes32()),
+          sync_aggregate: Types.SyncAggregate.t(),
+          signature_slot: Types.slot()
+        }
+
+  @impl LambdaEthereumConsensus.Container
+  def schema() do
+    [
+      attested_header: Types.LightClientHeader,
+      finalized_header: Types.LightClientHeader,
+      finality_branch: TypeAliases.finality_branch(),
+      sync_aggregate: Types.SyncAggregate,
+      signature_slot: TypeAliases.slot()
+    ]
+  end
+end
@@ -0,0 +1,32 @@
+defmodule Types.LightClientHeader do
+  @moduledoc """
+  Struct definition for `LightClientHeader`.
+  Related definitions in `native/ssz_nif/src/types/`.
+  """
+  use LambdaEthereumConsensus.Container
+
+  fields = [
+    :beacon,
+    :execution,
+    :execution_branch
+  ]
+
+  @enforce_keys fields
+  defstruct fields
+
+  @type t :: %__MODULE__{
+          beacon: Types.BeaconBlockHeader.t(),
+          execution: Types.ExecutionPayloadHeader.t(),
+          # Size: floorlog2(EXECUTION_PAYLOAD_GINDEX)
+          execution_branch: list(Types.bytes32())
+        }
+
+  @impl LambdaEthereumConsensus.Container
+  def schema() do
+    [
+      beacon: Types.BeaconBlockHeader,
+      execution: Types.ExecutionPayloadHeader,
+      execution_branch: TypeAliases.execution_branch()
+    ]
+  end
+end
@@ -0,0 +1,31 @@
+defmodule Types.LightClientOptimisticUpdate do
+  @moduledoc """
+  Struct definition for `LightClientOptimisticUpdate`.
+  Related definitions in `native/ssz_nif/src/types/`.
+  """
+  use LambdaEthereumConsensus.Container
+
+  fields = [
+    :attested_header,
+    :sync_aggregate,
+    :signature_slot
+  ]
+
+  @enforce_keys fields
+  defstruct fields
+
+  @type t :: %__MODULE__{
+          attested_header: Types.LightClientHeader.t(),
+          sync_aggregate: Types.SyncAggregate.t(),
+          signature_slot: Types.slot()
+        }
+
+  @impl LambdaEthereumConsensus.Container
+  def schema() do
+    [
+      attested_header: Types.LightClientHeader,
+      sync_aggregate: Types.SyncAggregate,
+      signature_slot: TypeAliases.slot()
+    ]
+  end
+end
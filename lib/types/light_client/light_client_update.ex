@@ -0,0 +1,49 @@
+defmodule Types.LightClientUpdate do
+  @moduledoc """
+  Struct definition for `LightClientUpdate`.
+  Related definitions in `native/ssz_nif/src/types/`.
+  """
+  use LambdaEthereumConsensus.Container
+
+  fields = [
+    :attested_header,
+    :next_sync_committee,
+    :next_sync_committee_branch,
+    :finalized_header,
+    :finality_branch,
+    :sync_aggregate,
+    :signature_slot
+  ]
+
+  @enforce_keys fields
+  defstruct fields
+
+  @type t :: %__MODULE__{
+          # Header attested to by the sync committee
+          attested_header: Types.LightClientHeader.t(),
+          # Next sync committee corresponding to `attested_header.beacon.state_root`
+          next_sync_committee: Types.SyncCommittee.t(),
+          # Size: floorlog2(NEXT_SYNC_COMMITTEE_GINDEX_ELECTRA)
+          next_sync_committee_branch: list(Types.bytes32()),
+          # Finalized header corresponding to `attested_header.beacon.state_root`
+          finalized_header: Types.LightClientHeader.t(),
+          # Size: floorlog2(FINALIZED_ROOT_GINDEX_ELECTRA)
+          finality_branch: list(Types.bytes32()),
+          sync_aggregate: Types.SyncAggregate.t(),
+          # Slot at which the aggregate signature was created (untrusted)
+          signature_slot: Types.slot()
+        }
+
+  @impl LambdaEthereumConsensus.Container
+  def schema() do
+    [
+      attested_header: Types.LightClientHeader,
+      next_sync_committee: Types.SyncCommittee,
+      next_sync_committee_branch: TypeAliases.next_sync_committee_branch(),
+      finalized_header: Types.LightClientHeader,
+      finality_branch: TypeAliases.finality_branch(),
+      sync_aggregate: Types.SyncAggregate,
+      signature_slot: TypeAliases.slot()
+    ]
+  end
+end
@@ -4,6 +4,7 @@ defmodule Types.Metadata do
   Related definitions in `native/ssz_nif/src/types/`.
   """
 
+  alias LambdaEthereumConsensus.Utils
   alias LambdaEthereumConsensus.Utils.BitVector
 
   fields = [
@@ -39,7 +40,8 @@ defmodule Types.Metadata do
     # TODO: remove when migrating to the new SSZ lib
     map
     |> Map.update!(:attnets, &BitVector.to_bytes/1)
-    |> Map.update!(:syncnets, &BitVector.to_bytes/1)
+    # Sync committee subnets are new in Altair
+    |> Utils.update_non_nil!(:syncnets, &BitVector.to_bytes/1)
   end
 
   def decode(%__MODULE__{} = map) do
@@ -49,6 +51,6 @@ defmodule Types.Metadata do
 
     map
     |> Map.update!(:attnets, &BitVector.new(&1, subnet_count))
-    |> Map.update!(:syncnets, &BitVector.new(&1, syncnet_count))
+    |> Utils.update_non_nil!(:syncnets, &BitVector.new(&1, syncnet_count))
   end
 end
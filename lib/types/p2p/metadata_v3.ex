@@ -0,0 +1,48 @@
+defmodule Types.MetadataV3 do
+  @moduledoc """
+  Struct definition for the Fulu `MetaData`, which adds `custody_group_count`.
+  Related definitions in `native/ssz_nif/src/types/`.
+  """
+
+  alias LambdaEthereumConsensus.Utils.BitVector
+
+  fields = [
+    :seq_number,
+    :attnets,
+    :syncnets,
+    :custody_group_count
+  ]
+
+  @enforce_keys fields
+  defstruct fields
+
+  @type t :: %__MODULE__{
+          seq_number: Types.uint64(),
+          attnets: Types.bitvector(),
+          syncnets: Types.bitvector(),
+          custody_group_count: Types.uint64()
+        }
+
+  def schema(),
+    do: [
+      seq_number: TypeAliases.uint64(),
+      attnets: {:bitvector, ChainSpec.get("ATTESTATION_SUBNET_COUNT")},
+      syncnets: {:bitvector, Constants.sync_committee_subnet_count()},
+      custody_group_count: TypeAliases.uint64()
+    ]
+
+  def encode(%__MODULE__{} = map) do
+    map
+    |> Map.update!(:attnets, &BitVector.to_bytes/1)
+    |> Map.update!(:syncnets, &BitVector.to_bytes/1)
+  end
+
+  def decode(%__MODULE__{} = map) do
+    subnet_count = ChainSpec.get("ATTESTATION_SUBNET_COUNT")
+    syncnet_count = Constants.sync_committee_subnet_count()
+
+    map
+    |> Map.update!(:attnets, &BitVector.new(&1, subnet_count))
+    |> Map.update!(:syncnets, &BitVector.new(&1, syncnet_count))
+  end
+end
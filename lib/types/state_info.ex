@@ -7,6 +7,7 @@ defmodule Types.StateInfo do
     state after
   Warning: Do not modify this manually. If you do, you may need to re-encode the beacon state using `from_beacon_state`.
   """
+  alias LambdaEthereumConsensus.StateTransition.StateRootCache
   alias Types.BeaconState
 
   defstruct [:root, :beacon_state, :encoded, :block_root]
@@ -32,7 +33,7 @@ defmodule Types.StateInfo do
 
   @spec from_beacon_state(Types.BeaconState.t(), binary(), Types.root()) :: t()
   def from_beacon_state(%BeaconState{} = state, encoded, block_root) do
-    root = Ssz.hash_tree_root!(state)
+    root = StateRootCache.root!(encoded)
     %__MODULE__{root: root, beacon_state: state, encoded: encoded, block_root: block_root}
   end
 
@@ -25,6 +25,14 @@ defmodule TypeAliases do
       {:byte_vector,
        Constants.bytes_per_field_element() * ChainSpec.get("FIELD_ELEMENTS_PER_BLOB")}
 
+  def column_index(), do: uint64()
+  def row_index(), do: uint64()
+
+  def cell(),
+    do:
+      {:byte_vector,
+       Constants.bytes_per_field_element() * ChainSpec.get("FIELD_ELEMENTS_PER_CELL")}
+
   def kzg_commitment(), do: {:byte_vector, 48}
   def kzg_proof(), do: {:byte_vector, 48}
 
@@ -45,4 +53,10 @@ defmodule TypeAliases do
     do: {:list, Types.BlobIdentifier, ChainSpec.get("MAX_REQUEST_BLOB_SIDECARS")}
 
   def error_message(), do: {:byte_list, 256}
+
+  # Branches of the light client proofs, of depth `floorlog2(gindex)`
+  def execution_branch(), do: {:vector, bytes32(), 4}
+  def current_sync_committee_branch(), do: {:vector, bytes32(), 6}
+  def next_sync_committee_branch(), do: {:vector, bytes32(), 6}
+  def finality_branch(), do: {:vector, bytes32(), 7}
 end
@@ -44,6 +44,10 @@ defmodule Types do
   @type blob_index :: uint64
   # Max size: BYTES_PER_FIELD_ELEMENT * FIELD_ELEMENTS_PER_BLOB
   @type blob :: binary
+  @type column_index :: uint64
+  @type row_index :: uint64
+  # Size: BYTES_PER_FIELD_ELEMENT * FIELD_ELEMENTS_PER_CELL
+  @type cell :: binary
   @type kzg_commitment :: Kzg.commitment()
   @type kzg_proof :: Kzg.proof()
   @type bls_signature :: Bls.signature()
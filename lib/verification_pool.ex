@@ -0,0 +1,42 @@
+defmodule VerificationPool do
+  @moduledoc """
+  Native worker pools that run the asynchronous verification functions of `Bls`
+  and `Kzg` (the ones ending in `_async`) without holding a scheduler thread.
+  Each NIF library starts its own pool when loaded.
+
+  Submitting a job returns a reference right away. When the job finishes, its
+  result is sent to the caller as a `{reference, result}` message, where
+  `result` is what the synchronous version of the function would return.
+
+  Each pool has a bounded queue. A job submitted while it's full isn't run, and
+  its result is `{:error, :queue_full}`.
+  """
+
+  @typedoc """
+  Tags the result of an asynchronous job.
+  """
+  @type job :: reference()
+
+  @doc """
+  Number of threads in each pool, set with the `--verification-workers` flag.
+  When `nil`, pools have one thread per core.
+  """
+  @spec workers() :: pos_integer() | nil
+  def workers() do
+    Application.get_env(:lambda_ethereum_consensus, __MODULE__, [])
+    |> Keyword.get(:workers)
+  end
+
+  @doc """
+  Waits for the result of `job`. If the timeout expires, the result will still
+  arrive later and should be flushed by the caller.
+  """
+  @spec await(job(), timeout()) :: {:ok, term()} | {:error, term()}
+  def await(job, timeout \\ 5000) do
+    receive do
+      {^job, result} -> result
+    after
+      timeout -> {:error, :timeout}
+    end
+  end
+end
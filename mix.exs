@@ -39,10 +39,8 @@ defmodule LambdaEthereumConsensus.MixProject do
       {:eleveldb,
        git: "https://github.com/basho/eleveldb", ref: "riak_kv-3.0.12", override: true},
       {:jason, "~> 1.4"},
-      {:scrypt_elixir, "~> 0.1.1", hex: :scrypt_elixir_copy},
       {:joken, "~> 2.6"},
       {:rustler, "~> 0.32", runtime: false},
-      {:snappyer, "~> 1.2"},
       {:yaml_elixir, "~> 2.8"},
       {:timex, "~> 3.7"},
       {:recase, "~> 0.7"},
@@ -0,0 +1,129 @@
+//! Errors returned by the NIFs, encoded as stable atoms or tagged tuples.
+//! See `docs/nif_errors.md` for the full list.
+
+use bls::{PUBLIC_KEY_BYTES_LEN, SECRET_KEY_BYTES_LEN, SIGNATURE_BYTES_LEN};
+use rustler::{Encoder, Env, Term};
+
+mod atoms {
+    rustler::atoms! {
+        infinity_pubkey,
+        invalid_pubkey_encoding,
+        invalid_signature_encoding,
+        invalid_secret_key,
+        zero_secret_key,
+        pubkey_length,
+        signature_length,
+        secret_key_length,
+        message_length,
+        empty_pubkeys,
+        empty_signatures,
+        unknown_validator_index,
+        invalid_cached_pubkey,
+        non_contiguous_extend,
+        invalid_derivation_path,
+        seed_too_short,
+        poisoned_lock,
+    }
+}
+
+pub(crate) const MESSAGE_LENGTH: usize = 32;
+
+pub(crate) enum BlsError {
+    InfinityPubkey,
+    InvalidPubkeyEncoding(String),
+    InvalidSignatureEncoding(String),
+    InvalidSecretKey(String),
+    ZeroSecretKey,
+    PubkeyLength(usize),
+    SignatureLength(usize),
+    SecretKeyLength(usize),
+    MessageLength(usize),
+    EmptyPubkeys,
+    EmptySignatures,
+    UnknownValidatorIndex(u64),
+    InvalidCachedPubkey(u64),
+    NonContiguousExtend { cached: usize, from_index: usize },
+    InvalidDerivationPath(String),
+    SeedTooShort(usize),
+    PoisonedLock,
+}
+
+impl BlsError {
+    pub(crate) fn from_pubkey(e: bls::Error) -> Self {
+        match e {
+            bls::Error::InvalidInfinityPublicKey => BlsError::InfinityPubkey,
+            bls::Error::InvalidByteLength { got, .. } => BlsError::PubkeyLength(got),
+            bls::Error::BlstError(e) => BlsError::InvalidPubkeyEncoding(format!("{:?}", e)),
+            e => BlsError::InvalidPubkeyEncoding(format!("{:?}", e)),
+        }
+    }
+
+    pub(crate) fn from_signature(e: bls::Error) -> Self {
+        match e {
+            bls::Error::InvalidByteLength { got, .. } => BlsError::SignatureLength(got),
+            bls::Error::BlstError(e) => BlsError::InvalidSignatureEncoding(format!("{:?}", e)),
+            e => BlsError::InvalidSignatureEncoding(format!("{:?}", e)),
+        }
+    }
+
+    pub(crate) fn from_secret_key(e: bls::Error) -> Self {
+        match e {
+            bls::Error::InvalidZeroSecretKey => BlsError::ZeroSecretKey,
+            bls::Error::InvalidSecretKeyLength { got, .. }
+            | bls::Error::InvalidByteLength { got, .. } => BlsError::SecretKeyLength(got),
+            bls::Error::BlstError(e) => BlsError::InvalidSecretKey(format!("{:?}", e)),
+            e => BlsError::InvalidSecretKey(format!("{:?}", e)),
+        }
+    }
+}
+
+impl Encoder for BlsError {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            BlsError::InfinityPubkey => atoms::infinity_pubkey().encode(env),
+            BlsError::InvalidPubkeyEncoding(detail) => {
+                (atoms::invalid_pubkey_encoding(), detail).encode(env)
+            }
+            BlsError::InvalidSignatureEncoding(detail) => {
+                (atoms::invalid_signature_encoding(), detail).encode(env)
+            }
+            BlsError::InvalidSecretKey(detail) => (atoms::invalid_secret_key(), detail).encode(env),
+            BlsError::ZeroSecretKey => atoms::zero_secret_key().encode(env),
+            BlsError::PubkeyLength(got) => {
+                (atoms::pubkey_length(), got, PUBLIC_KEY_BYTES_LEN).encode(env)
+            }
+            BlsError::SignatureLength(got) => {
+                (atoms::signature_length(), got, SIGNATURE_BYTES_LEN).encode(env)
+            }
+            BlsError::SecretKeyLength(got) => {
+                (atoms::secret_key_length(), got, SECRET_KEY_BYTES_LEN).encode(env)
+            }
+            BlsError::MessageLength(got) => {
+                (atoms::message_length(), got, MESSAGE_LENGTH).encode(env)
+            }
+            BlsError::EmptyPubkeys => atoms::empty_pubkeys().encode(env),
+            BlsError::EmptySignatures => atoms::empty_signatures().encode(env),
+            BlsError::UnknownValidatorIndex(index) => {
+                (atoms::unknown_validator_index(), index).encode(env)
+            }
+            BlsError::InvalidCachedPubkey(index) => {
+                (atoms::invalid_cached_pubkey(), index).encode(env)
+            }
+            BlsError::NonContiguousExtend { cached, from_index } => {
+                (atoms::non_contiguous_extend(), cached, from_index).encode(env)
+            }
+            BlsError::InvalidDerivationPath(detail) => {
+                (atoms::invalid_derivation_path(), detail).encode(env)
+            }
+            BlsError::SeedTooShort(got) => (atoms::seed_too_short(), got).encode(env),
+            BlsError::PoisonedLock => atoms::poisoned_lock().encode(env),
+        }
+    }
+}
+
+pub(crate) fn check_message(message: &[u8]) -> Result<(), BlsError> {
+    if message.len() != MESSAGE_LENGTH {
+        return Err(BlsError::MessageLength(message.len()));
+    }
+    Ok(())
+}
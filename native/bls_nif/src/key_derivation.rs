@@ -0,0 +1,84 @@
+//! [EIP-2333](https://eips.ethereum.org/EIPS/eip-2333) key derivation along
+//! [EIP-2334](https://eips.ethereum.org/EIPS/eip-2334) paths.
+
+use bls::SecretKey;
+use eth2_key_derivation::DerivedKey;
+use rustler::{Binary, Env, ResourceArc};
+
+use crate::bytes_to_binary;
+use crate::error::BlsError;
+use crate::secret_key::SecretKeyResource;
+
+const MIN_SEED_LENGTH: usize = 32;
+const PURPOSE: u32 = 12381;
+
+// Parses an EIP-2334 path like "m/12381/3600/0/0/0" into its indices.
+// The coin type isn't checked, as older keystores use "m/12381/60/...".
+fn parse_path(path: &str) -> Result<Vec<u32>, BlsError> {
+    let mut nodes = path.split('/');
+    if nodes.next() != Some("m") {
+        return Err(BlsError::InvalidDerivationPath(
+            "Path must start with \"m\"".to_owned(),
+        ));
+    }
+    let indices = nodes
+        .map(|node| {
+            node.parse::<u32>().map_err(|_| {
+                BlsError::InvalidDerivationPath(format!("Invalid path index: {:?}", node))
+            })
+        })
+        .collect::<Result<Vec<u32>, _>>()?;
+    match indices.first() {
+        Some(&PURPOSE) => Ok(indices),
+        Some(purpose) => Err(BlsError::InvalidDerivationPath(format!(
+            "Invalid path purpose: {}",
+            purpose
+        ))),
+        None => Err(BlsError::InvalidDerivationPath("Empty path".to_owned())),
+    }
+}
+
+fn master_key(seed: &[u8]) -> Result<DerivedKey, BlsError> {
+    if seed.len() < MIN_SEED_LENGTH {
+        return Err(BlsError::SeedTooShort(seed.len()));
+    }
+    // The only failure is an empty seed, which was ruled out above.
+    DerivedKey::from_seed(seed).map_err(|_| BlsError::SeedTooShort(seed.len()))
+}
+
+fn derive_from_path(seed: &[u8], path: &str) -> Result<DerivedKey, BlsError> {
+    let indices = parse_path(path)?;
+    let master = master_key(seed)?;
+    Ok(indices
+        .into_iter()
+        .fold(master, |key, index| key.child(index)))
+}
+
+#[rustler::nif]
+fn parse_derivation_path(path: &str) -> Result<Vec<u32>, BlsError> {
+    parse_path(path)
+}
+
+#[rustler::nif]
+fn derive_master_sk(seed: Binary) -> Result<ResourceArc<SecretKeyResource>, BlsError> {
+    let master = master_key(seed.as_slice())?;
+    let sk = SecretKey::deserialize(master.secret()).map_err(BlsError::from_secret_key)?;
+    Ok(SecretKeyResource::new(sk))
+}
+
+// Returns the secret and public keys found at `path`.
+#[rustler::nif]
+fn derive_key_from_path<'env>(
+    env: Env<'env>,
+    seed: Binary,
+    path: &str,
+) -> Result<(ResourceArc<SecretKeyResource>, Binary<'env>), BlsError> {
+    let derived = derive_from_path(seed.as_slice(), path)?;
+    let sk = SecretKey::deserialize(derived.secret()).map_err(BlsError::from_secret_key)?;
+    let public_key_bytes = sk.public_key().serialize();
+
+    Ok((
+        SecretKeyResource::new(sk),
+        bytes_to_binary(env, &public_key_bytes),
+    ))
+}
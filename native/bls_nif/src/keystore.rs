@@ -0,0 +1,118 @@
+//! [EIP-2335](https://eips.ethereum.org/EIPS/eip-2335) keystore encryption and decryption.
+
+use eth2_keystore::json_keystore::{Crypto, Kdf, Pbkdf2, Prf, Scrypt};
+use eth2_keystore::{decrypt, keypair_from_secret, Error, KeystoreBuilder, DKLEN, SALT_SIZE};
+use rand::Rng;
+use rustler::{Binary, Env, NifTaggedEnum, ResourceArc};
+use serde_json::Value;
+
+use crate::bytes_to_binary;
+use crate::secret_key::SecretKeyResource;
+
+#[derive(NifTaggedEnum)]
+enum KeystoreError {
+    InvalidPassword,
+    InvalidChecksum,
+    UnsupportedKdf(String),
+    InvalidKeystore(String),
+    EncryptionFailed(String),
+}
+
+/// KDF parameters for new keystores. A random salt is generated for each one.
+#[derive(NifTaggedEnum)]
+enum KdfParams {
+    Scrypt { n: u32, r: u32, p: u32 },
+    Pbkdf2 { c: u32 },
+}
+
+impl KdfParams {
+    fn into_kdf(self) -> Kdf {
+        let salt = rand::thread_rng().gen::<[u8; SALT_SIZE]>().to_vec();
+        match self {
+            KdfParams::Scrypt { n, r, p } => Kdf::Scrypt(Scrypt {
+                dklen: DKLEN,
+                n,
+                r,
+                p,
+                salt: salt.into(),
+            }),
+            KdfParams::Pbkdf2 { c } => Kdf::Pbkdf2(Pbkdf2 {
+                dklen: DKLEN,
+                c,
+                prf: Prf::HmacSha256,
+                salt: salt.into(),
+            }),
+        }
+    }
+}
+
+// The keystore parser doesn't tell these cases apart, so we check them beforehand.
+fn check_crypto_functions(crypto: &Value) -> Result<(), KeystoreError> {
+    match crypto["kdf"]["function"].as_str() {
+        Some("scrypt") | Some("pbkdf2") => {}
+        Some(function) => return Err(KeystoreError::UnsupportedKdf(function.to_owned())),
+        None => {
+            return Err(KeystoreError::InvalidKeystore(
+                "Missing KDF function".to_owned(),
+            ))
+        }
+    }
+
+    let checksum = &crypto["checksum"];
+    let checksum_message = checksum["message"].as_str().unwrap_or_default();
+    let valid_checksum = checksum["function"].as_str() == Some("sha256")
+        && checksum_message.len() == 64
+        && checksum_message.chars().all(|c| c.is_ascii_hexdigit());
+    if !valid_checksum {
+        return Err(KeystoreError::InvalidChecksum);
+    }
+    Ok(())
+}
+
+// Returns the secret and public keys held in the keystore.
+// Only the "crypto" module is used, so "pubkey" and "path" may be missing.
+// The password is expected to be already normalized, as per EIP-2335.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn decrypt_keystore<'env>(
+    env: Env<'env>,
+    json: &str,
+    password: Binary,
+) -> Result<(ResourceArc<SecretKeyResource>, Binary<'env>), KeystoreError> {
+    let json: Value =
+        serde_json::from_str(json).map_err(|e| KeystoreError::InvalidKeystore(e.to_string()))?;
+    check_crypto_functions(&json["crypto"])?;
+    let crypto: Crypto = serde_json::from_value(json["crypto"].clone())
+        .map_err(|e| KeystoreError::InvalidKeystore(e.to_string()))?;
+
+    let secret = decrypt(password.as_slice(), &crypto).map_err(|e| match e {
+        Error::InvalidPassword => KeystoreError::InvalidPassword,
+        e => KeystoreError::InvalidKeystore(format!("{:?}", e)),
+    })?;
+    let keypair = keypair_from_secret(secret.as_bytes())
+        .map_err(|e| KeystoreError::InvalidKeystore(format!("{:?}", e)))?;
+
+    Ok((
+        SecretKeyResource::new(keypair.sk),
+        bytes_to_binary(env, &keypair.pk.serialize()),
+    ))
+}
+
+// Encrypts a secret key into a new version 4 keystore, returned as JSON.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn encrypt_keystore(
+    secret_key: ResourceArc<SecretKeyResource>,
+    password: Binary,
+    path: String,
+    kdf_params: KdfParams,
+) -> Result<String, KeystoreError> {
+    let to_error = |e: Error| KeystoreError::EncryptionFailed(format!("{:?}", e));
+    let keypair = keypair_from_secret(secret_key.0.serialize().as_bytes()).map_err(to_error)?;
+
+    KeystoreBuilder::new(&keypair, password.as_slice(), path)
+        .map_err(to_error)?
+        .kdf(kdf_params.into_kdf())
+        .build()
+        .map_err(to_error)?
+        .to_json_string()
+        .map_err(to_error)
+}
@@ -1,7 +1,20 @@
+mod error;
+mod jobs;
+mod key_derivation;
+mod keystore;
+mod pubkey_cache;
+mod secret_key;
+mod slashing_protection;
+
+use std::borrow::Cow;
 use std::io::Write;
+use std::ops::Deref;
+
+use bls::{AggregatePublicKey, AggregateSignature, Hash256, PublicKey, Signature, SignatureSet};
+use rustler::{Binary, Env, NewBinary, NifTaggedEnum, Reference, ResourceArc};
 
-use bls::{AggregatePublicKey, AggregateSignature, Hash256, PublicKey, SecretKey, Signature};
-use rustler::{Binary, Env, NewBinary};
+use crate::error::{check_message, BlsError};
+use crate::secret_key::SecretKeyResource;
 
 pub(crate) fn bytes_to_binary<'env>(env: Env<'env>, bytes: &[u8]) -> Binary<'env> {
     let mut binary = NewBinary::new(env, bytes.len());
@@ -13,50 +26,58 @@ pub(crate) fn bytes_to_binary<'env>(env: Env<'env>, bytes: &[u8]) -> Binary<'env
 // Deserialize a PublicKey from a slice of bytes.
 // Faster than PublicKey::deserialize() as it doesn't validate the key
 // Returns Error on invalid BLST encoding or on Infinity Public Key.
-fn fast_public_key_deserialize(pk: &[u8]) -> Result<PublicKey, String> {
-    if pk == &bls::INFINITY_PUBLIC_KEY[..] {
-        Err("Infinity public Key".to_owned())
+fn fast_public_key_deserialize(pk: &[u8]) -> Result<PublicKey, BlsError> {
+    if pk.len() != bls::PUBLIC_KEY_BYTES_LEN {
+        Err(BlsError::PubkeyLength(pk.len()))
+    } else if pk == &bls::INFINITY_PUBLIC_KEY[..] {
+        Err(BlsError::InfinityPubkey)
     } else {
         bls::impls::blst::types::PublicKey::from_bytes(pk)
-            .map_err(|err| format!("BlstError({:?})", err))
+            .map_err(|err| BlsError::InvalidPubkeyEncoding(format!("{:?}", err)))
             .and_then(|pk| {
                 PublicKey::deserialize_uncompressed(pk.serialize().as_slice())
                     // This should never be an error as the pk is obtained from an uncompressed valid key
-                    .map_err(|e| format!("Deserialization error: {:?}", e))
+                    .map_err(BlsError::from_pubkey)
             })
     }
 }
 
+fn public_keys_deserialize<B: Deref<Target = [u8]>>(
+    public_keys: &[B],
+) -> Result<Vec<PublicKey>, BlsError> {
+    public_keys
+        .iter()
+        .map(|pkb| fast_public_key_deserialize(pkb))
+        .collect()
+}
+
+fn aggregate_signature_deserialize(signature: &[u8]) -> Result<AggregateSignature, BlsError> {
+    AggregateSignature::deserialize(signature).map_err(BlsError::from_signature)
+}
+
 #[rustler::nif]
 fn sign<'env>(
     env: Env<'env>,
-    private_key: Binary,
+    secret_key: ResourceArc<SecretKeyResource>,
     message: Binary,
-) -> Result<Binary<'env>, String> {
-    if message.len() != 32 {
-        return Err(format!("Message must be 32 bytes long"));
-    }
-    let sk = match SecretKey::deserialize(private_key.as_slice()) {
-        Ok(sk) => sk,
-        Err(e) => return Err(format!("{:?}", e)),
-    };
-
-    let signature = sk.sign(Hash256::from_slice(message.as_slice()));
+) -> Result<Binary<'env>, BlsError> {
+    check_message(message.as_slice())?;
+    let signature = secret_key.0.sign(Hash256::from_slice(message.as_slice()));
     let bytes = signature.serialize();
 
     Ok(bytes_to_binary(env, &bytes))
 }
 
 #[rustler::nif]
-fn aggregate<'env>(env: Env<'env>, signatures: Vec<Binary>) -> Result<Binary<'env>, String> {
+fn aggregate<'env>(env: Env<'env>, signatures: Vec<Binary>) -> Result<Binary<'env>, BlsError> {
     match signatures.len() {
-        0 => return Err(format!("Empty signature vector")),
+        0 => return Err(BlsError::EmptySignatures),
         _ => {
             let sigs_result = signatures
                 .iter()
                 .map(|sig| Signature::deserialize(sig.as_slice()))
                 .collect::<Result<Vec<Signature>, _>>();
-            let sigs = sigs_result.map_err(|err| format!("{:?}", err))?;
+            let sigs = sigs_result.map_err(BlsError::from_signature)?;
             let aggr_sig = sigs
                 .iter()
                 .fold(AggregateSignature::infinity(), |mut a, b| {
@@ -69,16 +90,17 @@ fn aggregate<'env>(env: Env<'env>, signatures: Vec<Binary>) -> Result<Binary<'en
     }
 }
 
-#[rustler::nif]
-fn verify<'env>(public_key: Binary, message: Binary, signature: Binary) -> Result<bool, String> {
-    if message.len() != 32 {
-        return Err(format!("Message must be 32 bytes long"));
-    }
-    let sig = Signature::deserialize(signature.as_slice()).map_err(|err| format!("{:?}", err))?;
-    let pubkey =
-        fast_public_key_deserialize(public_key.as_slice()).map_err(|err| format!("{:?}", err))?;
+fn verify_signature(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<bool, BlsError> {
+    check_message(message)?;
+    let sig = Signature::deserialize(signature).map_err(BlsError::from_signature)?;
+    let pubkey = fast_public_key_deserialize(public_key)?;
+
+    Ok(sig.verify(&pubkey, Hash256::from_slice(message)))
+}
 
-    Ok(sig.verify(&pubkey, Hash256::from_slice(message.as_slice())))
+#[rustler::nif]
+fn verify<'env>(public_key: Binary, message: Binary, signature: Binary) -> Result<bool, BlsError> {
+    verify_signature(&public_key, &message, &signature)
 }
 
 #[rustler::nif]
@@ -86,43 +108,41 @@ fn aggregate_verify<'env>(
     public_keys: Vec<Binary>,
     messages: Vec<Binary>,
     signature: Binary,
-) -> Result<bool, String> {
-    let aggregate_sig = AggregateSignature::deserialize(signature.as_slice())
-        .map_err(|err| format!("{:?}", err))?;
-    let pubkeys_result = public_keys
-        .iter()
-        .map(|pkb| fast_public_key_deserialize(pkb.as_slice()))
-        .collect::<Result<Vec<PublicKey>, _>>();
-    let pubkeys = pubkeys_result.map_err(|err| format!("{:?}", err))?;
+) -> Result<bool, BlsError> {
+    let aggregate_sig = aggregate_signature_deserialize(signature.as_slice())?;
+    let pubkeys = public_keys_deserialize(&public_keys)?;
 
     let pubkey_refs = pubkeys.iter().collect::<Vec<_>>();
     let msgs = messages
         .iter()
-        .map(|message| Hash256::from_slice(message.as_slice()))
-        .collect::<Vec<Hash256>>();
+        .map(|message| {
+            check_message(message.as_slice())?;
+            Ok(Hash256::from_slice(message.as_slice()))
+        })
+        .collect::<Result<Vec<Hash256>, BlsError>>()?;
     Ok(aggregate_sig.aggregate_verify(&msgs, &pubkey_refs))
 }
 
+fn fast_aggregate_verify_signature<B: Deref<Target = [u8]>>(
+    public_keys: &[B],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool, BlsError> {
+    check_message(message)?;
+    let aggregate_sig = aggregate_signature_deserialize(signature)?;
+    let pubkeys = public_keys_deserialize(public_keys)?;
+
+    let pubkey_refs = pubkeys.iter().collect::<Vec<_>>();
+    Ok(aggregate_sig.fast_aggregate_verify(Hash256::from_slice(message), &pubkey_refs))
+}
+
 #[rustler::nif(schedule = "DirtyCpu")]
 fn fast_aggregate_verify<'env>(
     public_keys: Vec<Binary>,
     message: Binary,
     signature: Binary,
-) -> Result<bool, String> {
-    if message.len() != 32 {
-        return Err(format!("Message must be 32 bytes long"));
-    }
-    let aggregate_sig = AggregateSignature::deserialize(signature.as_slice())
-        .map_err(|err| format!("{:?}", err))?;
-    let pubkeys_result = public_keys
-        .iter()
-        .map(|pkb| fast_public_key_deserialize(pkb.as_slice()))
-        //.map(|pkb| PublicKey::deserialize(pkb.as_slice()))
-        .collect::<Result<Vec<PublicKey>, _>>();
-    let pubkeys = pubkeys_result.map_err(|err| format!("{:?}", err))?;
-
-    let pubkey_refs = pubkeys.iter().collect::<Vec<_>>();
-    Ok(aggregate_sig.fast_aggregate_verify(Hash256::from_slice(message.as_slice()), &pubkey_refs))
+) -> Result<bool, BlsError> {
+    fast_aggregate_verify_signature(&public_keys, &message, &signature)
 }
 
 #[rustler::nif]
@@ -130,37 +150,140 @@ fn eth_fast_aggregate_verify<'env>(
     public_keys: Vec<Binary>,
     message: Binary,
     signature: Binary,
-) -> Result<bool, String> {
-    if message.len() != 32 {
-        return Err(format!("Message must be 32 bytes long"));
-    }
-    let aggregate_sig = AggregateSignature::deserialize(signature.as_slice())
-        .map_err(|err| format!("{:?}", err))?;
-    let pubkeys_result = public_keys
-        .iter()
-        .map(|pkb| fast_public_key_deserialize(pkb.as_slice()))
-        .collect::<Result<Vec<PublicKey>, _>>();
-    let pubkeys = pubkeys_result.map_err(|err| format!("{:?}", err))?;
+) -> Result<bool, BlsError> {
+    check_message(message.as_slice())?;
+    let aggregate_sig = aggregate_signature_deserialize(signature.as_slice())?;
+    let pubkeys = public_keys_deserialize(&public_keys)?;
 
     let pubkey_refs = pubkeys.iter().collect::<Vec<_>>();
     Ok(aggregate_sig
         .eth_fast_aggregate_verify(Hash256::from_slice(message.as_slice()), &pubkey_refs))
 }
 
+/// Result of a batch verification. On failure, holds the index of the first invalid set,
+/// or is `BatchInvalid` if every set is valid on its own.
+#[derive(NifTaggedEnum)]
+enum SignatureSetsVerification {
+    Valid,
+    Invalid(usize),
+    BatchInvalid,
+}
+
+/// A signature set with its public keys, message and signature decoded.
+type DecodedSet<'a> = (Vec<&'a PublicKey>, Hash256, AggregateSignature);
+
+// Verifies many {pubkeys, message, signature} sets at once, using blst's
+// multi-verification with random linear combination. If the batch check fails,
+// sets are verified one by one to find the first offending one.
+fn verify_decoded_sets(sets: &[DecodedSet]) -> SignatureSetsVerification {
+    if sets.is_empty() {
+        return SignatureSetsVerification::Valid;
+    }
+    let signature_sets = sets
+        .iter()
+        .map(|(pubkeys, message, signature)| {
+            SignatureSet::multiple_pubkeys(
+                signature,
+                pubkeys.iter().copied().map(Cow::Borrowed).collect(),
+                *message,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if bls::verify_signature_sets(signature_sets.iter()) {
+        return SignatureSetsVerification::Valid;
+    }
+    // No set failing on its own shouldn't happen, but then there's nobody to blame
+    match signature_sets.into_iter().position(|set| !set.verify()) {
+        Some(invalid_index) => SignatureSetsVerification::Invalid(invalid_index),
+        None => SignatureSetsVerification::BatchInvalid,
+    }
+}
+
+fn verify_sets<B: Deref<Target = [u8]>>(
+    sets: &[(Vec<B>, B, B)],
+) -> Result<SignatureSetsVerification, BlsError> {
+    let mut pubkeys = Vec::with_capacity(sets.len());
+    let mut messages_and_signatures = Vec::with_capacity(sets.len());
+    for (public_keys, message, signature) in sets.iter() {
+        check_message(message)?;
+        let aggregate_sig = aggregate_signature_deserialize(signature)?;
+        pubkeys.push(public_keys_deserialize(public_keys)?);
+        messages_and_signatures.push((Hash256::from_slice(message), aggregate_sig));
+    }
+    let decoded_sets = pubkeys
+        .iter()
+        .zip(messages_and_signatures)
+        .map(|(pubkeys, (message, signature))| (pubkeys.iter().collect(), message, signature))
+        .collect::<Vec<DecodedSet>>();
+    Ok(verify_decoded_sets(&decoded_sets))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_signature_sets<'env>(
+    sets: Vec<(Vec<Binary>, Binary, Binary)>,
+) -> Result<SignatureSetsVerification, BlsError> {
+    verify_sets(&sets)
+}
+
+// Asynchronous versions of the functions above. The inputs are copied, as they
+// have to outlive the call, and are decoded by the worker.
+
+#[rustler::nif]
+fn verify_async<'env>(
+    env: Env<'env>,
+    public_key: Binary,
+    message: Binary,
+    signature: Binary,
+) -> Reference<'env> {
+    let (public_key, message, signature) =
+        (public_key.to_vec(), message.to_vec(), signature.to_vec());
+    jobs::spawn(env, move || {
+        verify_signature(&public_key, &message, &signature)
+    })
+}
+
+#[rustler::nif]
+fn fast_aggregate_verify_async<'env>(
+    env: Env<'env>,
+    public_keys: Vec<Binary>,
+    message: Binary,
+    signature: Binary,
+) -> Reference<'env> {
+    let public_keys = public_keys.iter().map(|pk| pk.to_vec()).collect::<Vec<_>>();
+    let (message, signature) = (message.to_vec(), signature.to_vec());
+    jobs::spawn(env, move || {
+        fast_aggregate_verify_signature(&public_keys, &message, &signature)
+    })
+}
+
+#[rustler::nif]
+fn verify_signature_sets_async<'env>(
+    env: Env<'env>,
+    sets: Vec<(Vec<Binary>, Binary, Binary)>,
+) -> Reference<'env> {
+    let sets = sets
+        .iter()
+        .map(|(public_keys, message, signature)| {
+            (
+                public_keys.iter().map(|pk| pk.to_vec()).collect(),
+                message.to_vec(),
+                signature.to_vec(),
+            )
+        })
+        .collect::<Vec<(Vec<Vec<u8>>, Vec<u8>, Vec<u8>)>>();
+    jobs::spawn(env, move || verify_sets(&sets))
+}
+
 #[rustler::nif]
 fn eth_aggregate_pubkeys<'env>(
     env: Env<'env>,
     public_keys: Vec<Binary>,
-) -> Result<Binary<'env>, String> {
+) -> Result<Binary<'env>, BlsError> {
     match public_keys.len() {
-        0 => return Err(format!("Empty public key vector")),
+        0 => return Err(BlsError::EmptyPubkeys),
         _ => {
-            let pubkeys_result = public_keys
-                .iter()
-                .map(|pkb| fast_public_key_deserialize(pkb.as_slice()))
-                .collect::<Result<Vec<PublicKey>, _>>();
-
-            let pubkeys = pubkeys_result.map_err(|err| format!("{:?}", err))?;
+            let pubkeys = public_keys_deserialize(&public_keys)?;
             let pubkey_refs = pubkeys.into_iter().collect::<Vec<_>>();
 
             let agg_pubkey_bytes = AggregatePublicKey::aggregate(pubkey_refs.as_slice())
@@ -173,21 +296,20 @@ fn eth_aggregate_pubkeys<'env>(
     }
 }
 #[rustler::nif]
-fn key_validate<'env>(public_key: Binary) -> Result<bool, String> {
+fn key_validate<'env>(public_key: Binary) -> Result<bool, BlsError> {
     let _pubkey = fast_public_key_deserialize(public_key.as_slice())?;
 
     Ok(true)
 }
 #[rustler::nif]
-fn derive_pubkey<'env>(env: Env<'env>, private_key: Binary) -> Result<Binary<'env>, String> {
-    let sk = match SecretKey::deserialize(private_key.as_slice()) {
-        Ok(sk) => sk,
-        Err(e) => return Err(format!("{:?}", e)),
-    };
-    let public_key = sk.public_key();
+fn derive_pubkey<'env>(
+    env: Env<'env>,
+    secret_key: ResourceArc<SecretKeyResource>,
+) -> Result<Binary<'env>, BlsError> {
+    let public_key = secret_key.0.public_key();
     let public_key_bytes = public_key.serialize();
 
     Ok(bytes_to_binary(env, &public_key_bytes))
 }
 
-rustler::init!("Elixir.Bls");
+rustler::init!("Elixir.Bls", load = jobs::load);
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::sync::{RwLock, RwLockReadGuard};
+
+use bls::{Hash256, PublicKey};
+use rustler::{Binary, Env, ResourceArc};
+
+use crate::error::{check_message, BlsError};
+use crate::{
+    aggregate_signature_deserialize, bytes_to_binary, fast_public_key_deserialize,
+    verify_decoded_sets, DecodedSet, SignatureSetsVerification,
+};
+
+/// Decompressed validator public keys, addressed by validator index.
+///
+/// The validator registry is append-only, so the cache only ever grows.
+/// Keys that fail to decompress are kept as `None` so indices stay aligned
+/// with the registry, and any verification that uses them fails.
+pub(crate) struct PubkeyCache {
+    inner: RwLock<PubkeyCacheInner>,
+}
+
+#[derive(Default)]
+struct PubkeyCacheInner {
+    pubkeys: Vec<Option<PublicKey>>,
+    indices: HashMap<Vec<u8>, usize>,
+}
+
+#[rustler::resource_impl]
+impl rustler::Resource for PubkeyCache {}
+
+impl PubkeyCacheInner {
+    fn get_pubkeys(&self, indices: &[u64]) -> Result<Vec<&PublicKey>, BlsError> {
+        indices
+            .iter()
+            .map(|&index| match self.pubkeys.get(index as usize) {
+                Some(Some(pubkey)) => Ok(pubkey),
+                Some(None) => Err(BlsError::InvalidCachedPubkey(index)),
+                None => Err(BlsError::UnknownValidatorIndex(index)),
+            })
+            .collect()
+    }
+}
+
+fn read_cache(cache: &PubkeyCache) -> Result<RwLockReadGuard<'_, PubkeyCacheInner>, BlsError> {
+    cache.inner.read().map_err(|_| BlsError::PoisonedLock)
+}
+
+#[rustler::nif]
+fn pubkey_cache_new() -> ResourceArc<PubkeyCache> {
+    ResourceArc::new(PubkeyCache {
+        inner: RwLock::new(PubkeyCacheInner::default()),
+    })
+}
+
+// Appends the given keys, in registry order, starting at validator index `from_index`.
+// Keys already present are skipped, so concurrent callers can't add them twice.
+// Returns the new cache length.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn pubkey_cache_extend(
+    cache: ResourceArc<PubkeyCache>,
+    from_index: usize,
+    public_keys: Vec<Binary>,
+) -> Result<usize, BlsError> {
+    let decompressed = public_keys
+        .iter()
+        .map(|pkb| fast_public_key_deserialize(pkb.as_slice()).ok())
+        .collect::<Vec<_>>();
+
+    let mut inner = cache.inner.write().map_err(|_| BlsError::PoisonedLock)?;
+    let cached = inner.pubkeys.len();
+    if from_index > cached {
+        return Err(BlsError::NonContiguousExtend { cached, from_index });
+    }
+    let new_keys = public_keys
+        .iter()
+        .zip(decompressed)
+        .skip(cached - from_index);
+    for (pkb, pubkey) in new_keys {
+        let index = inner.pubkeys.len();
+        inner
+            .indices
+            .entry(pkb.as_slice().to_vec())
+            .or_insert(index);
+        inner.pubkeys.push(pubkey);
+    }
+    Ok(inner.pubkeys.len())
+}
+
+#[rustler::nif]
+fn pubkey_cache_len(cache: ResourceArc<PubkeyCache>) -> Result<usize, BlsError> {
+    Ok(read_cache(&cache)?.pubkeys.len())
+}
+
+#[rustler::nif]
+fn pubkey_cache_get<'env>(
+    env: Env<'env>,
+    cache: ResourceArc<PubkeyCache>,
+    index: u64,
+) -> Result<Binary<'env>, BlsError> {
+    let inner = read_cache(&cache)?;
+    let pubkey = inner.get_pubkeys(&[index])?[0];
+    Ok(bytes_to_binary(env, &pubkey.serialize()))
+}
+
+#[rustler::nif]
+fn pubkey_cache_index_of(
+    cache: ResourceArc<PubkeyCache>,
+    public_key: Binary,
+) -> Result<Option<usize>, BlsError> {
+    Ok(read_cache(&cache)?
+        .indices
+        .get(public_key.as_slice())
+        .copied())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn fast_aggregate_verify_indices(
+    cache: ResourceArc<PubkeyCache>,
+    indices: Vec<u64>,
+    message: Binary,
+    signature: Binary,
+) -> Result<bool, BlsError> {
+    check_message(message.as_slice())?;
+    let aggregate_sig = aggregate_signature_deserialize(signature.as_slice())?;
+    let inner = read_cache(&cache)?;
+    let pubkey_refs = inner.get_pubkeys(&indices)?;
+
+    Ok(aggregate_sig.fast_aggregate_verify(Hash256::from_slice(message.as_slice()), &pubkey_refs))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn eth_fast_aggregate_verify_indices(
+    cache: ResourceArc<PubkeyCache>,
+    indices: Vec<u64>,
+    message: Binary,
+    signature: Binary,
+) -> Result<bool, BlsError> {
+    check_message(message.as_slice())?;
+    let aggregate_sig = aggregate_signature_deserialize(signature.as_slice())?;
+    let inner = read_cache(&cache)?;
+    let pubkey_refs = inner.get_pubkeys(&indices)?;
+
+    Ok(aggregate_sig
+        .eth_fast_aggregate_verify(Hash256::from_slice(message.as_slice()), &pubkey_refs))
+}
+
+/// Same as `verify_signature_sets`, with the public keys of each set given as
+/// validator indices into the cache.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_signature_sets_indices(
+    cache: ResourceArc<PubkeyCache>,
+    sets: Vec<(Vec<u64>, Binary, Binary)>,
+) -> Result<SignatureSetsVerification, BlsError> {
+    let inner = read_cache(&cache)?;
+    let decoded_sets = sets
+        .iter()
+        .map(|(indices, message, signature)| {
+            check_message(message.as_slice())?;
+            let message = Hash256::from_slice(message.as_slice());
+            let aggregate_sig = aggregate_signature_deserialize(signature.as_slice())?;
+            Ok((inner.get_pubkeys(indices)?, message, aggregate_sig))
+        })
+        .collect::<Result<Vec<DecodedSet>, BlsError>>()?;
+    Ok(verify_decoded_sets(&decoded_sets))
+}
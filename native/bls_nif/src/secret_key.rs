@@ -0,0 +1,34 @@
+use bls::SecretKey;
+use rustler::{Binary, Env, ResourceArc};
+
+use crate::bytes_to_binary;
+use crate::error::BlsError;
+
+/// A secret key held in native memory, so it never lives in BEAM binaries.
+///
+/// The inner blst scalar is zeroized when the last reference to it is dropped.
+pub(crate) struct SecretKeyResource(pub(crate) SecretKey);
+
+#[rustler::resource_impl]
+impl rustler::Resource for SecretKeyResource {}
+
+impl SecretKeyResource {
+    pub(crate) fn new(sk: SecretKey) -> ResourceArc<Self> {
+        ResourceArc::new(Self(sk))
+    }
+}
+
+#[rustler::nif]
+fn secret_key_from_bytes(private_key: Binary) -> Result<ResourceArc<SecretKeyResource>, BlsError> {
+    let sk = SecretKey::deserialize(private_key.as_slice()).map_err(BlsError::from_secret_key)?;
+    Ok(SecretKeyResource::new(sk))
+}
+
+// The only way to get the secret key's bytes back into Elixir.
+#[rustler::nif]
+fn export_secret_key<'env>(
+    env: Env<'env>,
+    secret_key: ResourceArc<SecretKeyResource>,
+) -> Binary<'env> {
+    bytes_to_binary(env, secret_key.0.serialize().as_bytes())
+}
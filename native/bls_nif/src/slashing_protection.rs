@@ -0,0 +1,525 @@
+//! [EIP-3076](https://eips.ethereum.org/EIPS/eip-3076) slashing protection.
+//!
+//! Signed blocks and attestations are kept in memory, and appended to a log file
+//! before anything gets signed. Each line of the log is an interchange `data` entry,
+//! except for the first one, which holds the interchange metadata.
+
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::sync::{Mutex, MutexGuard};
+
+use bls::{Hash256, PublicKey};
+use rustler::{Binary, Env, NifTaggedEnum, NifUnitEnum, ResourceArc};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::bytes_to_binary;
+use crate::secret_key::SecretKeyResource;
+
+const INTERCHANGE_FORMAT_VERSION: &str = "5";
+
+#[derive(NifTaggedEnum)]
+enum SlashingError {
+    DoubleBlockProposal,
+    BlockSlotTooLow,
+    DoubleVote,
+    SurroundingVote,
+    SurroundedVote,
+    SourceExceedsTarget,
+    AttestationSourceTooLow,
+    AttestationTargetTooLow,
+    GenesisValidatorsRootMismatch,
+    InvalidInput(String),
+    InvalidInterchange(String),
+    DatabaseError(String),
+}
+
+/// Why signing a message is safe.
+#[derive(NifUnitEnum, Clone, Copy, PartialEq)]
+enum Safe {
+    Valid,
+    SameData,
+}
+
+fn database_error(e: impl std::fmt::Debug) -> SlashingError {
+    SlashingError::DatabaseError(format!("{:?}", e))
+}
+
+fn invalid_interchange(e: serde_json::Error) -> SlashingError {
+    SlashingError::InvalidInterchange(e.to_string())
+}
+
+/// Fixed-size bytes, encoded as 0x-prefixed hex.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Hex<const N: usize>([u8; N]);
+
+impl<const N: usize> Hex<N> {
+    fn from_slice(bytes: &[u8]) -> Result<Self, SlashingError> {
+        bytes.try_into().map(Self).map_err(|_| {
+            SlashingError::InvalidInput(format!("Expected {} bytes, got {}", N, bytes.len()))
+        })
+    }
+}
+
+impl<const N: usize> Serialize for Hex<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(self.0)))
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Hex<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        let digits = string
+            .strip_prefix("0x")
+            .ok_or_else(|| D::Error::custom("Missing 0x prefix"))?;
+        let mut bytes = [0; N];
+        hex::decode_to_slice(digits, &mut bytes).map_err(D::Error::custom)?;
+        Ok(Self(bytes))
+    }
+}
+
+// Integers are encoded as decimal strings.
+mod quoted_u64 {
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Interchange {
+    metadata: Metadata,
+    data: Vec<ValidatorData>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Metadata {
+    interchange_format_version: String,
+    genesis_validators_root: Hex<32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ValidatorData {
+    pubkey: Hex<48>,
+    #[serde(default)]
+    signed_blocks: Vec<SignedBlock>,
+    #[serde(default)]
+    signed_attestations: Vec<SignedAttestation>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct SignedBlock {
+    #[serde(with = "quoted_u64")]
+    slot: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signing_root: Option<Hex<32>>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+struct SignedAttestation {
+    #[serde(with = "quoted_u64")]
+    source_epoch: u64,
+    #[serde(with = "quoted_u64")]
+    target_epoch: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signing_root: Option<Hex<32>>,
+}
+
+/// Everything a single validator signed. Entries without a signing root
+/// (possible in imported data) never count as repeats of a new message.
+#[derive(Default)]
+struct History {
+    blocks: Vec<SignedBlock>,
+    attestations: Vec<SignedAttestation>,
+}
+
+impl History {
+    fn check_block(&self, block: &SignedBlock) -> Result<Safe, SlashingError> {
+        let mut repeat = false;
+        for prev in self.blocks.iter().filter(|prev| prev.slot == block.slot) {
+            if prev.signing_root.is_none() || prev.signing_root != block.signing_root {
+                return Err(SlashingError::DoubleBlockProposal);
+            }
+            repeat = true;
+        }
+        if repeat {
+            return Ok(Safe::SameData);
+        }
+
+        match self.blocks.iter().map(|prev| prev.slot).min() {
+            Some(min_slot) if block.slot <= min_slot => Err(SlashingError::BlockSlotTooLow),
+            _ => Ok(Safe::Valid),
+        }
+    }
+
+    fn check_attestation(&self, attestation: &SignedAttestation) -> Result<Safe, SlashingError> {
+        let SignedAttestation {
+            source_epoch,
+            target_epoch,
+            ..
+        } = *attestation;
+        if source_epoch > target_epoch {
+            return Err(SlashingError::SourceExceedsTarget);
+        }
+
+        let mut repeat = false;
+        for prev in self.attestations.iter() {
+            if prev.target_epoch == target_epoch {
+                if prev.signing_root.is_none() || prev.signing_root != attestation.signing_root {
+                    return Err(SlashingError::DoubleVote);
+                }
+                repeat = true;
+            }
+        }
+
+        // Imported data may be slashable, so even a repeat can surround or be
+        // surrounded by another entry.
+        for prev in self.attestations.iter() {
+            if source_epoch < prev.source_epoch && target_epoch > prev.target_epoch {
+                return Err(SlashingError::SurroundingVote);
+            }
+            if source_epoch > prev.source_epoch && target_epoch < prev.target_epoch {
+                return Err(SlashingError::SurroundedVote);
+            }
+        }
+        if repeat {
+            return Ok(Safe::SameData);
+        }
+
+        let min_source = self.attestations.iter().map(|prev| prev.source_epoch).min();
+        let min_target = self.attestations.iter().map(|prev| prev.target_epoch).min();
+        match (min_source, min_target) {
+            (Some(min_source), _) if source_epoch < min_source => {
+                Err(SlashingError::AttestationSourceTooLow)
+            }
+            (_, Some(min_target)) if target_epoch <= min_target => {
+                Err(SlashingError::AttestationTargetTooLow)
+            }
+            _ => Ok(Safe::Valid),
+        }
+    }
+
+    fn extend(&mut self, data: ValidatorData) {
+        self.blocks.extend(data.signed_blocks);
+        self.attestations.extend(data.signed_attestations);
+    }
+
+    // Imported data may repeat what we already have.
+    fn dedup(&mut self) {
+        self.blocks.sort_unstable();
+        self.blocks.dedup();
+        self.attestations.sort_unstable();
+        self.attestations.dedup();
+    }
+}
+
+pub(crate) struct SlashingProtectionDb {
+    inner: Mutex<SlashingProtectionDbInner>,
+}
+
+#[rustler::resource_impl]
+impl rustler::Resource for SlashingProtectionDb {}
+
+struct SlashingProtectionDbInner {
+    genesis_validators_root: Hex<32>,
+    log: File,
+    histories: BTreeMap<Hex<48>, History>,
+}
+
+impl SlashingProtectionDbInner {
+    fn open(path: &str, genesis_validators_root: Hex<32>) -> Result<Self, SlashingError> {
+        let mut log = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(database_error)?;
+        let mut contents = String::new();
+        log.read_to_string(&mut contents).map_err(database_error)?;
+
+        // A partially written line means we crashed before signing its message,
+        // so it's safe to drop it.
+        let valid_len = contents.rfind('\n').map_or(0, |i| i + 1);
+        if valid_len < contents.len() {
+            log.set_len(valid_len as u64).map_err(database_error)?;
+        }
+
+        let mut db = Self {
+            genesis_validators_root,
+            log,
+            histories: BTreeMap::new(),
+        };
+        let mut lines = contents[..valid_len].lines();
+        match lines.next() {
+            None => db.append(&[Metadata {
+                interchange_format_version: INTERCHANGE_FORMAT_VERSION.to_owned(),
+                genesis_validators_root,
+            }])?,
+            Some(line) => {
+                let metadata: Metadata = serde_json::from_str(line).map_err(invalid_interchange)?;
+                db.check_metadata(&metadata)?;
+                for line in lines {
+                    let data: ValidatorData =
+                        serde_json::from_str(line).map_err(invalid_interchange)?;
+                    db.histories.entry(data.pubkey).or_default().extend(data);
+                }
+                db.histories.values_mut().for_each(History::dedup);
+            }
+        }
+        Ok(db)
+    }
+
+    fn check_metadata(&self, metadata: &Metadata) -> Result<(), SlashingError> {
+        if metadata.interchange_format_version != INTERCHANGE_FORMAT_VERSION {
+            return Err(SlashingError::InvalidInterchange(format!(
+                "Unsupported interchange format version: {}",
+                metadata.interchange_format_version
+            )));
+        }
+        if metadata.genesis_validators_root != self.genesis_validators_root {
+            return Err(SlashingError::GenesisValidatorsRootMismatch);
+        }
+        Ok(())
+    }
+
+    // Writes the entries to disk, one per line, and waits until they're persisted.
+    // On failure, the log is truncated back so that none of them are kept.
+    fn append<T: Serialize>(&mut self, entries: &[T]) -> Result<(), SlashingError> {
+        let mut buffer = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut buffer, entry).map_err(database_error)?;
+            buffer.push(b'\n');
+        }
+        let len = self.log.metadata().map_err(database_error)?.len();
+        let result = self
+            .log
+            .write_all(&buffer)
+            .and_then(|()| self.log.sync_data());
+        if let Err(e) = result {
+            // Best effort: if this fails too, reopening drops the partial line.
+            let _ = self.log.set_len(len);
+            return Err(database_error(e));
+        }
+        Ok(())
+    }
+
+    fn check_and_insert_block(
+        &mut self,
+        pubkey: Hex<48>,
+        block: SignedBlock,
+    ) -> Result<Safe, SlashingError> {
+        let empty = History::default();
+        let safe = self
+            .histories
+            .get(&pubkey)
+            .unwrap_or(&empty)
+            .check_block(&block)?;
+        if safe == Safe::Valid {
+            self.append(&[ValidatorData {
+                pubkey,
+                signed_blocks: vec![block],
+                signed_attestations: vec![],
+            }])?;
+            self.histories.entry(pubkey).or_default().blocks.push(block);
+        }
+        Ok(safe)
+    }
+
+    fn check_and_insert_attestation(
+        &mut self,
+        pubkey: Hex<48>,
+        attestation: SignedAttestation,
+    ) -> Result<Safe, SlashingError> {
+        let empty = History::default();
+        let safe = self
+            .histories
+            .get(&pubkey)
+            .unwrap_or(&empty)
+            .check_attestation(&attestation)?;
+        if safe == Safe::Valid {
+            self.append(&[ValidatorData {
+                pubkey,
+                signed_blocks: vec![],
+                signed_attestations: vec![attestation],
+            }])?;
+            let history = self.histories.entry(pubkey).or_default();
+            history.attestations.push(attestation);
+        }
+        Ok(safe)
+    }
+
+    // Everything is validated before being persisted, so a rejected interchange
+    // leaves the database untouched. Entries that conflict with each other are
+    // still accepted, as EIP-3076 requires.
+    fn import(&mut self, interchange: Interchange) -> Result<(), SlashingError> {
+        self.check_metadata(&interchange.metadata)?;
+        for data in interchange.data.iter() {
+            PublicKey::deserialize(&data.pubkey.0).map_err(|e| {
+                SlashingError::InvalidInterchange(format!("Invalid pubkey: {:?}", e))
+            })?;
+        }
+        self.append(&interchange.data)?;
+        for data in interchange.data {
+            let history = self.histories.entry(data.pubkey).or_default();
+            history.extend(data);
+            history.dedup();
+        }
+        Ok(())
+    }
+
+    fn export(&self) -> Interchange {
+        let data = self
+            .histories
+            .iter()
+            .map(|(pubkey, history)| ValidatorData {
+                pubkey: *pubkey,
+                signed_blocks: history.blocks.clone(),
+                signed_attestations: history.attestations.clone(),
+            })
+            .collect();
+        Interchange {
+            metadata: Metadata {
+                interchange_format_version: INTERCHANGE_FORMAT_VERSION.to_owned(),
+                genesis_validators_root: self.genesis_validators_root,
+            },
+            data,
+        }
+    }
+}
+
+fn lock(
+    db: &SlashingProtectionDb,
+) -> Result<MutexGuard<'_, SlashingProtectionDbInner>, SlashingError> {
+    db.inner.lock().map_err(|_| {
+        SlashingError::DatabaseError("Poisoned slashing protection database".to_owned())
+    })
+}
+
+fn signing_root(bytes: Binary) -> Result<Option<Hex<32>>, SlashingError> {
+    Hex::from_slice(bytes.as_slice()).map(Some)
+}
+
+// Opens the database at `path`, creating it if needed.
+// Only one handle should be open for a given path at any time.
+#[rustler::nif(schedule = "DirtyIo")]
+fn slashing_protection_open(
+    path: String,
+    genesis_validators_root: Binary,
+) -> Result<ResourceArc<SlashingProtectionDb>, SlashingError> {
+    let genesis_validators_root = Hex::from_slice(genesis_validators_root.as_slice())?;
+    let inner = SlashingProtectionDbInner::open(&path, genesis_validators_root)?;
+    Ok(ResourceArc::new(SlashingProtectionDb {
+        inner: Mutex::new(inner),
+    }))
+}
+
+// Checks that signing the block is safe, and records it if it's new.
+#[rustler::nif(schedule = "DirtyIo")]
+fn slashing_protection_check_block(
+    db: ResourceArc<SlashingProtectionDb>,
+    public_key: Binary,
+    slot: u64,
+    block_signing_root: Binary,
+) -> Result<Safe, SlashingError> {
+    let pubkey = Hex::from_slice(public_key.as_slice())?;
+    let block = SignedBlock {
+        slot,
+        signing_root: signing_root(block_signing_root)?,
+    };
+    lock(&db)?.check_and_insert_block(pubkey, block)
+}
+
+// Checks that signing the attestation is safe, and records it if it's new.
+#[rustler::nif(schedule = "DirtyIo")]
+fn slashing_protection_check_attestation(
+    db: ResourceArc<SlashingProtectionDb>,
+    public_key: Binary,
+    source_epoch: u64,
+    target_epoch: u64,
+    attestation_signing_root: Binary,
+) -> Result<Safe, SlashingError> {
+    let pubkey = Hex::from_slice(public_key.as_slice())?;
+    let attestation = SignedAttestation {
+        source_epoch,
+        target_epoch,
+        signing_root: signing_root(attestation_signing_root)?,
+    };
+    lock(&db)?.check_and_insert_attestation(pubkey, attestation)
+}
+
+// Signs the block only once it's been checked and persisted.
+#[rustler::nif(schedule = "DirtyIo")]
+fn slashing_protection_sign_block<'env>(
+    env: Env<'env>,
+    db: ResourceArc<SlashingProtectionDb>,
+    secret_key: ResourceArc<SecretKeyResource>,
+    slot: u64,
+    block_signing_root: Binary,
+) -> Result<Binary<'env>, SlashingError> {
+    let pubkey = Hex(secret_key.0.public_key().serialize());
+    let block = SignedBlock {
+        slot,
+        signing_root: signing_root(block_signing_root)?,
+    };
+    lock(&db)?.check_and_insert_block(pubkey, block)?;
+
+    let signature = secret_key
+        .0
+        .sign(Hash256::from_slice(block_signing_root.as_slice()));
+    Ok(bytes_to_binary(env, &signature.serialize()))
+}
+
+// Signs the attestation only once it's been checked and persisted.
+#[rustler::nif(schedule = "DirtyIo")]
+fn slashing_protection_sign_attestation<'env>(
+    env: Env<'env>,
+    db: ResourceArc<SlashingProtectionDb>,
+    secret_key: ResourceArc<SecretKeyResource>,
+    source_epoch: u64,
+    target_epoch: u64,
+    attestation_signing_root: Binary,
+) -> Result<Binary<'env>, SlashingError> {
+    let pubkey = Hex(secret_key.0.public_key().serialize());
+    let attestation = SignedAttestation {
+        source_epoch,
+        target_epoch,
+        signing_root: signing_root(attestation_signing_root)?,
+    };
+    lock(&db)?.check_and_insert_attestation(pubkey, attestation)?;
+
+    let signature = secret_key
+        .0
+        .sign(Hash256::from_slice(attestation_signing_root.as_slice()));
+    Ok(bytes_to_binary(env, &signature.serialize()))
+}
+
+// Imports an interchange JSON. Its entries are merged with the ones already stored.
+#[rustler::nif(schedule = "DirtyIo")]
+fn slashing_protection_import(
+    db: ResourceArc<SlashingProtectionDb>,
+    json: &str,
+) -> Result<usize, SlashingError> {
+    let interchange: Interchange = serde_json::from_str(json).map_err(invalid_interchange)?;
+    let count = interchange.data.len();
+    lock(&db)?.import(interchange)?;
+    Ok(count)
+}
+
+// Exports every stored entry as an interchange JSON, in the "complete" format.
+#[rustler::nif(schedule = "DirtyIo")]
+fn slashing_protection_export(
+    db: ResourceArc<SlashingProtectionDb>,
+) -> Result<String, SlashingError> {
+    let interchange = lock(&db)?.export();
+    serde_json::to_string(&interchange).map_err(database_error)
+}
@@ -0,0 +1,86 @@
+//! PeerDAS (EIP-7594) operations on the cells of extended blobs. Cells and
+//! proofs are returned as lists of `CELLS_PER_EXT_BLOB` binaries.
+
+use rustler::{Binary, Env};
+
+use c_kzg::{Cell, KzgProof};
+
+use crate::error::{
+    blob_from_bytes, cell_from_bytes, commitment_from_bytes, proof_from_bytes, KzgError,
+};
+use crate::{bytes_to_binary, trusted_setup};
+
+fn cells_to_binaries<'env>(env: Env<'env>, cells: &[Cell]) -> Vec<Binary<'env>> {
+    cells
+        .iter()
+        .map(|cell| bytes_to_binary(env, &cell.to_bytes()))
+        .collect()
+}
+
+fn proofs_to_binaries<'env>(env: Env<'env>, proofs: &[KzgProof]) -> Vec<Binary<'env>> {
+    proofs
+        .iter()
+        .map(|proof| bytes_to_binary(env, &proof.to_bytes().into_inner()))
+        .collect()
+}
+
+fn cells_from_binaries(cells: &[Binary]) -> Result<Vec<Cell>, KzgError> {
+    cells.iter().map(|cell| cell_from_bytes(cell)).collect()
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn compute_cells<'env>(env: Env<'env>, blob: Binary) -> Result<Vec<Binary<'env>>, KzgError> {
+    let blob = blob_from_bytes(blob.as_slice())?;
+    let settings = trusted_setup::get()?;
+    let cells = settings.compute_cells(&blob)?;
+    Ok(cells_to_binaries(env, cells.as_slice()))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn compute_cells_and_kzg_proofs<'env>(
+    env: Env<'env>,
+    blob: Binary,
+) -> Result<(Vec<Binary<'env>>, Vec<Binary<'env>>), KzgError> {
+    let blob = blob_from_bytes(blob.as_slice())?;
+    let settings = trusted_setup::get()?;
+    let (cells, proofs) = settings.compute_cells_and_kzg_proofs(&blob)?;
+    Ok((
+        cells_to_binaries(env, cells.as_slice()),
+        proofs_to_binaries(env, proofs.as_slice()),
+    ))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_cell_kzg_proof_batch(
+    kzg_commitments: Vec<Binary>,
+    cell_indices: Vec<u64>,
+    cells: Vec<Binary>,
+    kzg_proofs: Vec<Binary>,
+) -> Result<bool, KzgError> {
+    let commitments = kzg_commitments
+        .iter()
+        .map(|commitment| commitment_from_bytes(commitment))
+        .collect::<Result<Vec<_>, _>>()?;
+    let cells = cells_from_binaries(&cells)?;
+    let proofs = kzg_proofs
+        .iter()
+        .map(|proof| proof_from_bytes(proof))
+        .collect::<Result<Vec<_>, _>>()?;
+    let settings = trusted_setup::get()?;
+    Ok(settings.verify_cell_kzg_proof_batch(&commitments, &cell_indices, &cells, &proofs)?)
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn recover_cells_and_kzg_proofs<'env>(
+    env: Env<'env>,
+    cell_indices: Vec<u64>,
+    cells: Vec<Binary>,
+) -> Result<(Vec<Binary<'env>>, Vec<Binary<'env>>), KzgError> {
+    let cells = cells_from_binaries(&cells)?;
+    let settings = trusted_setup::get()?;
+    let (cells, proofs) = settings.recover_cells_and_kzg_proofs(&cell_indices, &cells)?;
+    Ok((
+        cells_to_binaries(env, cells.as_slice()),
+        proofs_to_binaries(env, proofs.as_slice()),
+    ))
+}
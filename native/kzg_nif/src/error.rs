@@ -0,0 +1,103 @@
+//! Errors returned by the NIFs, encoded as stable atoms or tagged tuples.
+//! See `docs/nif_errors.md` for the full list.
+
+use c_kzg::{
+    BYTES_PER_BLOB, BYTES_PER_CELL, BYTES_PER_COMMITMENT, BYTES_PER_FIELD_ELEMENT, BYTES_PER_PROOF,
+};
+use rustler::{Encoder, Env, Term};
+
+mod atoms {
+    rustler::atoms! {
+        blob_length,
+        commitment_length,
+        proof_length,
+        field_element_length,
+        cell_length,
+        length_mismatch,
+        invalid_trusted_setup,
+        invalid_blob_encoding,
+        kzg_error,
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum KzgError {
+    BlobLength(usize),
+    CommitmentLength(usize),
+    ProofLength(usize),
+    FieldElementLength(usize),
+    CellLength(usize),
+    LengthMismatch(String),
+    InvalidTrustedSetup(String),
+    InvalidBlobEncoding(String),
+    Kzg(String),
+}
+
+impl From<c_kzg::Error> for KzgError {
+    fn from(e: c_kzg::Error) -> Self {
+        match e {
+            c_kzg::Error::MismatchLength(detail) => KzgError::LengthMismatch(detail),
+            c_kzg::Error::InvalidTrustedSetup(detail) => KzgError::InvalidTrustedSetup(detail),
+            e => KzgError::Kzg(format!("{:?}", e)),
+        }
+    }
+}
+
+impl Encoder for KzgError {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            KzgError::BlobLength(got) => (atoms::blob_length(), got, BYTES_PER_BLOB).encode(env),
+            KzgError::CommitmentLength(got) => {
+                (atoms::commitment_length(), got, BYTES_PER_COMMITMENT).encode(env)
+            }
+            KzgError::ProofLength(got) => (atoms::proof_length(), got, BYTES_PER_PROOF).encode(env),
+            KzgError::FieldElementLength(got) => {
+                (atoms::field_element_length(), got, BYTES_PER_FIELD_ELEMENT).encode(env)
+            }
+            KzgError::CellLength(got) => (atoms::cell_length(), got, BYTES_PER_CELL).encode(env),
+            KzgError::LengthMismatch(detail) => (atoms::length_mismatch(), detail).encode(env),
+            KzgError::InvalidTrustedSetup(detail) => {
+                (atoms::invalid_trusted_setup(), detail).encode(env)
+            }
+            KzgError::InvalidBlobEncoding(detail) => {
+                (atoms::invalid_blob_encoding(), detail).encode(env)
+            }
+            KzgError::Kzg(detail) => (atoms::kzg_error(), detail).encode(env),
+        }
+    }
+}
+
+pub(crate) fn blob_from_bytes(bytes: &[u8]) -> Result<c_kzg::Blob, KzgError> {
+    if bytes.len() != BYTES_PER_BLOB {
+        return Err(KzgError::BlobLength(bytes.len()));
+    }
+    Ok(c_kzg::Blob::from_bytes(bytes)?)
+}
+
+pub(crate) fn commitment_from_bytes(bytes: &[u8]) -> Result<c_kzg::Bytes48, KzgError> {
+    if bytes.len() != BYTES_PER_COMMITMENT {
+        return Err(KzgError::CommitmentLength(bytes.len()));
+    }
+    Ok(c_kzg::Bytes48::from_bytes(bytes)?)
+}
+
+pub(crate) fn proof_from_bytes(bytes: &[u8]) -> Result<c_kzg::Bytes48, KzgError> {
+    if bytes.len() != BYTES_PER_PROOF {
+        return Err(KzgError::ProofLength(bytes.len()));
+    }
+    Ok(c_kzg::Bytes48::from_bytes(bytes)?)
+}
+
+pub(crate) fn field_element_from_bytes(bytes: &[u8]) -> Result<c_kzg::Bytes32, KzgError> {
+    if bytes.len() != BYTES_PER_FIELD_ELEMENT {
+        return Err(KzgError::FieldElementLength(bytes.len()));
+    }
+    Ok(c_kzg::Bytes32::from_bytes(bytes)?)
+}
+
+pub(crate) fn cell_from_bytes(bytes: &[u8]) -> Result<c_kzg::Cell, KzgError> {
+    if bytes.len() != BYTES_PER_CELL {
+        return Err(KzgError::CellLength(bytes.len()));
+    }
+    Ok(c_kzg::Cell::from_bytes(bytes)?)
+}
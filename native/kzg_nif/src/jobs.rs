@@ -0,0 +1,72 @@
+//! Asynchronous verification jobs. A job runs in the shared worker pool, off the
+//! BEAM schedulers, and its result is sent to the calling process as a
+//! `{reference, result}` message.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::OnceLock;
+
+use rustler::{Encoder, Env, OwnedEnv, Term};
+use verification_pool::{Job, Pool};
+
+mod atoms {
+    rustler::atoms! {
+        error,
+        queue_full,
+        worker_panicked,
+    }
+}
+
+static POOL: OnceLock<Pool> = OnceLock::new();
+
+fn pool() -> &'static Pool {
+    POOL.get()
+        .expect("The pool is started when the library is loaded")
+}
+
+// Starts the pool with the number of workers given as load data, or one per
+// core if it's missing.
+pub(crate) fn load(_env: Env, load_info: Term) -> bool {
+    // On a reload, the pool from the first load is kept
+    if POOL.get().is_some() {
+        return true;
+    }
+    match Pool::start("kzg_nif_worker", load_info.decode().ok()) {
+        Ok(pool) => POOL.set(pool).is_ok(),
+        Err(_) => false,
+    }
+}
+
+// rustler 0.32 can't create references, so this goes through the C API.
+fn make_ref(env: Env) -> Term {
+    unsafe { Term::new(env, rustler_sys::enif_make_ref(env.as_c_arg())) }
+}
+
+/// Queues `job` and returns the reference that tags its result. If the queue
+/// is full, `{:error, :queue_full}` is sent right away instead.
+pub(crate) fn spawn<'a, F, T>(env: Env<'a>, job: F) -> Term<'a>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Encoder + Send + 'static,
+{
+    let pid = env.pid();
+    let reference = make_ref(env);
+    let mut owned_env = OwnedEnv::new();
+    let saved_reference = owned_env.save(reference);
+
+    let task: Job = Box::new(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(job));
+        // This only fails if the caller is dead, so there's nobody to notify
+        let _ = owned_env.send_and_clear(&pid, |env| {
+            let reference = saved_reference.load(env);
+            match result {
+                Ok(result) => (reference, result).encode(env),
+                Err(_) => (reference, (atoms::error(), atoms::worker_panicked())).encode(env),
+            }
+        });
+    });
+
+    if pool().try_submit(task).is_err() {
+        let _ = env.send(&pid, (reference, (atoms::error(), atoms::queue_full())));
+    }
+    reference
+}
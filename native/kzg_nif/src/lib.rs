@@ -1,9 +1,24 @@
+mod cells;
+mod error;
+mod jobs;
+mod packing;
+mod trusted_setup;
+
 use std::io::Write;
+use std::ops::Deref;
 use std::path::Path;
 
-use rustler::{Binary, Env, NewBinary};
+use rayon::prelude::*;
+use rustler::{Binary, Encoder, Env, NewBinary, Term};
+
+pub use c_kzg::{
+    Blob, Bytes32, Bytes48, Error, KzgCommitment, KzgProof, KzgSettings, BYTES_PER_COMMITMENT,
+};
+use c_kzg::{BYTES_PER_BLOB, BYTES_PER_PROOF};
 
-pub use c_kzg::{Bytes32, Bytes48, Blob, KzgSettings, KzgCommitment, KzgProof, BYTES_PER_COMMITMENT, Error};
+use crate::error::{
+    blob_from_bytes, commitment_from_bytes, field_element_from_bytes, proof_from_bytes, KzgError,
+};
 
 pub(crate) fn bytes_to_binary<'env>(env: Env<'env>, bytes: &[u8]) -> Binary<'env> {
     let mut binary = NewBinary::new(env, bytes.len());
@@ -12,161 +27,241 @@ pub(crate) fn bytes_to_binary<'env>(env: Env<'env>, bytes: &[u8]) -> Binary<'env
     binary.into()
 }
 
-fn get_trusted_setup() -> KzgSettings {
-    let lib_path = std::env::current_dir().unwrap().join(file!());
-    let src_path = lib_path.parent().unwrap();
-    let main_path = src_path.parent().unwrap();
-    let trusted_setup_path = main_path.join("native/kzg_nif/official_trusted_setup.txt");
-    let trusted_setup_file: &Path = trusted_setup_path.as_path();
-    debug_assert!(trusted_setup_file.exists(), "Missing trusted setup file");
-    KzgSettings::load_trusted_setup_file(trusted_setup_file).unwrap()
+mod atoms {
+    rustler::atoms! {
+        ok,
+        error,
+    }
 }
 
-#[rustler::nif]     
-fn blob_to_kzg_commitment<'env>(
-    env: Env<'env>,
-    blob: Binary,
-) -> Result<Binary<'env>, String> {
-    let blob = match Blob::from_bytes(blob.as_slice()) {
-        Ok(blob) => blob,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let trusted_setup = get_trusted_setup();
-    let kzg_commitment: Result<KzgCommitment, _>  = c_kzg::KzgCommitment::blob_to_kzg_commitment(&blob, &trusted_setup);
-    let commitment = match kzg_commitment {
-        Ok(commitment) => commitment.to_bytes().into_inner(),
-        Err(e) => return Err(format!("{:?}", e))
+// The load data is `{workers, trusted_setup_path}`, where the path may be nil.
+fn load(env: Env, load_info: Term) -> bool {
+    let Ok((workers, path)) = load_info.decode::<(Term, Option<String>)>() else {
+        return false;
     };
-    Ok(bytes_to_binary(env, &commitment))
+    trusted_setup::init(path.as_deref().map(Path::new));
+    jobs::load(env, workers)
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyIo")]
+fn load_trusted_setup<'env>(env: Env<'env>, path: String) -> Term<'env> {
+    match trusted_setup::reload(Path::new(&path)) {
+        Ok(()) => atoms::ok().encode(env),
+        Err(e) => (atoms::error(), e).encode(env),
+    }
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn blob_to_kzg_commitment<'env>(env: Env<'env>, blob: Binary) -> Result<Binary<'env>, KzgError> {
+    let blob = blob_from_bytes(blob.as_slice())?;
+    let settings = trusted_setup::get()?;
+    let commitment = settings.blob_to_kzg_commitment(&blob)?;
+    Ok(bytes_to_binary(env, &commitment.to_bytes().into_inner()))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
 fn compute_kzg_proof<'env>(
     env: Env<'env>,
     blob: Binary,
-    z: Binary
-) -> Result<(Binary<'env>, Binary<'env>), String> {
-    let blob = match Blob::from_bytes(blob.as_slice()) {
-        Ok(blob) => blob,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let z_bytes = match Bytes32::from_bytes(z.as_slice()) {
-        Ok(z_bytes) => z_bytes,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let trusted_setup = get_trusted_setup();
-    let proof: Result<(KzgProof, Bytes32), _>  = c_kzg::KzgProof::compute_kzg_proof(&blob, &z_bytes, &trusted_setup);
-    let proof = match proof {
-        Ok(proof) => proof,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let (kzg_proof, y) = proof; 
+    z: Binary,
+) -> Result<(Binary<'env>, Binary<'env>), KzgError> {
+    let blob = blob_from_bytes(blob.as_slice())?;
+    let z_bytes = field_element_from_bytes(z.as_slice())?;
+    let settings = trusted_setup::get()?;
+    let (kzg_proof, y) = settings.compute_kzg_proof(&blob, &z_bytes)?;
     let kzg_proof = kzg_proof.to_bytes().into_inner();
     let y = y.as_slice();
-    Ok((bytes_to_binary(env, &kzg_proof), bytes_to_binary(env, &y)))
+    Ok((bytes_to_binary(env, &kzg_proof), bytes_to_binary(env, y)))
 }
 
-#[rustler::nif]
+#[rustler::nif(schedule = "DirtyCpu")]
 fn compute_blob_kzg_proof<'env>(
     env: Env<'env>,
     blob: Binary,
-    kzg_commitment: Binary
-) -> Result<Binary<'env>, String> {
-    let blob = match Blob::from_bytes(blob.as_slice()) {
-        Ok(blob) => blob,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let commitment = match KzgCommitment::from_bytes(kzg_commitment.as_slice()) {
-        Ok(commitment) => commitment,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let trusted_setup = get_trusted_setup();
-    let kzg_proof: Result<KzgProof, Error>  = c_kzg::KzgProof::compute_blob_kzg_proof(&blob, &commitment.to_bytes(), &trusted_setup);
-    let kzg_proof = match kzg_proof {
-        Ok(proof) => proof,
-        Err(e) => return Err(format!("{:?}", e))
-    };
+    kzg_commitment: Binary,
+) -> Result<Binary<'env>, KzgError> {
+    let blob = blob_from_bytes(blob.as_slice())?;
+    let commitment = commitment_from_bytes(kzg_commitment.as_slice())?;
+    let settings = trusted_setup::get()?;
+    let kzg_proof = settings.compute_blob_kzg_proof(&blob, &commitment)?;
     Ok(bytes_to_binary(env, &kzg_proof.to_bytes().into_inner()))
 }
 
-#[rustler::nif]
-fn verify_kzg_proof<'env>(
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_kzg_proof(
     kzg_commitment: Binary,
     z: Binary,
     y: Binary,
-    kzg_proof: Binary
-) -> Result<bool, String> {
-    let commitment = match KzgCommitment::from_bytes(kzg_commitment.as_slice()) {
-        Ok(commitment) => commitment,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let z_bytes = match Bytes32::from_bytes(z.as_slice()) {
-        Ok(z_bytes) => z_bytes,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let y_bytes = match Bytes32::from_bytes(y.as_slice()) {
-        Ok(z_bytes) => z_bytes,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let proof = match KzgProof::from_bytes(kzg_proof.as_slice()) {
-        Ok(proof) => proof,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let trusted_setup = get_trusted_setup();
-    match c_kzg::KzgProof::verify_kzg_proof(&commitment.to_bytes(), &z_bytes, &y_bytes, &proof.to_bytes(), &trusted_setup) {
-        Ok(status) => Ok(status),
-        Err(e) => Err(format!("{:?}", e))
-    }
+    kzg_proof: Binary,
+) -> Result<bool, KzgError> {
+    let commitment = commitment_from_bytes(kzg_commitment.as_slice())?;
+    let z_bytes = field_element_from_bytes(z.as_slice())?;
+    let y_bytes = field_element_from_bytes(y.as_slice())?;
+    let proof = proof_from_bytes(kzg_proof.as_slice())?;
+    let settings = trusted_setup::get()?;
+    Ok(settings.verify_kzg_proof(&commitment, &z_bytes, &y_bytes, &proof)?)
 }
 
-#[rustler::nif]
-fn verify_blob_kzg_proof<'env>(
+fn verify_blob_proof(
+    blob: &[u8],
+    kzg_commitment: &[u8],
+    kzg_proof: &[u8],
+) -> Result<bool, KzgError> {
+    let blob = blob_from_bytes(blob)?;
+    let commitment = commitment_from_bytes(kzg_commitment)?;
+    let proof = proof_from_bytes(kzg_proof)?;
+    let settings = trusted_setup::get()?;
+    Ok(settings.verify_blob_kzg_proof(&blob, &commitment, &proof)?)
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_blob_kzg_proof(
     blob: Binary,
     kzg_commitment: Binary,
-    kzg_proof: Binary
-) -> Result<bool, String> {
-    let blob = match Blob::from_bytes(blob.as_slice()) {
-        Ok(blob) => blob,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let commitment = match KzgCommitment::from_bytes(kzg_commitment.as_slice()) {
-        Ok(commitment) => commitment,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let proof = match KzgProof::from_bytes(kzg_proof.as_slice()) {
-        Ok(proof) => proof,
-        Err(e) => return Err(format!("{:?}", e))
-    };
-    let trusted_setup = get_trusted_setup();
-    match c_kzg::KzgProof::verify_blob_kzg_proof(&blob, &commitment.to_bytes(), &proof.to_bytes(), &trusted_setup) {
-        Ok(status) => Ok(status),
-        Err(e) => Err(format!("{:?}", e))
+    kzg_proof: Binary,
+) -> Result<bool, KzgError> {
+    verify_blob_proof(&blob, &kzg_commitment, &kzg_proof)
+}
+
+/// Size of the index that precedes the blob in an encoded `BlobSidecar`.
+const SIDECAR_INDEX_SIZE: usize = 8;
+
+// Returns the blob, commitment and proof of an encoded `BlobSidecar`, which come
+// right after its index. The fields of a truncated sidecar come out short, and
+// are reported with their lengths.
+fn sidecar_kzg_fields(sidecar: &[u8]) -> (&[u8], &[u8], &[u8]) {
+    let fields = sidecar.get(SIDECAR_INDEX_SIZE..).unwrap_or_default();
+    let (blob, rest) = fields.split_at(BYTES_PER_BLOB.min(fields.len()));
+    let (commitment, rest) = rest.split_at(BYTES_PER_COMMITMENT.min(rest.len()));
+    let proof = &rest[..BYTES_PER_PROOF.min(rest.len())];
+    (blob, commitment, proof)
+}
+
+// Verifies the KZG proof of an encoded `BlobSidecar` without decoding the rest
+// of it, so gossip validation doesn't copy the blob out of the sidecar.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_blob_sidecar_kzg_proof(sidecar: Binary) -> Result<bool, KzgError> {
+    let (blob, commitment, proof) = sidecar_kzg_fields(&sidecar);
+    verify_blob_proof(blob, commitment, proof)
+}
+
+fn verify_blob_proof_batch<B, C, P>(
+    blobs: &[B],
+    kzg_commitments: &[C],
+    kzg_proofs: &[P],
+) -> Result<bool, KzgError>
+where
+    B: Deref<Target = [u8]>,
+    C: Deref<Target = [u8]>,
+    P: Deref<Target = [u8]>,
+{
+    let blobs = blobs
+        .iter()
+        .map(|blob| blob_from_bytes(blob))
+        .collect::<Result<Vec<Blob>, _>>()?;
+    let commitments = kzg_commitments
+        .iter()
+        .map(|commitment| commitment_from_bytes(commitment))
+        .collect::<Result<Vec<Bytes48>, _>>()?;
+    let proofs = kzg_proofs
+        .iter()
+        .map(|proof| proof_from_bytes(proof))
+        .collect::<Result<Vec<Bytes48>, _>>()?;
+
+    if blobs.len() != commitments.len() || blobs.len() != proofs.len() {
+        return Err(KzgError::LengthMismatch(format!(
+            "There are {} blobs, {} commitments and {} proofs",
+            blobs.len(),
+            commitments.len(),
+            proofs.len()
+        )));
     }
+
+    // The batch is split into one smaller batch per thread
+    let settings = trusted_setup::get()?;
+    let chunk_size = blobs.len().div_ceil(rayon::current_num_threads()).max(1);
+    let results = blobs
+        .par_chunks(chunk_size)
+        .zip(commitments.par_chunks(chunk_size))
+        .zip(proofs.par_chunks(chunk_size))
+        .map(|((blobs, commitments), proofs)| {
+            settings.verify_blob_kzg_proof_batch(blobs, commitments, proofs)
+        })
+        .collect::<Result<Vec<bool>, _>>()?;
+    Ok(results.into_iter().all(|valid| valid))
 }
 
-#[rustler::nif]
-fn verify_blob_kzg_proof_batch<'env>(
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_blob_kzg_proof_batch(
     blobs: Vec<Binary>,
     kzg_commitments: Vec<Binary>,
-    kzg_proofs: Vec<Binary>
-) -> Result<bool, String> {
-    let blob_results = blobs.iter().map(|blob| Blob::from_bytes(blob.as_slice())).collect::<Result<Vec<Blob>, _>>();
-    let blobs = blob_results.map_err(|err| format!("{:?}", err))?;
+    kzg_proofs: Vec<Binary>,
+) -> Result<bool, KzgError> {
+    verify_blob_proof_batch(&blobs, &kzg_commitments, &kzg_proofs)
+}
 
-    let commitments_results = kzg_commitments.iter().map(|commitment| KzgCommitment::from_bytes(commitment.as_slice())).collect::<Result<Vec<KzgCommitment>, _>>();
-    let commitments = commitments_results.map_err(|err| format!("{:?}", err))?;
-    let commitments = commitments.iter().map(|commitment| commitment.to_bytes()).collect::<Vec<_>>();
+/// Computes the commitment and proof of each blob, as in a `BlobsBundle`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn compute_blob_commitments_and_proofs<'env>(
+    env: Env<'env>,
+    blobs: Vec<Binary>,
+) -> Result<(Vec<Binary<'env>>, Vec<Binary<'env>>), KzgError> {
+    let blobs = blobs
+        .iter()
+        .map(|blob| blob_from_bytes(blob))
+        .collect::<Result<Vec<Blob>, _>>()?;
+    let settings = trusted_setup::get()?;
+    let results = blobs
+        .par_iter()
+        .map(|blob| {
+            let commitment = settings.blob_to_kzg_commitment(blob)?.to_bytes();
+            let proof = settings.compute_blob_kzg_proof(blob, &commitment)?;
+            Ok::<_, c_kzg::Error>((commitment.into_inner(), proof.to_bytes().into_inner()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
+    let mut commitments = Vec::with_capacity(results.len());
+    let mut proofs = Vec::with_capacity(results.len());
+    for (commitment, proof) in results {
+        commitments.push(bytes_to_binary(env, &commitment));
+        proofs.push(bytes_to_binary(env, &proof));
+    }
+    Ok((commitments, proofs))
+}
 
-    let proof_results = kzg_proofs.iter().map(|proof| KzgProof::from_bytes(proof.as_slice())).collect::<Result<Vec<KzgProof>, _>>();
-    let proofs = proof_results.map_err(|err| format!("{:?}", err))?;
-    let proofs = proofs.iter().map(|proof| proof.to_bytes()).collect::<Vec<_>>();
+// Asynchronous versions of the functions above. The inputs are copied, as they
+// have to outlive the call, and are decoded by the worker.
 
-    let trusted_setup = get_trusted_setup();
-    match c_kzg::KzgProof::verify_blob_kzg_proof_batch(&blobs, &commitments, &proofs, &trusted_setup) {
-        Ok(status) => Ok(status),
-        Err(e) => Err(format!("{:?}", e))
-    }
+#[rustler::nif]
+fn verify_blob_kzg_proof_async<'env>(
+    env: Env<'env>,
+    blob: Binary,
+    kzg_commitment: Binary,
+    kzg_proof: Binary,
+) -> Term<'env> {
+    let (blob, kzg_commitment, kzg_proof) =
+        (blob.to_vec(), kzg_commitment.to_vec(), kzg_proof.to_vec());
+    jobs::spawn(env, move || {
+        verify_blob_proof(&blob, &kzg_commitment, &kzg_proof)
+    })
+}
+
+#[rustler::nif]
+fn verify_blob_kzg_proof_batch_async<'env>(
+    env: Env<'env>,
+    blobs: Vec<Binary>,
+    kzg_commitments: Vec<Binary>,
+    kzg_proofs: Vec<Binary>,
+) -> Term<'env> {
+    let to_vecs = |binaries: Vec<Binary>| binaries.iter().map(|b| b.to_vec()).collect::<Vec<_>>();
+    let (blobs, kzg_commitments, kzg_proofs) = (
+        to_vecs(blobs),
+        to_vecs(kzg_commitments),
+        to_vecs(kzg_proofs),
+    );
+    jobs::spawn(env, move || {
+        verify_blob_proof_batch(&blobs, &kzg_commitments, &kzg_proofs)
+    })
 }
 
 rustler::init!(
@@ -177,6 +272,18 @@ rustler::init!(
         verify_kzg_proof,
         compute_blob_kzg_proof,
         verify_blob_kzg_proof,
-        verify_blob_kzg_proof_batch
-    ]
-);
\ No newline at end of file
+        verify_blob_sidecar_kzg_proof,
+        verify_blob_kzg_proof_batch,
+        verify_blob_kzg_proof_async,
+        verify_blob_kzg_proof_batch_async,
+        compute_blob_commitments_and_proofs,
+        load_trusted_setup,
+        cells::compute_cells,
+        cells::compute_cells_and_kzg_proofs,
+        cells::verify_cell_kzg_proof_batch,
+        cells::recover_cells_and_kzg_proofs,
+        packing::pack_blobs,
+        packing::unpack_blobs
+    ],
+    load = load
+);
@@ -0,0 +1,91 @@
+//! Packing of arbitrary data into canonical blobs. The data is prefixed with its
+//! length, as a big-endian `u64`, and split into 31-byte chunks. Each chunk is
+//! stored in a field element after a zero byte, so it's always below the BLS
+//! modulus. The last blob is padded with zeros.
+
+use rustler::{Binary, Env};
+
+use c_kzg::{BYTES_PER_BLOB, BYTES_PER_FIELD_ELEMENT};
+
+use crate::bytes_to_binary;
+use crate::error::KzgError;
+
+const BYTES_PER_CHUNK: usize = BYTES_PER_FIELD_ELEMENT - 1;
+const CHUNKS_PER_BLOB: usize = BYTES_PER_BLOB / BYTES_PER_FIELD_ELEMENT;
+const LENGTH_PREFIX: usize = std::mem::size_of::<u64>();
+
+fn pack(data: &[u8]) -> Vec<Vec<u8>> {
+    let mut packed = Vec::with_capacity(LENGTH_PREFIX + data.len());
+    packed.extend_from_slice(&(data.len() as u64).to_be_bytes());
+    packed.extend_from_slice(data);
+
+    packed
+        .chunks(BYTES_PER_CHUNK * CHUNKS_PER_BLOB)
+        .map(|blob_data| {
+            let mut blob = vec![0; BYTES_PER_BLOB];
+            for (field_element, chunk) in blob
+                .chunks_exact_mut(BYTES_PER_FIELD_ELEMENT)
+                .zip(blob_data.chunks(BYTES_PER_CHUNK))
+            {
+                field_element[1..=chunk.len()].copy_from_slice(chunk);
+            }
+            blob
+        })
+        .collect()
+}
+
+fn unpack(blobs: &[Binary]) -> Result<Vec<u8>, KzgError> {
+    let mut packed = Vec::with_capacity(blobs.len() * BYTES_PER_CHUNK * CHUNKS_PER_BLOB);
+    for (index, blob) in blobs.iter().enumerate() {
+        if blob.len() != BYTES_PER_BLOB {
+            return Err(KzgError::BlobLength(blob.len()));
+        }
+        for field_element in blob.chunks_exact(BYTES_PER_FIELD_ELEMENT) {
+            if field_element[0] != 0 {
+                return Err(invalid(format!(
+                    "Blob {index} has a field element with a non-zero first byte"
+                )));
+            }
+            packed.extend_from_slice(&field_element[1..]);
+        }
+    }
+
+    if packed.len() < LENGTH_PREFIX {
+        return Err(invalid("No blobs".to_owned()));
+    }
+    let (length, rest) = packed.split_at(LENGTH_PREFIX);
+    let length = u64::from_be_bytes(length.try_into().unwrap());
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|length| *length <= rest.len())
+        .ok_or_else(|| {
+            invalid(format!(
+                "Length prefix is {length}, but the blobs hold {} bytes",
+                rest.len()
+            ))
+        })?;
+    let (data, padding) = rest.split_at(length);
+    // Blobs beyond the ones holding the data, or non-zero padding, would mean
+    // the same data has more than one encoding
+    if padding.len() >= BYTES_PER_CHUNK * CHUNKS_PER_BLOB || padding.iter().any(|byte| *byte != 0) {
+        return Err(invalid("The blobs have data past the end".to_owned()));
+    }
+    Ok(data.to_vec())
+}
+
+fn invalid(detail: String) -> KzgError {
+    KzgError::InvalidBlobEncoding(detail)
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn pack_blobs<'env>(env: Env<'env>, data: Binary) -> Result<Vec<Binary<'env>>, KzgError> {
+    Ok(pack(&data)
+        .iter()
+        .map(|blob| bytes_to_binary(env, blob))
+        .collect())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn unpack_blobs<'env>(env: Env<'env>, blobs: Vec<Binary>) -> Result<Binary<'env>, KzgError> {
+    Ok(bytes_to_binary(env, &unpack(&blobs)?))
+}
@@ -0,0 +1,145 @@
+//! Process-wide cache of the trusted setup. It's parsed once, when the library
+//! is loaded or when a new one is explicitly loaded, and shared by all calls.
+
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use c_kzg::{KzgSettings, FIELD_ELEMENTS_PER_BLOB};
+
+use crate::error::KzgError;
+
+const BYTES_PER_G1_POINT: usize = 48;
+const BYTES_PER_G2_POINT: usize = 96;
+const NUM_G2_POINTS: usize = 65;
+
+/// Number of precomputed tables for cell proofs. Higher values make computing
+/// them faster at the cost of memory.
+const PRECOMPUTE: u64 = 0;
+
+/// A trusted setup, either the embedded one or one loaded from a file. Calls
+/// running in other threads keep the one they got after a reload, and loaded
+/// ones are freed once they're done.
+#[derive(Clone)]
+pub(crate) enum Settings {
+    Embedded(&'static KzgSettings),
+    Loaded(Arc<KzgSettings>),
+}
+
+impl Deref for Settings {
+    type Target = KzgSettings;
+
+    fn deref(&self) -> &KzgSettings {
+        match self {
+            Settings::Embedded(settings) => settings,
+            Settings::Loaded(settings) => settings,
+        }
+    }
+}
+
+// Keeps the error of a failed load, so that calls return it instead of silently
+// falling back to another setup.
+static TRUSTED_SETUP: RwLock<Option<Result<Settings, KzgError>>> = RwLock::new(None);
+
+/// Loads the trusted setup from `path`, or uses the mainnet one embedded in
+/// c-kzg if `None`, and caches it. Failures are cached too, and returned by [`get`].
+pub(crate) fn init(path: Option<&Path>) {
+    let settings = match path {
+        Some(path) => load_file(path),
+        None => Ok(Settings::Embedded(c_kzg::ethereum_kzg_settings(PRECOMPUTE))),
+    };
+    set(settings);
+}
+
+/// Replaces the cached trusted setup with the one in `path`. On failure, the
+/// cached one is kept.
+pub(crate) fn reload(path: &Path) -> Result<(), KzgError> {
+    set(Ok(load_file(path)?));
+    Ok(())
+}
+
+pub(crate) fn get() -> Result<Settings, KzgError> {
+    let trusted_setup = TRUSTED_SETUP
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match trusted_setup.as_ref() {
+        Some(settings) => settings.clone(),
+        None => Err(KzgError::InvalidTrustedSetup("Not loaded".to_owned())),
+    }
+}
+
+fn set(settings: Result<Settings, KzgError>) {
+    let mut trusted_setup = TRUSTED_SETUP
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    *trusted_setup = Some(settings);
+}
+
+fn load_file(path: &Path) -> Result<Settings, KzgError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        KzgError::InvalidTrustedSetup(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    Ok(Settings::Loaded(Arc::new(parse(&contents)?)))
+}
+
+/// Parses a trusted setup in the c-kzg text format: the number of G1 points, the
+/// number of G2 points, and then each point in hex, one per line. Points are in
+/// order: G1 in Lagrange form, G2 in monomial form and G1 in monomial form.
+fn parse(contents: &str) -> Result<KzgSettings, KzgError> {
+    let mut lines = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty());
+    let g1_count = parse_count(lines.next(), FIELD_ELEMENTS_PER_BLOB)?;
+    let g2_count = parse_count(lines.next(), NUM_G2_POINTS)?;
+    let g1_lagrange = parse_points(&mut lines, g1_count, BYTES_PER_G1_POINT)?;
+    let g2_monomial = parse_points(&mut lines, g2_count, BYTES_PER_G2_POINT)?;
+    let g1_monomial = parse_points(&mut lines, g1_count, BYTES_PER_G1_POINT)?;
+    if lines.next().is_some() {
+        return Err(invalid("Unexpected data after the last point"));
+    }
+    Ok(KzgSettings::load_trusted_setup(
+        &g1_monomial,
+        &g1_lagrange,
+        &g2_monomial,
+        PRECOMPUTE,
+    )?)
+}
+
+// Only the sizes of the Ethereum setup are supported, so anything else is
+// rejected before allocating room for the points.
+fn parse_count(line: Option<&str>, expected: usize) -> Result<usize, KzgError> {
+    let line = line.ok_or_else(|| invalid("Missing number of points"))?;
+    match line.parse() {
+        Ok(count) if count == expected => Ok(count),
+        Ok(count) => Err(invalid(&format!(
+            "Expected {} points, got {}",
+            expected, count
+        ))),
+        Err(_) => Err(invalid(&format!("Invalid number of points: {}", line))),
+    }
+}
+
+/// Returns the concatenation of the next `count` points.
+fn parse_points<'a>(
+    lines: &mut impl Iterator<Item = &'a str>,
+    count: usize,
+    point_length: usize,
+) -> Result<Vec<u8>, KzgError> {
+    let length = count
+        .checked_mul(point_length)
+        .ok_or_else(|| invalid("Too many points"))?;
+    let mut points = vec![0; length];
+    for point in points.chunks_exact_mut(point_length) {
+        let line = lines
+            .next()
+            .ok_or_else(|| invalid("Fewer points than expected"))?;
+        hex::decode_to_slice(line.trim_start_matches("0x"), point)
+            .map_err(|e| invalid(&format!("Invalid point {}: {}", line, e)))?;
+    }
+    Ok(points)
+}
+
+fn invalid(detail: &str) -> KzgError {
+    KzgError::InvalidTrustedSetup(detail.to_owned())
+}
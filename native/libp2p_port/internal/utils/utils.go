@@ -67,7 +67,10 @@ func ConvertToInterfacePubkey(pubkey *ecdsa.PublicKey) (crypto.PubKey, error) {
 	return newKey, nil
 }
 
-// Only valid for post-Altair topics
+// Only valid for post-Altair topics.
+// Gossipsub needs the id before the message reaches Elixir, to deduplicate it
+// and to advertise it to peers in IHAVE/IWANT, so it's computed here and sent
+// along with the message instead of in a NIF.
 func MsgID(msg *pb.Message) string {
 	if msg == nil || msg.Data == nil || msg.Topic == nil {
 		// Should never happen
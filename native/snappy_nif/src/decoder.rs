@@ -0,0 +1,130 @@
+//! Incremental decoder of snappy frame streams, for data that arrives in pieces.
+//! Only complete chunks of the stream are given to the frame decoder, so it
+//! never sees a truncated chunk, and its output is bounded like in
+//! `decompress/2`.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use rustler::{Binary, Env, ResourceArc};
+use snap::read::FrameDecoder;
+
+use crate::{bytes_to_binary, read_bounded, SnappyError};
+
+const CHUNK_HEADER_LENGTH: usize = 4;
+/// Longest chunk we buffer: 64 KiB of uncompressed data plus its checksum. The
+/// format allows longer padding chunks, but encoders don't produce them.
+const MAX_CHUNK_LENGTH: usize = (1 << 16) + 4;
+
+struct State {
+    frames: FrameDecoder<VecDeque<u8>>,
+    // Bytes of a chunk that hasn't fully arrived yet
+    pending: Vec<u8>,
+    decompressed: usize,
+    max_length: usize,
+    // Once failed, the decoder keeps returning the same error
+    error: Option<SnappyError>,
+}
+
+impl State {
+    fn feed(&mut self, data: &[u8]) -> Result<Vec<u8>, SnappyError> {
+        self.pending.extend_from_slice(data);
+        let complete = self.complete_chunks_length()?;
+        self.frames.get_mut().extend(self.pending.drain(..complete));
+
+        let mut output = Vec::new();
+        read_bounded(
+            &mut self.frames,
+            self.max_length - self.decompressed,
+            &mut output,
+        )?;
+        self.decompressed += output.len();
+        Ok(output)
+    }
+
+    /// Returns the length of the complete chunks at the start of `pending`.
+    fn complete_chunks_length(&self) -> Result<usize, SnappyError> {
+        let mut offset = 0;
+        while let Some(header) = self.pending.get(offset..offset + CHUNK_HEADER_LENGTH) {
+            let length = u32::from_le_bytes([header[1], header[2], header[3], 0]) as usize;
+            if length > MAX_CHUNK_LENGTH {
+                return Err(SnappyError::CorruptInput(format!(
+                    "Chunk of {length} bytes is longer than the maximum of {MAX_CHUNK_LENGTH}"
+                )));
+            }
+            let end = offset + CHUNK_HEADER_LENGTH + length;
+            if end > self.pending.len() {
+                break;
+            }
+            offset = end;
+        }
+        Ok(offset)
+    }
+}
+
+struct Decoder(Mutex<State>);
+
+#[allow(non_local_definitions)]
+pub(crate) fn load(env: Env) -> bool {
+    rustler::resource!(Decoder, env);
+    true
+}
+
+/// Runs `f` on the decoder's state, unless it already failed. Errors are kept.
+fn with_state<T>(
+    decoder: &Decoder,
+    f: impl FnOnce(&mut State) -> Result<T, SnappyError>,
+) -> Result<T, SnappyError> {
+    // A panic while holding the lock can only come from the frame decoder, so
+    // the state is treated as failed
+    let mut state = decoder
+        .0
+        .lock()
+        .map_err(|_| SnappyError::Io("Decoder panicked".to_owned()))?;
+    if let Some(error) = &state.error {
+        return Err(error.clone());
+    }
+    let result = f(&mut state);
+    if let Err(error) = &result {
+        state.error = Some(error.clone());
+    }
+    result
+}
+
+#[rustler::nif]
+fn decoder_new(max_length: usize) -> ResourceArc<Decoder> {
+    ResourceArc::new(Decoder(Mutex::new(State {
+        frames: FrameDecoder::new(VecDeque::new()),
+        pending: Vec::new(),
+        decompressed: 0,
+        max_length,
+        error: None,
+    })))
+}
+
+// Returns the data decompressed from the complete chunks received so far, which
+// may be empty.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn decoder_feed<'env>(
+    env: Env<'env>,
+    decoder: ResourceArc<Decoder>,
+    data: Binary,
+) -> Result<Binary<'env>, SnappyError> {
+    let output = with_state(&decoder, |state| state.feed(&data))?;
+    Ok(bytes_to_binary(env, &output))
+}
+
+// Checks that the stream didn't end in the middle of a chunk, and returns the
+// total decompressed length.
+#[rustler::nif]
+fn decoder_finish(decoder: ResourceArc<Decoder>) -> Result<usize, SnappyError> {
+    with_state(&decoder, |state| {
+        if !state.pending.is_empty() {
+            return Err(SnappyError::CorruptInput(format!(
+                "Stream ends with {} bytes of an incomplete chunk",
+                state.pending.len()
+            )));
+        }
+        Ok(state.decompressed)
+    })
+}
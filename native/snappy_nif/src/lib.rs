@@ -1,9 +1,69 @@
-use std::io::{Read, Write};
+mod decoder;
+mod req_resp;
 
-use rustler::{Binary, Env, NewBinary};
-use snap::read;
+use std::io::{ErrorKind, Read, Write};
 
-fn bytes_to_binary<'env>(env: Env<'env>, bytes: &[u8]) -> Binary<'env> {
+use rustler::{Binary, Encoder, Env, NewBinary, Term};
+use snap::{raw, read};
+
+mod atoms {
+    rustler::atoms! {
+        corrupt_input,
+        io_error,
+        max_length_exceeded,
+        invalid_length_prefix,
+    }
+}
+
+// Errors returned by the NIFs, see `docs/nif_errors.md`.
+#[derive(Clone)]
+pub(crate) enum SnappyError {
+    CorruptInput(String),
+    Io(String),
+    MaxLengthExceeded(usize),
+    /// Length prefix, and the minimum and maximum lengths allowed.
+    LengthPrefix(u64, usize, usize),
+}
+
+impl From<std::io::Error> for SnappyError {
+    fn from(e: std::io::Error) -> Self {
+        // The frame reader wraps decoding errors in an `io::Error`, and
+        // reports truncated streams as unexpected EOFs.
+        if let Some(snap_error) = e
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<snap::Error>())
+        {
+            return SnappyError::CorruptInput(snap_error.to_string());
+        }
+        match e.kind() {
+            ErrorKind::UnexpectedEof => SnappyError::CorruptInput(e.to_string()),
+            _ => SnappyError::Io(e.to_string()),
+        }
+    }
+}
+
+impl From<snap::Error> for SnappyError {
+    fn from(e: snap::Error) -> Self {
+        SnappyError::CorruptInput(e.to_string())
+    }
+}
+
+impl Encoder for SnappyError {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            SnappyError::CorruptInput(detail) => (atoms::corrupt_input(), detail).encode(env),
+            SnappyError::Io(detail) => (atoms::io_error(), detail).encode(env),
+            SnappyError::MaxLengthExceeded(max_length) => {
+                (atoms::max_length_exceeded(), max_length).encode(env)
+            }
+            SnappyError::LengthPrefix(got, min, max) => {
+                (atoms::invalid_length_prefix(), got, (min, max)).encode(env)
+            }
+        }
+    }
+}
+
+pub(crate) fn bytes_to_binary<'env>(env: Env<'env>, bytes: &[u8]) -> Binary<'env> {
     let mut binary = NewBinary::new(env, bytes.len());
     // This cannot fail because bin size equals bytes len
     binary.as_mut_slice().write_all(bytes).unwrap();
@@ -11,23 +71,100 @@ fn bytes_to_binary<'env>(env: Env<'env>, bytes: &[u8]) -> Binary<'env> {
 }
 
 #[rustler::nif]
-fn compress<'env>(env: Env<'env>, bin: Binary) -> Result<Binary<'env>, String> {
+fn compress<'env>(env: Env<'env>, bin: Binary) -> Result<Binary<'env>, SnappyError> {
     let mut encoder = read::FrameEncoder::new(&bin[..]);
     let mut buffer = Vec::with_capacity(bin.len());
-    encoder
-        .read_to_end(&mut buffer)
-        .map_err(|e| e.to_string())?;
+    encoder.read_to_end(&mut buffer)?;
     Ok(bytes_to_binary(env, &buffer))
 }
 
 #[rustler::nif]
-fn decompress<'env>(env: Env<'env>, bin: Binary) -> Result<Binary<'env>, String> {
+fn decompress<'env>(env: Env<'env>, bin: Binary) -> Result<Binary<'env>, SnappyError> {
     let mut decoder = read::FrameDecoder::new(&bin[..]);
     let mut buffer = Vec::with_capacity(bin.len());
-    decoder
-        .read_to_end(&mut buffer)
-        .map_err(|e| e.to_string())?;
+    decoder.read_to_end(&mut buffer)?;
     Ok(bytes_to_binary(env, &buffer))
 }
 
-rustler::init!("Elixir.Snappy", [compress, decompress]);
+/// Reads all of `reader`, failing as soon as it yields more than `max_length` bytes.
+pub(crate) fn read_bounded(
+    reader: impl Read,
+    max_length: usize,
+    buffer: &mut Vec<u8>,
+) -> Result<(), SnappyError> {
+    // `max_length` may be `usize::MAX`, so this can't just add one
+    let limit = ((max_length - buffer.len()) as u64).saturating_add(1);
+    reader.take(limit).read_to_end(buffer)?;
+    if buffer.len() > max_length {
+        return Err(SnappyError::MaxLengthExceeded(max_length));
+    }
+    Ok(())
+}
+
+// The initial capacity is bounded too, as `max_length` may come from the peer.
+#[rustler::nif(name = "decompress", schedule = "DirtyCpu")]
+fn decompress_bounded<'env>(
+    env: Env<'env>,
+    bin: Binary,
+    max_length: usize,
+) -> Result<Binary<'env>, SnappyError> {
+    let decoder = read::FrameDecoder::new(&bin[..]);
+    let mut buffer = Vec::with_capacity(bin.len().min(max_length));
+    read_bounded(decoder, max_length, &mut buffer)?;
+    Ok(bytes_to_binary(env, &buffer))
+}
+
+// Raw (unframed) snappy, as used in gossip messages. The decompressed length
+// is in the header, so it's checked before decompressing.
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn compress_raw<'env>(env: Env<'env>, bin: Binary) -> Result<Binary<'env>, SnappyError> {
+    let compressed = raw::Encoder::new().compress_vec(&bin)?;
+    Ok(bytes_to_binary(env, &compressed))
+}
+
+fn raw_decompress(bin: &[u8], max_length: usize) -> Result<Vec<u8>, SnappyError> {
+    let length = raw::decompress_len(bin)?;
+    if length > max_length {
+        return Err(SnappyError::MaxLengthExceeded(max_length));
+    }
+    Ok(raw::Decoder::new().decompress_vec(bin)?)
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn decompress_raw<'env>(env: Env<'env>, bin: Binary) -> Result<Binary<'env>, SnappyError> {
+    Ok(bytes_to_binary(env, &raw_decompress(&bin, usize::MAX)?))
+}
+
+#[rustler::nif(name = "decompress_raw", schedule = "DirtyCpu")]
+fn decompress_raw_bounded<'env>(
+    env: Env<'env>,
+    bin: Binary,
+    max_length: usize,
+) -> Result<Binary<'env>, SnappyError> {
+    Ok(bytes_to_binary(env, &raw_decompress(&bin, max_length)?))
+}
+
+fn load(env: Env, _load_info: Term) -> bool {
+    decoder::load(env)
+}
+
+rustler::init!(
+    "Elixir.Snappy",
+    [
+        compress,
+        decompress,
+        decompress_bounded,
+        compress_raw,
+        decompress_raw,
+        decompress_raw_bounded,
+        decoder::decoder_new,
+        decoder::decoder_feed,
+        decoder::decoder_finish,
+        req_resp::encode_request,
+        req_resp::encode_response_chunks,
+        req_resp::decode_request,
+        req_resp::decode_response_chunks
+    ],
+    load = load
+);
@@ -0,0 +1,203 @@
+//! `ssz_snappy` encoding of Req/Resp messages. A request is
+//! `<length> <snappy frames>`, and a response is a sequence of chunks
+//! `<result> <context-bytes> <length> <snappy frames>`, where `length` is the
+//! unsigned varint length of the SSZ bytes. Error chunks have no context bytes.
+
+use std::io::Read;
+
+use rustler::{Binary, Env};
+use snap::{raw, read};
+
+use crate::{bytes_to_binary, read_bounded, SnappyError};
+
+const SUCCESS: u8 = 0;
+/// Longest varint that fits in a `u64`.
+const MAX_VARINT_LENGTH: usize = 10;
+/// `ErrorMessage` is a `List[byte, 256]`.
+const MAX_ERROR_MESSAGE_LENGTH: usize = 256;
+const CHUNK_HEADER_LENGTH: usize = 4;
+const CHECKSUM_LENGTH: usize = 4;
+const STREAM_IDENTIFIER: u8 = 0xff;
+
+fn encode_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn decode_varint(input: &mut &[u8]) -> Result<u64, SnappyError> {
+    let bytes: &[u8] = input;
+    let mut value: u64 = 0;
+    for (i, byte) in bytes.iter().take(MAX_VARINT_LENGTH).enumerate() {
+        let bits = (byte & 0x7f) as u64;
+        if i == MAX_VARINT_LENGTH - 1 && bits > 1 {
+            break;
+        }
+        value |= bits << (7 * i);
+        if byte & 0x80 == 0 {
+            // Canonical varints don't end in a zero byte
+            if i > 0 && *byte == 0 {
+                return Err(corrupt("Non-canonical length prefix"));
+            }
+            *input = &bytes[i + 1..];
+            return Ok(value);
+        }
+    }
+    Err(corrupt("Invalid length prefix"))
+}
+
+/// Longest snappy encoding of `length` bytes, as in the spec.
+fn max_compressed_length(length: usize) -> usize {
+    32 + length + length / 6
+}
+
+fn compress(ssz: &[u8], out: &mut Vec<u8>) -> Result<(), SnappyError> {
+    encode_varint(ssz.len() as u64, out);
+    read::FrameEncoder::new(ssz).read_to_end(out)?;
+    Ok(())
+}
+
+/// Returns the type and body of the chunk of the frame stream at `offset`.
+fn chunk_at(input: &[u8], offset: usize) -> Result<(u8, &[u8]), SnappyError> {
+    let header = input
+        .get(offset..offset + CHUNK_HEADER_LENGTH)
+        .ok_or_else(|| corrupt("Stream ends before the announced length"))?;
+    let length = u32::from_le_bytes([header[1], header[2], header[3], 0]) as usize;
+    let start = offset + CHUNK_HEADER_LENGTH;
+    let body = input
+        .get(start..start + length)
+        .ok_or_else(|| corrupt("Truncated chunk"))?;
+    Ok((header[0], body))
+}
+
+/// Returns the length of the frames at the start of `input` that decompress to
+/// `length` bytes. Frames are only measured here; [`decompress`] validates them.
+fn frames_length(input: &[u8], length: usize) -> Result<usize, SnappyError> {
+    let mut offset = 0;
+    let mut decompressed = 0;
+    while decompressed < length || (offset == 0 && input.first() == Some(&STREAM_IDENTIFIER)) {
+        let (chunk_type, body) = chunk_at(input, offset)?;
+        decompressed += match chunk_type {
+            0x00 => raw::decompress_len(body.get(CHECKSUM_LENGTH..).unwrap_or_default())?,
+            0x01 => body.len().saturating_sub(CHECKSUM_LENGTH),
+            _ => 0,
+        };
+        offset += CHUNK_HEADER_LENGTH + body.len();
+        if decompressed > length {
+            return Err(corrupt("Frames are longer than the announced length"));
+        }
+        if offset > max_compressed_length(length) {
+            return Err(corrupt(
+                "Frames are longer than any encoding of the payload",
+            ));
+        }
+    }
+    Ok(offset)
+}
+
+/// Decodes `<length> <snappy frames>` at the start of `input`, checking that the
+/// length is in bounds, and advances `input` past it.
+fn decompress(
+    input: &mut &[u8],
+    min_length: usize,
+    max_length: usize,
+) -> Result<Vec<u8>, SnappyError> {
+    let length = decode_varint(input)?;
+    let length = usize::try_from(length)
+        .ok()
+        .filter(|length| (min_length..=max_length).contains(length))
+        .ok_or(SnappyError::LengthPrefix(length, min_length, max_length))?;
+
+    let bytes: &[u8] = input;
+    let (frames, rest) = bytes.split_at(frames_length(bytes, length)?);
+    let mut ssz = Vec::with_capacity(length);
+    read_bounded(read::FrameDecoder::new(frames), length, &mut ssz)?;
+    if ssz.len() != length {
+        return Err(corrupt("Frames are shorter than the announced length"));
+    }
+    *input = rest;
+    Ok(ssz)
+}
+
+fn corrupt(detail: &str) -> SnappyError {
+    SnappyError::CorruptInput(detail.to_owned())
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn encode_request<'env>(env: Env<'env>, ssz: Binary) -> Result<Binary<'env>, SnappyError> {
+    let mut out = Vec::with_capacity(MAX_VARINT_LENGTH + max_compressed_length(ssz.len()));
+    compress(&ssz, &mut out)?;
+    Ok(bytes_to_binary(env, &out))
+}
+
+// Each chunk is `{result, context_bytes, ssz}`. The context bytes of error
+// chunks are ignored.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn encode_response_chunks<'env>(
+    env: Env<'env>,
+    chunks: Vec<(u8, Binary, Binary)>,
+) -> Result<Binary<'env>, SnappyError> {
+    let mut out = Vec::new();
+    for (result, context_bytes, ssz) in chunks {
+        out.push(result);
+        if result == SUCCESS {
+            out.extend_from_slice(&context_bytes);
+        }
+        compress(&ssz, &mut out)?;
+    }
+    Ok(bytes_to_binary(env, &out))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn decode_request<'env>(
+    env: Env<'env>,
+    bin: Binary,
+    min_length: usize,
+    max_length: usize,
+) -> Result<Binary<'env>, SnappyError> {
+    let mut input = bin.as_slice();
+    let ssz = decompress(&mut input, min_length, max_length)?;
+    if !input.is_empty() {
+        return Err(corrupt("Data after the end of the request"));
+    }
+    Ok(bytes_to_binary(env, &ssz))
+}
+
+// Returns each chunk as `{result, context_bytes, ssz}`. Successful chunks have
+// `context_length` context bytes, and a payload between the lengths given.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn decode_response_chunks<'env>(
+    env: Env<'env>,
+    bin: Binary,
+    context_length: usize,
+    min_length: usize,
+    max_length: usize,
+) -> Result<Vec<(u8, Binary<'env>, Binary<'env>)>, SnappyError> {
+    let mut input = bin.as_slice();
+    let mut chunks = vec![];
+    while let Some((&result, rest)) = input.split_first() {
+        input = rest;
+        let (context_bytes, ssz) = if result == SUCCESS {
+            if input.len() < context_length {
+                return Err(corrupt("Truncated context bytes"));
+            }
+            let (context_bytes, rest) = input.split_at(context_length);
+            input = rest;
+            (
+                context_bytes,
+                decompress(&mut input, min_length, max_length)?,
+            )
+        } else {
+            let message = decompress(&mut input, 0, MAX_ERROR_MESSAGE_LENGTH)?;
+            (&[][..], message)
+        };
+        chunks.push((
+            result,
+            bytes_to_binary(env, context_bytes),
+            bytes_to_binary(env, &ssz),
+        ));
+    }
+    Ok(chunks)
+}
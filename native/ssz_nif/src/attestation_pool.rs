@@ -0,0 +1,261 @@
+//! Naive aggregation pool for Electra attestations, keyed by the root of their
+//! [`AttestationData`]. Attestations with the same committee bits and disjoint
+//! aggregation bits are merged as they arrive, aggregating their signatures.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use bls::AggregateSignature;
+use rustler::{Atom, Binary, Encoder, Env, NifResult, NifUnitEnum, ResourceArc, Term};
+use ssz::{Decode, Encode};
+use ssz_types::{typenum::Unsigned, BitList, BitVector, FixedVector};
+use tree_hash::TreeHash;
+
+use crate::ssz_types::config::Config;
+use crate::ssz_types::{Attestation, AttestationData, Root, SingleAttestation};
+use crate::utils::errors::{invalid_signature_encoding, invalid_ssz, invalid_value};
+use crate::utils::helpers::bytes_to_binary;
+use crate::utils::preset_match;
+
+mod atoms {
+    use rustler::atoms;
+
+    atoms! {
+        ok,
+    }
+}
+
+struct Aggregate {
+    committee_bits: Vec<bool>,
+    aggregation_bits: Vec<bool>,
+    signature: AggregateSignature,
+}
+
+impl Aggregate {
+    fn participants(&self) -> usize {
+        self.aggregation_bits.iter().filter(|bit| **bit).count()
+    }
+}
+
+struct Entry {
+    data: AttestationData,
+    aggregates: Vec<Aggregate>,
+}
+
+/// What happened to an inserted attestation.
+#[derive(NifUnitEnum)]
+enum Insertion {
+    /// It didn't fit in any aggregate, so it started a new one.
+    New,
+    /// It was merged into an existing aggregate.
+    Aggregated,
+    /// All of its bits were already in an aggregate.
+    AlreadyKnown,
+}
+
+pub(crate) struct AttestationPool(Mutex<HashMap<Root, Entry>>);
+
+impl AttestationPool {
+    fn lock(&self) -> MutexGuard<'_, HashMap<Root, Entry>> {
+        // Entries are only modified after all fallible steps
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn insert(&self, data: AttestationData, incoming: Aggregate) -> Insertion {
+        let root = data.tree_hash_root().0;
+        let mut entries = self.lock();
+        let entry = entries.entry(root).or_insert_with(|| Entry {
+            data,
+            aggregates: vec![],
+        });
+
+        let compatible = entry.aggregates.iter_mut().filter(|aggregate| {
+            aggregate.committee_bits == incoming.committee_bits
+                && aggregate.aggregation_bits.len() == incoming.aggregation_bits.len()
+        });
+        for aggregate in compatible {
+            let bit_pairs = || {
+                aggregate
+                    .aggregation_bits
+                    .iter()
+                    .zip(&incoming.aggregation_bits)
+            };
+            if bit_pairs().all(|(old, new)| *old || !*new) {
+                return Insertion::AlreadyKnown;
+            }
+            if bit_pairs().all(|(old, new)| !(*old && *new)) {
+                let new_bits = incoming.aggregation_bits.iter();
+                for (old, new) in aggregate.aggregation_bits.iter_mut().zip(new_bits) {
+                    *old |= *new;
+                }
+                aggregate
+                    .signature
+                    .add_assign_aggregate(&incoming.signature);
+                return Insertion::Aggregated;
+            }
+        }
+        entry.aggregates.push(incoming);
+        Insertion::New
+    }
+}
+
+#[allow(non_local_definitions)]
+pub(crate) fn load(env: Env) -> bool {
+    rustler::resource!(AttestationPool, env);
+    true
+}
+
+fn deserialize_signature(bytes: &[u8]) -> NifResult<AggregateSignature> {
+    AggregateSignature::deserialize(bytes).map_err(invalid_signature_encoding)
+}
+
+fn decode_attestation<C: Config>(bytes: &[u8]) -> NifResult<(AttestationData, Aggregate)> {
+    let attestation = Attestation::<C>::from_ssz_bytes(bytes).map_err(invalid_ssz)?;
+    let aggregate = Aggregate {
+        committee_bits: attestation.committee_bits.iter().collect(),
+        aggregation_bits: attestation.aggregation_bits.iter().collect(),
+        signature: deserialize_signature(&attestation.signature)?,
+    };
+    Ok((attestation.data, aggregate))
+}
+
+fn decode_single_attestation<C: Config>(
+    bytes: &[u8],
+    index_in_committee: usize,
+    committee_length: usize,
+) -> NifResult<(AttestationData, Aggregate)> {
+    let attestation = SingleAttestation::from_ssz_bytes(bytes).map_err(invalid_ssz)?;
+    let committee_index = attestation.committee_index as usize;
+    let committees = C::MaxCommitteesPerSlot::to_usize();
+    if committee_index >= committees {
+        return Err(invalid_value(
+            format!("Committee index {committee_index} out of {committees} committees").into(),
+        ));
+    }
+    if index_in_committee >= committee_length {
+        return Err(invalid_value(
+            format!("Index {index_in_committee} out of a committee of {committee_length}").into(),
+        ));
+    }
+
+    let mut committee_bits = vec![false; committees];
+    committee_bits[committee_index] = true;
+    let mut aggregation_bits = vec![false; committee_length];
+    aggregation_bits[index_in_committee] = true;
+    let aggregate = Aggregate {
+        committee_bits,
+        aggregation_bits,
+        signature: deserialize_signature(&attestation.signature)?,
+    };
+    Ok((attestation.data, aggregate))
+}
+
+fn encode_aggregate<C: Config>(
+    data: &AttestationData,
+    aggregate: &Aggregate,
+) -> NifResult<Vec<u8>> {
+    let mut aggregation_bits =
+        BitList::<C::MaxValidatorsPerSlot>::with_capacity(aggregate.aggregation_bits.len())
+            .map_err(invalid_ssz)?;
+    for (i, _) in aggregate
+        .aggregation_bits
+        .iter()
+        .enumerate()
+        .filter(|(_, bit)| **bit)
+    {
+        aggregation_bits.set(i, true).map_err(invalid_ssz)?;
+    }
+    let mut committee_bits = BitVector::<C::MaxCommitteesPerSlot>::new();
+    for (i, _) in aggregate
+        .committee_bits
+        .iter()
+        .enumerate()
+        .filter(|(_, bit)| **bit)
+    {
+        committee_bits.set(i, true).map_err(invalid_ssz)?;
+    }
+
+    let attestation = Attestation::<C> {
+        aggregation_bits,
+        data: data.clone(),
+        signature: FixedVector::new(aggregate.signature.serialize().to_vec())
+            .map_err(invalid_ssz)?,
+        committee_bits,
+    };
+    Ok(attestation.as_ssz_bytes())
+}
+
+#[rustler::nif]
+fn attestation_pool_new() -> ResourceArc<AttestationPool> {
+    ResourceArc::new(AttestationPool(Mutex::new(HashMap::new())))
+}
+
+#[rustler::nif]
+fn attestation_pool_insert<'env>(
+    env: Env<'env>,
+    pool: ResourceArc<AttestationPool>,
+    attestation: Binary,
+    config: Atom,
+) -> NifResult<Term<'env>> {
+    let config = config.to_term(env).atom_to_string()?;
+    let (data, aggregate) = preset_match!(config.as_str(), decode_attestation(&attestation))?;
+    Ok((atoms::ok(), pool.insert(data, aggregate)).encode(env))
+}
+
+#[rustler::nif]
+fn attestation_pool_insert_single<'env>(
+    env: Env<'env>,
+    pool: ResourceArc<AttestationPool>,
+    attestation: Binary,
+    index_in_committee: usize,
+    committee_length: usize,
+    config: Atom,
+) -> NifResult<Term<'env>> {
+    let config = config.to_term(env).atom_to_string()?;
+    let (data, aggregate) = preset_match!(
+        config.as_str(),
+        decode_single_attestation(&attestation, index_in_committee, committee_length)
+    )?;
+    Ok((atoms::ok(), pool.insert(data, aggregate)).encode(env))
+}
+
+// Returns the aggregate with the most participants for the given data root, if any.
+#[rustler::nif]
+fn attestation_pool_get_aggregate<'env>(
+    env: Env<'env>,
+    pool: ResourceArc<AttestationPool>,
+    data_root: Binary,
+    config: Atom,
+) -> NifResult<Term<'env>> {
+    let config = config.to_term(env).atom_to_string()?;
+    let root = Root::try_from(data_root.as_slice())
+        .map_err(|_| invalid_value("Data root must be 32 bytes long".to_owned().into()))?;
+
+    let entries = pool.lock();
+    let best = entries.get(&root).and_then(|entry| {
+        let aggregate = entry.aggregates.iter().max_by_key(|a| a.participants())?;
+        Some((&entry.data, aggregate))
+    });
+    let encoded = match best {
+        Some((data, aggregate)) => Some(preset_match!(
+            config.as_str(),
+            encode_aggregate(data, aggregate)
+        )?),
+        None => None,
+    };
+    Ok((
+        atoms::ok(),
+        encoded.map(|bytes| bytes_to_binary(env, &bytes)),
+    )
+        .encode(env))
+}
+
+// Drops the attestations for slots before `min_slot`, returning how many data
+// roots were removed.
+#[rustler::nif]
+fn attestation_pool_prune(pool: ResourceArc<AttestationPool>, min_slot: u64) -> usize {
+    let mut entries = pool.lock();
+    let len = entries.len();
+    entries.retain(|_, entry| entry.data.slot >= min_slot);
+    len - entries.len()
+}
@@ -0,0 +1,225 @@
+//! Building of [`BlobSidecar`]s and validation of their inclusion proofs. KZG
+//! proofs are computed and verified by the KZG library, with the setup it loaded.
+
+use rustler::{Atom, Binary, Encoder, Env, NifResult, Term};
+use ssz::{Decode, Encode};
+use ssz_types::{typenum::Unsigned, FixedVector};
+use tree_hash::TreeHash;
+
+use crate::elx_types;
+use crate::merkle_proof::{is_valid_merkle_branch, merkle_branch, merkle_branches};
+use crate::ssz_types::config::Config;
+use crate::ssz_types::{
+    BeaconBlockBody, BeaconBlockHeader, BlobSidecar, Root, SignedBeaconBlock,
+    SignedBeaconBlockHeader,
+};
+use crate::utils::errors::{invalid_ssz, invalid_value, unknown_fork};
+use crate::utils::from_ssz::FromSsz;
+use crate::utils::helpers::bytes_to_binary;
+use crate::utils::preset_match;
+
+mod atoms {
+    use rustler::atoms;
+
+    atoms! {
+        ok,
+    }
+}
+
+/// Depth of the tree of Electra `BeaconBlockBody` fields.
+pub(crate) const BODY_DEPTH: usize = 4;
+/// Position of `blob_kzg_commitments` in Electra `BeaconBlockBody`.
+const BLOB_KZG_COMMITMENTS_INDEX: u64 = 11;
+
+/// Depth of the body tree and position of `blob_kzg_commitments` in it, for the
+/// blocks of `fork`.
+fn commitments_position(fork: &str) -> NifResult<(usize, u64)> {
+    match fork {
+        // Deneb's 12 fields fit in the same depth as Electra's 13
+        "deneb" | "electra" => Ok((BODY_DEPTH, BLOB_KZG_COMMITMENTS_INDEX)),
+        "phase0" | "altair" | "bellatrix" | "capella" => Err(invalid_value(
+            format!("There are no blob sidecars in {}", fork).into(),
+        )),
+        other => Err(unknown_fork(other)),
+    }
+}
+
+/// Position of the commitment at `index` among the leaves of the body tree, at
+/// depth `KzgCommitmentInclusionProofDepth`. The extra level is the list length.
+pub(crate) fn commitment_subtree_index<C: Config>(
+    index: u64,
+    (body_depth, commitments_index): (usize, u64),
+) -> u64 {
+    let list_depth = C::KzgCommitmentInclusionProofDepth::to_usize() - body_depth - 1;
+    (commitments_index << (list_depth + 1)) | index
+}
+
+fn verify_inclusion_proof<C: Config>(sidecar: &BlobSidecar<C>, position: (usize, u64)) -> bool {
+    if sidecar.index >= C::MaxBlobCommitmentsPerBlock::to_u64() {
+        return false;
+    }
+    is_valid_merkle_branch(
+        sidecar.kzg_commitment.tree_hash_root().0,
+        &sidecar.kzg_commitment_inclusion_proof,
+        commitment_subtree_index::<C>(sidecar.index, position),
+        &sidecar.signed_block_header.message.body_root,
+    )
+}
+
+fn decode<'a, C: Config>(bytes: &[u8], env: Env<'a>, fork: &str) -> NifResult<Term<'a>> {
+    let position = commitments_position(fork)?;
+    let sidecar = BlobSidecar::<C>::from_ssz_bytes(bytes).map_err(invalid_ssz)?;
+    let block_root = sidecar.signed_block_header.message.tree_hash_root().0;
+    let inclusion_proof_valid = verify_inclusion_proof(&sidecar, position);
+    let sidecar: elx_types::BlobSidecar = FromSsz::from(sidecar, env);
+    Ok((
+        sidecar,
+        bytes_to_binary(env, &block_root),
+        inclusion_proof_valid,
+    )
+        .encode(env))
+}
+
+// Decodes a sidecar of `fork`. Returns it along with the root of its block and
+// whether the inclusion proof of its commitment is valid.
+#[rustler::nif]
+fn decode_blob_sidecar_rs<'env>(
+    env: Env<'env>,
+    sidecar: Binary,
+    config: Atom,
+    fork: Atom,
+) -> NifResult<Term<'env>> {
+    let config = config.to_term(env).atom_to_string()?;
+    let fork = fork.to_term(env).atom_to_string()?;
+    let result = preset_match!(config.as_str(), decode(&sidecar, env, fork.as_str()))?;
+    Ok((atoms::ok(), result).encode(env))
+}
+
+pub(crate) fn body_field_roots<C: Config>(body: &BeaconBlockBody<C>) -> Vec<Root> {
+    vec![
+        body.randao_reveal.tree_hash_root().0,
+        body.eth1_data.tree_hash_root().0,
+        body.graffiti.tree_hash_root().0,
+        body.proposer_slashings.tree_hash_root().0,
+        body.attester_slashings.tree_hash_root().0,
+        body.attestations.tree_hash_root().0,
+        body.deposits.tree_hash_root().0,
+        body.voluntary_exits.tree_hash_root().0,
+        body.sync_aggregate.tree_hash_root().0,
+        body.execution_payload.tree_hash_root().0,
+        body.bls_to_execution_changes.tree_hash_root().0,
+        body.blob_kzg_commitments.tree_hash_root().0,
+        body.execution_requests.tree_hash_root().0,
+    ]
+}
+
+/// Returns the inclusion proof of each commitment in the body: its branch in the
+/// commitments tree, the length of the list and the branch of the list in the body.
+fn inclusion_proofs<C: Config>(body: &BeaconBlockBody<C>) -> Vec<Vec<Root>> {
+    let commitments = &body.blob_kzg_commitments;
+    let list_depth = C::KzgCommitmentInclusionProofDepth::to_usize() - BODY_DEPTH - 1;
+    let body_branch = merkle_branch(
+        body_field_roots(body),
+        BLOB_KZG_COMMITMENTS_INDEX as usize,
+        BODY_DEPTH,
+    );
+    let mut length = [0; 32];
+    length[..8].copy_from_slice(&(commitments.len() as u64).to_le_bytes());
+
+    let leaves: Vec<Root> = commitments
+        .iter()
+        .map(|commitment| commitment.tree_hash_root().0)
+        .collect();
+    merkle_branches(leaves, list_depth)
+        .into_iter()
+        .map(|mut proof| {
+            proof.push(length);
+            proof.extend_from_slice(&body_branch);
+            proof
+        })
+        .collect()
+}
+
+fn build<C: Config>(
+    signed_block: &[u8],
+    blobs: &[Binary],
+    kzg_proofs: &[Binary],
+) -> NifResult<Vec<Vec<u8>>> {
+    let signed_block = SignedBeaconBlock::<C>::from_ssz_bytes(signed_block).map_err(invalid_ssz)?;
+    let block = &signed_block.message;
+    let commitments = &block.body.blob_kzg_commitments;
+    if blobs.len() != commitments.len() {
+        return Err(invalid_value(
+            format!(
+                "There are {} blobs but {} commitments in the block",
+                blobs.len(),
+                commitments.len()
+            )
+            .into(),
+        ));
+    }
+    if kzg_proofs.len() != blobs.len() {
+        return Err(invalid_value(
+            format!(
+                "There are {} blobs but {} proofs",
+                blobs.len(),
+                kzg_proofs.len()
+            )
+            .into(),
+        ));
+    }
+
+    let signed_block_header = SignedBeaconBlockHeader {
+        message: BeaconBlockHeader {
+            slot: block.slot,
+            proposer_index: block.proposer_index,
+            parent_root: block.parent_root,
+            state_root: block.state_root,
+            body_root: block.body.tree_hash_root().0,
+        },
+        signature: signed_block.signature.clone(),
+    };
+    let inclusion_proofs = inclusion_proofs(&block.body);
+
+    let mut sidecars = Vec::with_capacity(blobs.len());
+    for (index, ((blob, kzg_proof), inclusion_proof)) in blobs
+        .iter()
+        .zip(kzg_proofs)
+        .zip(inclusion_proofs)
+        .enumerate()
+    {
+        let sidecar = BlobSidecar::<C> {
+            index: index as u64,
+            blob: FixedVector::new(blob.to_vec()).map_err(|_| {
+                invalid_value(format!("Blob {} has {} bytes", index, blob.len()).into())
+            })?,
+            kzg_commitment: commitments[index].clone(),
+            kzg_proof: FixedVector::new(kzg_proof.to_vec()).map_err(|_| {
+                invalid_value(format!("Proof {} has {} bytes", index, kzg_proof.len()).into())
+            })?,
+            signed_block_header: signed_block_header.clone(),
+            kzg_commitment_inclusion_proof: FixedVector::new(inclusion_proof)
+                .map_err(invalid_ssz)?,
+        };
+        sidecars.push(sidecar.as_ssz_bytes());
+    }
+    Ok(sidecars)
+}
+
+// Builds the sidecars of the blobs of a block, given the KZG proof of each blob.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn build_blob_sidecars<'env>(
+    env: Env<'env>,
+    signed_block: Binary,
+    blobs: Vec<Binary>,
+    kzg_proofs: Vec<Binary>,
+    config: Atom,
+) -> NifResult<Term<'env>> {
+    let config = config.to_term(env).atom_to_string()?;
+    let sidecars = preset_match!(config.as_str(), build(&signed_block, &blobs, &kzg_proofs))?;
+    let sidecars: Vec<Binary> = sidecars
+        .iter()
+        .map(|sidecar| bytes_to_binary(env, sidecar))
+        .collect();
+    Ok((atoms::ok(), sidecars).encode(env))
+}
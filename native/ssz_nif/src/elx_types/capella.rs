@@ -0,0 +1,148 @@
+//! Capella containers that changed in Deneb.
+
+use rustler::{Binary, NifStruct};
+
+use crate::utils::gen_struct_with_config;
+
+use super::*;
+
+pub(crate) use super::deneb::{
+    AggregateAndProof, Attestation, AttesterSlashing, IndexedAttestation, SignedAggregateAndProof,
+};
+
+gen_struct_with_config!(
+    fork = capella;
+    #[derive(NifStruct)]
+    #[module = "Types.ExecutionPayloadHeader"]
+    pub(crate) struct ExecutionPayloadHeader<'a> {
+        parent_hash: Hash32<'a>,
+        fee_recipient: ExecutionAddress<'a>,
+        state_root: Root<'a>,
+        receipts_root: Root<'a>,
+        logs_bloom: Binary<'a>,
+        prev_randao: Bytes32<'a>,
+        block_number: u64,
+        gas_limit: u64,
+        gas_used: u64,
+        timestamp: u64,
+        extra_data: Binary<'a>,
+        base_fee_per_gas: Uint256<'a>,
+        block_hash: Hash32<'a>,
+        transactions_root: Root<'a>,
+        withdrawals_root: Root<'a>,
+    }
+);
+
+gen_struct_with_config!(
+    fork = capella;
+    #[derive(NifStruct)]
+    #[module = "Types.ExecutionPayload"]
+    pub(crate) struct ExecutionPayload<'a> {
+        parent_hash: Hash32<'a>,
+        fee_recipient: ExecutionAddress<'a>,
+        state_root: Root<'a>,
+        receipts_root: Root<'a>,
+        logs_bloom: Binary<'a>,
+        prev_randao: Bytes32<'a>,
+        block_number: u64,
+        gas_limit: u64,
+        gas_used: u64,
+        timestamp: u64,
+        extra_data: Binary<'a>,
+        base_fee_per_gas: Uint256<'a>,
+        block_hash: Hash32<'a>,
+        transactions: Vec<Transaction<'a>>,
+        withdrawals: Vec<Withdrawal<'a>>,
+    }
+);
+
+gen_struct_with_config!(
+    fork = capella;
+    #[derive(NifStruct)]
+    #[module = "Types.BeaconBlockBody"]
+    pub(crate) struct BeaconBlockBody<'a> {
+        randao_reveal: BLSSignature<'a>,
+        eth1_data: Eth1Data<'a>,
+        graffiti: Bytes32<'a>,
+        proposer_slashings: Vec<ProposerSlashing<'a>>,
+        attester_slashings: Vec<AttesterSlashing<'a>>,
+        attestations: Vec<Attestation<'a>>,
+        deposits: Vec<Deposit<'a>>,
+        voluntary_exits: Vec<SignedVoluntaryExit<'a>>,
+        sync_aggregate: SyncAggregate<'a>,
+        execution_payload: ExecutionPayload<'a>,
+        bls_to_execution_changes: Vec<SignedBLSToExecutionChange<'a>>,
+    }
+);
+
+gen_struct_with_config!(
+    fork = capella;
+    #[derive(NifStruct)]
+    #[module = "Types.BeaconBlock"]
+    pub(crate) struct BeaconBlock<'a> {
+        slot: Slot,
+        proposer_index: ValidatorIndex,
+        parent_root: Root<'a>,
+        state_root: Root<'a>,
+        body: BeaconBlockBody<'a>,
+    }
+);
+
+gen_struct_with_config!(
+    fork = capella;
+    #[derive(NifStruct)]
+    #[module = "Types.SignedBeaconBlock"]
+    pub(crate) struct SignedBeaconBlock<'a> {
+        message: BeaconBlock<'a>,
+        signature: BLSSignature<'a>,
+    }
+);
+
+gen_struct_with_config!(
+    fork = capella;
+    #[derive(NifStruct)]
+    #[module = "Types.BeaconState"]
+    pub(crate) struct BeaconState<'a> {
+        // Versioning
+        genesis_time: u64,
+        genesis_validators_root: Root<'a>,
+        slot: Slot,
+        fork: Fork<'a>,
+        // History
+        latest_block_header: BeaconBlockHeader<'a>,
+        block_roots: Vec<Root<'a>>,
+        state_roots: Vec<Root<'a>>,
+        historical_roots: Vec<Root<'a>>,
+        // Eth1
+        eth1_data: Eth1Data<'a>,
+        eth1_data_votes: Vec<Eth1Data<'a>>,
+        eth1_deposit_index: u64,
+        // Registry
+        validators: Vec<Validator<'a>>,
+        balances: Vec<Gwei>,
+        // Randomness
+        randao_mixes: Vec<Bytes32<'a>>,
+        // Slashings
+        slashings: Vec<Gwei>, // Per-epoch sums of slashed effective balances
+        // Participation
+        previous_epoch_participation: Vec<ParticipationFlags>,
+        current_epoch_participation: Vec<ParticipationFlags>,
+        // Finality
+        justification_bits: Binary<'a>, // Bit set for every recent justified epoch
+        previous_justified_checkpoint: Checkpoint<'a>,
+        current_justified_checkpoint: Checkpoint<'a>,
+        finalized_checkpoint: Checkpoint<'a>,
+        // Inactivity
+        inactivity_scores: Vec<u64>,
+        // Sync
+        current_sync_committee: SyncCommittee<'a>,
+        next_sync_committee: SyncCommittee<'a>,
+        // Execution
+        latest_execution_payload_header: ExecutionPayloadHeader<'a>,
+        // Withdrawals
+        next_withdrawal_index: WithdrawalIndex,
+        next_withdrawal_validator_index: ValidatorIndex,
+        // Deep history valid from Capella onwards
+        historical_summaries: Vec<HistoricalSummary<'a>>,
+    }
+);
@@ -0,0 +1,61 @@
+use rustler::NifStruct;
+
+use crate::utils::gen_struct_with_config;
+
+use super::*;
+
+gen_struct_with_config!(
+    #[derive(NifStruct)]
+    #[module = "Types.LightClientHeader"]
+    pub(crate) struct LightClientHeader<'a> {
+        beacon: BeaconBlockHeader<'a>,
+        execution: ExecutionPayloadHeader<'a>,
+        execution_branch: Vec<Bytes32<'a>>,
+    }
+);
+
+gen_struct_with_config!(
+    #[derive(NifStruct)]
+    #[module = "Types.LightClientBootstrap"]
+    pub(crate) struct LightClientBootstrap<'a> {
+        header: LightClientHeader<'a>,
+        current_sync_committee: SyncCommittee<'a>,
+        current_sync_committee_branch: Vec<Bytes32<'a>>,
+    }
+);
+
+gen_struct_with_config!(
+    #[derive(NifStruct)]
+    #[module = "Types.LightClientUpdate"]
+    pub(crate) struct LightClientUpdate<'a> {
+        attested_header: LightClientHeader<'a>,
+        next_sync_committee: SyncCommittee<'a>,
+        next_sync_committee_branch: Vec<Bytes32<'a>>,
+        finalized_header: LightClientHeader<'a>,
+        finality_branch: Vec<Bytes32<'a>>,
+        sync_aggregate: SyncAggregate<'a>,
+        signature_slot: Slot,
+    }
+);
+
+gen_struct_with_config!(
+    #[derive(NifStruct)]
+    #[module = "Types.LightClientFinalityUpdate"]
+    pub(crate) struct LightClientFinalityUpdate<'a> {
+        attested_header: LightClientHeader<'a>,
+        finalized_header: LightClientHeader<'a>,
+        finality_branch: Vec<Bytes32<'a>>,
+        sync_aggregate: SyncAggregate<'a>,
+        signature_slot: Slot,
+    }
+);
+
+gen_struct_with_config!(
+    #[derive(NifStruct)]
+    #[module = "Types.LightClientOptimisticUpdate"]
+    pub(crate) struct LightClientOptimisticUpdate<'a> {
+        attested_header: LightClientHeader<'a>,
+        sync_aggregate: SyncAggregate<'a>,
+        signature_slot: Slot,
+    }
+);
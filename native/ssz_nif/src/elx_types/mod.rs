@@ -3,15 +3,27 @@
 //! To add a new type, add the struct definition (with [`rustler`] types)
 //! in the corresponding module. You may need to add some [`FromElx`] and
 //! [`FromSsz`] implementations to convert between the types.
+//!
+//! Containers of earlier forks are in their fork's module, mirroring
+//! [`ssz_types`](crate::ssz_types). They map to the same Elixir struct as the
+//! Electra container, with only the fields of their fork.
 
 mod beacon_chain;
+mod light_client;
 mod p2p;
 mod validator;
 
 pub(crate) use beacon_chain::*;
+pub(crate) use light_client::*;
 pub(crate) use p2p::*;
 pub(crate) use validator::*;
 
+pub(crate) mod altair;
+pub(crate) mod bellatrix;
+pub(crate) mod capella;
+pub(crate) mod deneb;
+pub(crate) mod phase0;
+
 use rustler::Binary;
 
 type Bytes4<'a> = Binary<'a>;
@@ -44,6 +56,9 @@ type KZGCommitment<'a> = Bytes48<'a>;
 type KZGProof<'a> = Bytes48<'a>;
 type BlobIndex = u64;
 type Blob<'a> = Binary<'a>;
+type ColumnIndex = u64;
+type RowIndex = u64;
+type Cell<'a> = Binary<'a>;
 
 // This type should be a little-endian encoded uint256.
 type Uint256<'a> = Binary<'a>;
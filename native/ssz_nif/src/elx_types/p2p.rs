@@ -57,3 +57,60 @@ gen_struct!(
         index: BlobIndex,
     }
 );
+
+gen_struct!(
+    #[derive(NifStruct)]
+    #[module = "Types.StatusMessageV2"]
+    pub(crate) struct StatusMessageV2<'a> {
+        fork_digest: ForkDigest<'a>,
+        finalized_root: Root<'a>,
+        finalized_epoch: Epoch,
+        head_root: Root<'a>,
+        head_slot: Slot,
+        earliest_available_slot: Slot,
+    }
+);
+
+gen_struct_with_config!(
+    #[derive(NifStruct)]
+    #[module = "Types.MetadataV3"]
+    pub(crate) struct MetadataV3<'a> {
+        seq_number: u64,
+        attnets: Binary<'a>,
+        syncnets: Binary<'a>,
+        custody_group_count: u64,
+    }
+);
+
+gen_struct_with_config!(
+    #[derive(NifStruct)]
+    #[module = "Types.DataColumnSidecar"]
+    pub(crate) struct DataColumnSidecar<'a> {
+        index: ColumnIndex,
+        column: Vec<Cell<'a>>,
+        kzg_commitments: Vec<KZGCommitment<'a>>,
+        kzg_proofs: Vec<KZGProof<'a>>,
+        signed_block_header: SignedBeaconBlockHeader<'a>,
+        kzg_commitments_inclusion_proof: Vec<Bytes32<'a>>,
+    }
+);
+
+gen_struct_with_config!(
+    #[derive(NifStruct)]
+    #[module = "Types.DataColumnsByRootIdentifier"]
+    pub(crate) struct DataColumnsByRootIdentifier<'a> {
+        block_root: Root<'a>,
+        columns: Vec<ColumnIndex>,
+    }
+);
+
+gen_struct_with_config!(
+    #[derive(NifStruct)]
+    #[module = "Types.MatrixEntry"]
+    pub(crate) struct MatrixEntry<'a> {
+        cell: Cell<'a>,
+        kzg_proof: KZGProof<'a>,
+        column_index: ColumnIndex,
+        row_index: RowIndex,
+    }
+);
@@ -0,0 +1,97 @@
+//! Phase0 containers that changed in Altair.
+
+use rustler::{Binary, NifStruct};
+
+use crate::utils::gen_struct_with_config;
+
+use super::*;
+
+pub(crate) use super::deneb::{
+    AggregateAndProof, Attestation, AttesterSlashing, IndexedAttestation, SignedAggregateAndProof,
+};
+
+gen_struct_with_config!(
+    fork = phase0;
+    #[derive(NifStruct)]
+    #[module = "Types.BeaconBlockBody"]
+    pub(crate) struct BeaconBlockBody<'a> {
+        randao_reveal: BLSSignature<'a>,
+        eth1_data: Eth1Data<'a>,
+        graffiti: Bytes32<'a>,
+        proposer_slashings: Vec<ProposerSlashing<'a>>,
+        attester_slashings: Vec<AttesterSlashing<'a>>,
+        attestations: Vec<Attestation<'a>>,
+        deposits: Vec<Deposit<'a>>,
+        voluntary_exits: Vec<SignedVoluntaryExit<'a>>,
+    }
+);
+
+gen_struct_with_config!(
+    fork = phase0;
+    #[derive(NifStruct)]
+    #[module = "Types.BeaconBlock"]
+    pub(crate) struct BeaconBlock<'a> {
+        slot: Slot,
+        proposer_index: ValidatorIndex,
+        parent_root: Root<'a>,
+        state_root: Root<'a>,
+        body: BeaconBlockBody<'a>,
+    }
+);
+
+gen_struct_with_config!(
+    fork = phase0;
+    #[derive(NifStruct)]
+    #[module = "Types.SignedBeaconBlock"]
+    pub(crate) struct SignedBeaconBlock<'a> {
+        message: BeaconBlock<'a>,
+        signature: BLSSignature<'a>,
+    }
+);
+
+gen_struct_with_config!(
+    fork = phase0;
+    #[derive(NifStruct)]
+    #[module = "Types.BeaconState"]
+    pub(crate) struct BeaconState<'a> {
+        // Versioning
+        genesis_time: u64,
+        genesis_validators_root: Root<'a>,
+        slot: Slot,
+        fork: Fork<'a>,
+        // History
+        latest_block_header: BeaconBlockHeader<'a>,
+        block_roots: Vec<Root<'a>>,
+        state_roots: Vec<Root<'a>>,
+        historical_roots: Vec<Root<'a>>,
+        // Eth1
+        eth1_data: Eth1Data<'a>,
+        eth1_data_votes: Vec<Eth1Data<'a>>,
+        eth1_deposit_index: u64,
+        // Registry
+        validators: Vec<Validator<'a>>,
+        balances: Vec<Gwei>,
+        // Randomness
+        randao_mixes: Vec<Bytes32<'a>>,
+        // Slashings
+        slashings: Vec<Gwei>, // Per-epoch sums of slashed effective balances
+        // Attestations
+        previous_epoch_attestations: Vec<PendingAttestation<'a>>,
+        current_epoch_attestations: Vec<PendingAttestation<'a>>,
+        // Finality
+        justification_bits: Binary<'a>, // Bit set for every recent justified epoch
+        previous_justified_checkpoint: Checkpoint<'a>,
+        current_justified_checkpoint: Checkpoint<'a>,
+        finalized_checkpoint: Checkpoint<'a>,
+    }
+);
+
+gen_struct_with_config!(
+    fork = phase0;
+    #[derive(NifStruct)]
+    #[module = "Types.Metadata"]
+    pub(crate) struct Metadata<'a> {
+        seq_number: u64,
+        attnets: Binary<'a>,
+    }
+);
@@ -4,12 +4,23 @@
 //!  - Add the type to the [`elx_types`] and [`ssz_types`] modules, using the [`gen_struct`](utils::gen_struct) macro
 //!  - Implement the necessary traits ([`FromElx`](utils::from_elx::FromElx) and [`FromSsz`](utils::from_ssz::FromSsz)) for its attributes
 //!  - Add the type to [`to_ssz_rs`] and [`from_ssz_rs`] "match" macros
+//!  - If the type changed in a fork, add its earlier layouts to the fork modules, and
+//!    to the fork's list in [`fork_schema_match`](utils::fork_schema_match)
 
+mod attestation_pool;
+mod blob_sidecar;
 pub(crate) mod elx_types;
+mod light_client;
+mod merkle_proof;
 pub(crate) mod ssz_types;
+mod state_hash_cache;
 pub(crate) mod utils;
+mod versioned_hashes;
 
-use crate::utils::{helpers::bytes_to_binary, schema_match};
+use crate::merkle_proof::{are_independent, encode_roots, multiproof};
+use crate::utils::errors::invalid_path;
+use crate::utils::merkle_node::PathItem;
+use crate::utils::{fork_schema_match, helpers::bytes_to_binary, schema_match};
 use rustler::{Atom, Binary, Encoder, Env, NifResult, Term};
 
 mod atoms {
@@ -20,17 +31,48 @@ mod atoms {
     }
 }
 
-const SCHEMA_PREFIX_SIZE: usize = "Elixir.Types.".len();
+const SCHEMA_PREFIX: &str = "Elixir.Types.";
 
-#[rustler::nif]
-fn to_ssz_rs<'env>(env: Env<'env>, map: Term, schema: Atom, config: Atom) -> NifResult<Term<'env>> {
+/// Returns the names of the schema, without its module prefix, and the config.
+fn schema_and_config(env: Env, schema: Atom, config: Atom) -> NifResult<(String, String)> {
     let schema = schema.to_term(env).atom_to_string()?;
-    let schema = schema
-        .get(SCHEMA_PREFIX_SIZE..)
-        .ok_or(rustler::Error::BadArg)?;
+    let schema = match schema.strip_prefix(SCHEMA_PREFIX) {
+        Some(name) => name.to_owned(),
+        None => schema,
+    };
     let config = config.to_term(env).atom_to_string()?;
+    Ok((schema, config))
+}
+
+/// Like [`schema_and_config`], also returning the name of the fork.
+fn schema_config_and_fork(
+    env: Env,
+    schema: Atom,
+    config: Atom,
+    fork: Atom,
+) -> NifResult<(String, String, String)> {
+    let (schema, config) = schema_and_config(env, schema, config)?;
+    let fork = fork.to_term(env).atom_to_string()?;
+    Ok((schema, config, fork))
+}
+
+#[rustler::nif]
+fn to_ssz_rs<'env>(
+    env: Env<'env>,
+    map: Term,
+    schema: Atom,
+    config: Atom,
+    fork: Atom,
+) -> NifResult<Term<'env>> {
+    let (schema, config, fork) = schema_config_and_fork(env, schema, config, fork)?;
 
-    let serialized = schema_match!(schema, config.as_str(), encode_ssz, (map));
+    let serialized = fork_schema_match!(
+        fork.as_str(),
+        schema.as_str(),
+        config.as_str(),
+        encode_ssz,
+        (map)
+    );
     Ok((atoms::ok(), bytes_to_binary(env, &serialized?)).encode(env))
 }
 
@@ -40,14 +82,17 @@ fn from_ssz_rs<'env>(
     bytes: Binary,
     schema: Atom,
     config: Atom,
+    fork: Atom,
 ) -> NifResult<Term<'env>> {
-    let schema = schema.to_term(env).atom_to_string()?;
-    let schema = schema
-        .get(SCHEMA_PREFIX_SIZE..)
-        .ok_or(rustler::Error::BadArg)?;
-    let config = config.to_term(env).atom_to_string()?;
+    let (schema, config, fork) = schema_config_and_fork(env, schema, config, fork)?;
 
-    let res = schema_match!(schema, config.as_str(), decode_ssz, (&bytes, env))?;
+    let res = fork_schema_match!(
+        fork.as_str(),
+        schema.as_str(),
+        config.as_str(),
+        decode_ssz,
+        (&bytes, env)
+    )?;
     Ok((atoms::ok(), res).encode(env))
 }
 
@@ -57,14 +102,17 @@ fn list_from_ssz_rs<'env>(
     bytes: Binary,
     schema: Atom,
     config: Atom,
+    fork: Atom,
 ) -> NifResult<Term<'env>> {
-    let schema = schema.to_term(env).atom_to_string()?;
-    let schema = schema
-        .get(SCHEMA_PREFIX_SIZE..)
-        .ok_or(rustler::Error::BadArg)?;
-    let config = config.to_term(env).atom_to_string()?;
+    let (schema, config, fork) = schema_config_and_fork(env, schema, config, fork)?;
 
-    let res = schema_match!(schema, config.as_str(), list_decode_ssz, (&bytes, env))?;
+    let res = fork_schema_match!(
+        fork.as_str(),
+        schema.as_str(),
+        config.as_str(),
+        list_decode_ssz,
+        (&bytes, env)
+    )?;
     Ok((atoms::ok(), res).encode(env))
 }
 
@@ -74,17 +122,84 @@ fn hash_tree_root_rs<'env>(
     map: Term,
     schema: Atom,
     config: Atom,
+    fork: Atom,
 ) -> NifResult<Term<'env>> {
-    let schema = schema.to_term(env).atom_to_string()?;
-    let schema = schema
-        .get(SCHEMA_PREFIX_SIZE..)
-        .ok_or(rustler::Error::BadArg)?;
-    let config = config.to_term(env).atom_to_string()?;
+    let (schema, config, fork) = schema_config_and_fork(env, schema, config, fork)?;
 
-    let serialized = schema_match!(schema, config.as_str(), hash_tree_root, (map));
+    let serialized = fork_schema_match!(
+        fork.as_str(),
+        schema.as_str(),
+        config.as_str(),
+        hash_tree_root,
+        (map)
+    );
     Ok((atoms::ok(), bytes_to_binary(env, &serialized?)).encode(env))
 }
 
+// Returns the generalized index of the value at `path`, its chunk and its branch.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn merkle_proof_rs<'env>(
+    env: Env<'env>,
+    map: Term,
+    path: Vec<PathItem>,
+    schema: Atom,
+    config: Atom,
+    fork: Atom,
+) -> NifResult<Term<'env>> {
+    let (schema, config, fork) = schema_config_and_fork(env, schema, config, fork)?;
+
+    let paths = [path];
+    let mut proofs = fork_schema_match!(
+        fork.as_str(),
+        schema.as_str(),
+        config.as_str(),
+        merkle_proofs,
+        (map, &paths[..])
+    )?;
+    let proof = proofs.remove(0);
+    let result = (
+        proof.gindex,
+        bytes_to_binary(env, &proof.leaf),
+        encode_roots(env, &proof.branch),
+    );
+    Ok((atoms::ok(), result).encode(env))
+}
+
+// Returns the generalized index and chunk of the value at each path, and the
+// nodes of their multiproof. No path may repeat or lead inside another.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn merkle_multiproof_rs<'env>(
+    env: Env<'env>,
+    map: Term,
+    paths: Vec<Vec<PathItem>>,
+    schema: Atom,
+    config: Atom,
+    fork: Atom,
+) -> NifResult<Term<'env>> {
+    let (schema, config, fork) = schema_config_and_fork(env, schema, config, fork)?;
+
+    let proofs = fork_schema_match!(
+        fork.as_str(),
+        schema.as_str(),
+        config.as_str(),
+        merkle_proofs,
+        (map, &paths[..])
+    )?;
+    let gindices: Vec<u64> = proofs.iter().map(|proof| proof.gindex).collect();
+    if !are_independent(&gindices) {
+        return Err(invalid_path(
+            "A path repeats or leads inside another one".to_owned(),
+        ));
+    }
+    let leaves: Vec<_> = proofs.iter().map(|proof| proof.leaf).collect();
+    let result = (
+        gindices,
+        encode_roots(env, &leaves),
+        encode_roots(env, &multiproof(&proofs)),
+    );
+    Ok((atoms::ok(), result).encode(env))
+}
+
 #[rustler::nif]
 fn hash_tree_root_list_rs<'env>(
     env: Env<'env>,
@@ -93,14 +208,10 @@ fn hash_tree_root_list_rs<'env>(
     schema: Atom,
     config: Atom,
 ) -> NifResult<Term<'env>> {
-    let schema = schema.to_term(env).atom_to_string()?;
-    let schema = schema
-        .get(SCHEMA_PREFIX_SIZE..)
-        .ok_or(rustler::Error::BadArg)?;
-    let config = config.to_term(env).atom_to_string()?;
+    let (schema, config) = schema_and_config(env, schema, config)?;
 
     let serialized = schema_match!(
-        schema,
+        schema.as_str(),
         config.as_str(),
         hash_list_tree_root,
         (list, max_size)
@@ -116,14 +227,10 @@ fn hash_tree_root_vector_rs<'env>(
     schema: Atom,
     config: Atom,
 ) -> NifResult<Term<'env>> {
-    let schema = schema.to_term(env).atom_to_string()?;
-    let schema = schema
-        .get(SCHEMA_PREFIX_SIZE..)
-        .ok_or(rustler::Error::BadArg)?;
-    let config = config.to_term(env).atom_to_string()?;
+    let (schema, config) = schema_and_config(env, schema, config)?;
 
     let serialized = schema_match!(
-        schema,
+        schema.as_str(),
         config.as_str(),
         hash_vector_tree_root,
         (list, max_size)
@@ -131,6 +238,18 @@ fn hash_tree_root_vector_rs<'env>(
     Ok((atoms::ok(), bytes_to_binary(env, &serialized?)).encode(env))
 }
 
+#[rustler::nif]
+fn fixed_length_rs<'env>(env: Env<'env>, schema: Atom, config: Atom) -> NifResult<Term<'env>> {
+    let (schema, config) = schema_and_config(env, schema, config)?;
+
+    let length = schema_match!(schema.as_str(), config.as_str(), fixed_length, (()))?;
+    Ok((atoms::ok(), length).encode(env))
+}
+
+fn load(env: Env, _load_info: Term) -> bool {
+    attestation_pool::load(env) && state_hash_cache::load(env)
+}
+
 rustler::init!(
     "Elixir.Ssz",
     [
@@ -138,7 +257,27 @@ rustler::init!(
         from_ssz_rs,
         list_from_ssz_rs,
         hash_tree_root_rs,
+        merkle_proof_rs,
+        merkle_multiproof_rs,
         hash_tree_root_list_rs,
         hash_tree_root_vector_rs,
-    ]
+        fixed_length_rs,
+        attestation_pool::attestation_pool_new,
+        attestation_pool::attestation_pool_insert,
+        attestation_pool::attestation_pool_insert_single,
+        attestation_pool::attestation_pool_get_aggregate,
+        attestation_pool::attestation_pool_prune,
+        blob_sidecar::decode_blob_sidecar_rs,
+        blob_sidecar::build_blob_sidecars,
+        light_client::build_light_client_bootstrap,
+        light_client::build_light_client_update,
+        merkle_proof::verify_merkle_proof,
+        merkle_proof::verify_merkle_multiproof,
+        state_hash_cache::state_hash_cache_new,
+        state_hash_cache::state_hash_cache_root,
+        versioned_hashes::kzg_commitments_to_versioned_hashes,
+        versioned_hashes::blob_versioned_hashes,
+        versioned_hashes::verify_blob_versioned_hashes,
+    ],
+    load = load
 );
@@ -0,0 +1,345 @@
+//! Building of the light client bootstraps and updates served to light clients,
+//! with the Merkle proofs of their sync committees, finalized checkpoint and
+//! execution payload. Only the Electra layouts are supported.
+
+use rustler::{Atom, Binary, Encoder, Env, NifResult, Term};
+use ssz::{Decode, Encode};
+use ssz_types::{typenum::Unsigned, FixedVector};
+use tree_hash::TreeHash;
+
+use crate::blob_sidecar::{body_field_roots, BODY_DEPTH};
+use crate::merkle_proof::merkle_branch;
+use crate::ssz_types::config::Config;
+use crate::ssz_types::{
+    BeaconBlockHeader, BeaconState, ExecutionPayloadHeader, LightClientBootstrap,
+    LightClientHeader, LightClientUpdate, Root, SignedBeaconBlock, SyncCommittee, Uint256,
+};
+use crate::utils::errors::{invalid_ssz, invalid_value};
+use crate::utils::helpers::bytes_to_binary;
+use crate::utils::preset_match;
+
+mod atoms {
+    use rustler::atoms;
+
+    atoms! {
+        ok,
+    }
+}
+
+/// Depth of the tree of `BeaconState` fields.
+const STATE_DEPTH: usize = 6;
+/// Positions of the proven fields in `BeaconState`.
+const FINALIZED_CHECKPOINT_INDEX: usize = 20;
+const CURRENT_SYNC_COMMITTEE_INDEX: usize = 22;
+const NEXT_SYNC_COMMITTEE_INDEX: usize = 23;
+/// Position of `execution_payload` in `BeaconBlockBody`.
+const EXECUTION_PAYLOAD_INDEX: usize = 9;
+const MIN_SYNC_COMMITTEE_PARTICIPANTS: usize = 1;
+const GENESIS_SLOT: u64 = 0;
+
+fn state_field_roots<C: Config>(state: &BeaconState<C>) -> Vec<Root> {
+    vec![
+        state.genesis_time.tree_hash_root().0,
+        state.genesis_validators_root.tree_hash_root().0,
+        state.slot.tree_hash_root().0,
+        state.fork.tree_hash_root().0,
+        state.latest_block_header.tree_hash_root().0,
+        state.block_roots.tree_hash_root().0,
+        state.state_roots.tree_hash_root().0,
+        state.historical_roots.tree_hash_root().0,
+        state.eth1_data.tree_hash_root().0,
+        state.eth1_data_votes.tree_hash_root().0,
+        state.eth1_deposit_index.tree_hash_root().0,
+        state.validators.tree_hash_root().0,
+        state.balances.tree_hash_root().0,
+        state.randao_mixes.tree_hash_root().0,
+        state.slashings.tree_hash_root().0,
+        state.previous_epoch_participation.tree_hash_root().0,
+        state.current_epoch_participation.tree_hash_root().0,
+        state.justification_bits.tree_hash_root().0,
+        state.previous_justified_checkpoint.tree_hash_root().0,
+        state.current_justified_checkpoint.tree_hash_root().0,
+        state.finalized_checkpoint.tree_hash_root().0,
+        state.inactivity_scores.tree_hash_root().0,
+        state.current_sync_committee.tree_hash_root().0,
+        state.next_sync_committee.tree_hash_root().0,
+        state.latest_execution_payload_header.tree_hash_root().0,
+        state.next_withdrawal_index.tree_hash_root().0,
+        state.next_withdrawal_validator_index.tree_hash_root().0,
+        state.historical_summaries.tree_hash_root().0,
+        state.deposit_requests_start_index.tree_hash_root().0,
+        state.deposit_balance_to_consume.tree_hash_root().0,
+        state.exit_balance_to_consume.tree_hash_root().0,
+        state.earliest_exit_epoch.tree_hash_root().0,
+        state.consolidation_balance_to_consume.tree_hash_root().0,
+        state.earliest_consolidation_epoch.tree_hash_root().0,
+        state.pending_deposits.tree_hash_root().0,
+        state.pending_partial_withdrawals.tree_hash_root().0,
+        state.pending_consolidations.tree_hash_root().0,
+    ]
+}
+
+/// Root of a tree whose leaves are `leaves`, padded with zeros to a power of two.
+fn merkle_root(leaves: &[Root]) -> Root {
+    tree_hash::merkle_root(&leaves.concat(), 0).0
+}
+
+fn to_branch<N: Unsigned>(branch: Vec<Root>) -> NifResult<FixedVector<Root, N>> {
+    FixedVector::new(branch).map_err(invalid_ssz)
+}
+
+fn invalid(detail: String) -> rustler::Error {
+    invalid_value(detail.into())
+}
+
+fn sync_committee_period<C: Config>(slot: u64) -> u64 {
+    slot / C::SlotsPerEpoch::to_u64() / C::EpochsPerSyncCommitteePeriod::to_u64()
+}
+
+fn decode<T: Decode>(bytes: &[u8]) -> NifResult<T> {
+    T::from_ssz_bytes(bytes).map_err(invalid_ssz)
+}
+
+/// Checks that `state`, with the given field roots, is the post-state of `block`.
+fn check_post_state<C: Config>(
+    state: &BeaconState<C>,
+    state_leaves: &[Root],
+    block: &SignedBeaconBlock<C>,
+) -> NifResult<()> {
+    let mut header = state.latest_block_header.clone();
+    header.state_root = merkle_root(state_leaves);
+    if state.slot != header.slot || header.tree_hash_root() != block.message.tree_hash_root() {
+        return Err(invalid(format!(
+            "The state at slot {} isn't the post-state of the block at slot {}",
+            state.slot, block.message.slot
+        )));
+    }
+    Ok(())
+}
+
+/// This is `block_to_light_client_header` from the spec.
+fn block_to_header<C: Config>(
+    signed_block: SignedBeaconBlock<C>,
+) -> NifResult<LightClientHeader<C>> {
+    let block = signed_block.message;
+    let body_leaves = body_field_roots(&block.body);
+    let body_root = merkle_root(&body_leaves);
+    let execution_branch = merkle_branch(body_leaves, EXECUTION_PAYLOAD_INDEX, BODY_DEPTH);
+    let payload = block.body.execution_payload;
+
+    Ok(LightClientHeader {
+        beacon: BeaconBlockHeader {
+            slot: block.slot,
+            proposer_index: block.proposer_index,
+            parent_root: block.parent_root,
+            state_root: block.state_root,
+            body_root,
+        },
+        execution: ExecutionPayloadHeader {
+            parent_hash: payload.parent_hash,
+            fee_recipient: payload.fee_recipient,
+            state_root: payload.state_root,
+            receipts_root: payload.receipts_root,
+            logs_bloom: payload.logs_bloom,
+            prev_randao: payload.prev_randao,
+            block_number: payload.block_number,
+            gas_limit: payload.gas_limit,
+            gas_used: payload.gas_used,
+            timestamp: payload.timestamp,
+            extra_data: payload.extra_data,
+            base_fee_per_gas: payload.base_fee_per_gas,
+            block_hash: payload.block_hash,
+            transactions_root: payload.transactions.tree_hash_root().0,
+            withdrawals_root: payload.withdrawals.tree_hash_root().0,
+            blob_gas_used: payload.blob_gas_used,
+            excess_blob_gas: payload.excess_blob_gas,
+        },
+        execution_branch: to_branch(execution_branch)?,
+    })
+}
+
+fn empty_header<C: Config>() -> LightClientHeader<C> {
+    LightClientHeader {
+        beacon: BeaconBlockHeader {
+            slot: 0,
+            proposer_index: 0,
+            parent_root: Root::default(),
+            state_root: Root::default(),
+            body_root: Root::default(),
+        },
+        execution: ExecutionPayloadHeader {
+            parent_hash: Root::default(),
+            fee_recipient: FixedVector::default(),
+            state_root: Root::default(),
+            receipts_root: Root::default(),
+            logs_bloom: FixedVector::default(),
+            prev_randao: Root::default(),
+            block_number: 0,
+            gas_limit: 0,
+            gas_used: 0,
+            timestamp: 0,
+            extra_data: Default::default(),
+            base_fee_per_gas: Uint256([0; 32]),
+            block_hash: Root::default(),
+            transactions_root: Root::default(),
+            withdrawals_root: Root::default(),
+            blob_gas_used: 0,
+            excess_blob_gas: 0,
+        },
+        execution_branch: FixedVector::default(),
+    }
+}
+
+fn empty_sync_committee<C: Config>() -> SyncCommittee<C> {
+    SyncCommittee {
+        pubkeys: FixedVector::default(),
+        aggregate_pubkey: FixedVector::default(),
+    }
+}
+
+fn build_bootstrap<C: Config>(state: &[u8], signed_block: &[u8]) -> NifResult<Vec<u8>> {
+    let state = decode::<BeaconState<C>>(state)?;
+    let signed_block = decode::<SignedBeaconBlock<C>>(signed_block)?;
+    let state_leaves = state_field_roots(&state);
+    check_post_state(&state, &state_leaves, &signed_block)?;
+
+    let branch = merkle_branch(state_leaves, CURRENT_SYNC_COMMITTEE_INDEX, STATE_DEPTH);
+    let bootstrap = LightClientBootstrap {
+        header: block_to_header(signed_block)?,
+        current_sync_committee: state.current_sync_committee,
+        current_sync_committee_branch: to_branch(branch)?,
+    };
+    Ok(bootstrap.as_ssz_bytes())
+}
+
+/// This is `create_light_client_update` from the spec.
+fn build_update<C: Config>(
+    state: &[u8],
+    signed_block: &[u8],
+    attested_state: &[u8],
+    attested_block: &[u8],
+    finalized_block: Option<&[u8]>,
+) -> NifResult<Vec<u8>> {
+    let state = decode::<BeaconState<C>>(state)?;
+    let signed_block = decode::<SignedBeaconBlock<C>>(signed_block)?;
+    let attested_state = decode::<BeaconState<C>>(attested_state)?;
+    let attested_block = decode::<SignedBeaconBlock<C>>(attested_block)?;
+    let finalized_block = finalized_block
+        .map(decode::<SignedBeaconBlock<C>>)
+        .transpose()?;
+
+    let participants = signed_block
+        .message
+        .body
+        .sync_aggregate
+        .sync_committee_bits
+        .num_set_bits();
+    if participants < MIN_SYNC_COMMITTEE_PARTICIPANTS {
+        return Err(invalid(format!(
+            "Only {participants} sync committee participants, the minimum is {MIN_SYNC_COMMITTEE_PARTICIPANTS}"
+        )));
+    }
+    let state_leaves = state_field_roots(&attested_state);
+    check_post_state(&state, &state_field_roots(&state), &signed_block)?;
+    check_post_state(&attested_state, &state_leaves, &attested_block)?;
+    let attested_root = attested_block.message.tree_hash_root().0;
+    if attested_root != signed_block.message.parent_root {
+        return Err(invalid(
+            "The attested block isn't the parent of the block".to_owned(),
+        ));
+    }
+
+    let signature_period = sync_committee_period::<C>(signed_block.message.slot);
+    let attested_period = sync_committee_period::<C>(attested_block.message.slot);
+    let attested_header = block_to_header(attested_block)?;
+
+    // The next sync committee is only useful if the update is signed by the current one
+    let (next_sync_committee, next_sync_committee_branch) = if attested_period == signature_period {
+        let branch = merkle_branch(state_leaves.clone(), NEXT_SYNC_COMMITTEE_INDEX, STATE_DEPTH);
+        (attested_state.next_sync_committee, to_branch(branch)?)
+    } else {
+        (empty_sync_committee(), FixedVector::default())
+    };
+
+    let finalized_root = attested_state.finalized_checkpoint.root;
+    let (finalized_header, finality_branch) = match finalized_block {
+        Some(finalized_block) => {
+            let finalized_header = if finalized_block.message.slot != GENESIS_SLOT {
+                let header = block_to_header(finalized_block)?;
+                if header.beacon.tree_hash_root().0 != finalized_root {
+                    return Err(invalid(
+                        "The finalized block isn't the finalized checkpoint of the attested state"
+                            .to_owned(),
+                    ));
+                }
+                header
+            } else if finalized_root != Root::default() {
+                return Err(invalid(
+                    "The finalized block is at genesis, but the checkpoint isn't empty".to_owned(),
+                ));
+            } else {
+                empty_header()
+            };
+            // The root is the second field of the checkpoint
+            let mut branch = vec![attested_state.finalized_checkpoint.epoch.tree_hash_root().0];
+            branch.extend(merkle_branch(
+                state_leaves,
+                FINALIZED_CHECKPOINT_INDEX,
+                STATE_DEPTH,
+            ));
+            (finalized_header, to_branch(branch)?)
+        }
+        None => (empty_header(), FixedVector::default()),
+    };
+
+    let signature_slot = signed_block.message.slot;
+    let update = LightClientUpdate {
+        attested_header,
+        next_sync_committee,
+        next_sync_committee_branch,
+        finalized_header,
+        finality_branch,
+        sync_aggregate: signed_block.message.body.sync_aggregate,
+        signature_slot,
+    };
+    Ok(update.as_ssz_bytes())
+}
+
+// Builds the bootstrap of a block from its post-state.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn build_light_client_bootstrap<'env>(
+    env: Env<'env>,
+    state: Binary,
+    signed_block: Binary,
+    config: Atom,
+) -> NifResult<Term<'env>> {
+    let config = config.to_term(env).atom_to_string()?;
+    let bootstrap = preset_match!(config.as_str(), build_bootstrap(&state, &signed_block))?;
+    Ok((atoms::ok(), bytes_to_binary(env, &bootstrap)).encode(env))
+}
+
+// Builds the update signed by the sync aggregate of `signed_block`, attesting
+// to its parent. `finalized_block` is `nil` if the finalized block is unknown.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn build_light_client_update<'env>(
+    env: Env<'env>,
+    state: Binary,
+    signed_block: Binary,
+    attested_state: Binary,
+    attested_block: Binary,
+    finalized_block: Option<Binary>,
+    config: Atom,
+) -> NifResult<Term<'env>> {
+    let config = config.to_term(env).atom_to_string()?;
+    let update = preset_match!(
+        config.as_str(),
+        build_update(
+            &state,
+            &signed_block,
+            &attested_state,
+            &attested_block,
+            finalized_block.as_deref()
+        )
+    )?;
+    Ok((atoms::ok(), bytes_to_binary(env, &update)).encode(env))
+}
@@ -0,0 +1,361 @@
+//! Merkle proofs of the values inside SSZ containers, addressed by their
+//! generalized index, and verification of single proofs and multiproofs. See
+//! `ssz/merkle-proofs.md` in the consensus specs.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use ethereum_hashing::hash32_concat;
+use rustler::{Binary, Encoder, Env, NifResult, Term};
+use tree_hash::Hash256;
+
+use crate::ssz_types::Root;
+use crate::utils::errors::{invalid_path, invalid_value};
+use crate::utils::from_elx::FromElx;
+use crate::utils::helpers::bytes_to_binary;
+use crate::utils::merkle_node::{MerkleNode, PathItem};
+
+mod atoms {
+    use rustler::atoms;
+
+    atoms! {
+        ok,
+    }
+}
+
+/// The proof of a single value: its generalized index, the chunk with the value,
+/// and the branch from it to the root.
+pub(crate) struct Proof {
+    pub(crate) gindex: u64,
+    pub(crate) leaf: Root,
+    pub(crate) branch: Vec<Root>,
+}
+
+/// Returns the sibling of each node in the path from the leaf at `index` to the
+/// root of a tree of the given depth, with the leaves missing from `leaves`
+/// being zero.
+pub(crate) fn merkle_branch(leaves: Vec<Root>, mut index: usize, depth: usize) -> Vec<Root> {
+    let mut branch = Vec::with_capacity(depth);
+    let mut layer = leaves;
+    let mut zero_hash = [0; 32];
+    for _ in 0..depth {
+        branch.push(layer.get(index ^ 1).copied().unwrap_or(zero_hash));
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash32_concat(&pair[0], pair.get(1).unwrap_or(&zero_hash)))
+            .collect();
+        zero_hash = hash32_concat(&zero_hash, &zero_hash);
+        index /= 2;
+    }
+    branch
+}
+
+/// Returns the branch of each of `leaves` in a tree of the given depth, as
+/// [`merkle_branch`] does, but hashing each layer of the tree only once.
+pub(crate) fn merkle_branches(leaves: Vec<Root>, depth: usize) -> Vec<Vec<Root>> {
+    let mut branches = vec![Vec::with_capacity(depth); leaves.len()];
+    let mut layer = leaves;
+    let mut zero_hash = [0; 32];
+    for level in 0..depth {
+        for (index, branch) in branches.iter_mut().enumerate() {
+            let sibling = (index >> level) ^ 1;
+            branch.push(layer.get(sibling).copied().unwrap_or(zero_hash));
+        }
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash32_concat(&pair[0], pair.get(1).unwrap_or(&zero_hash)))
+            .collect();
+        zero_hash = hash32_concat(&zero_hash, &zero_hash);
+    }
+    branches
+}
+
+pub(crate) fn is_valid_merkle_branch(leaf: Root, branch: &[Root], index: u64, root: &Root) -> bool {
+    let value = branch.iter().enumerate().fold(leaf, |value, (i, node)| {
+        if (index >> i) & 1 == 1 {
+            hash32_concat(node, &value)
+        } else {
+            hash32_concat(&value, node)
+        }
+    });
+    &value == root
+}
+
+fn length_chunk(length: usize) -> Root {
+    let mut chunk = [0; 32];
+    chunk[..8].copy_from_slice(&(length as u64).to_le_bytes());
+    chunk
+}
+
+/// The tree of a node, hashed once for all the proofs that go through it.
+struct NodeTree {
+    /// Layers of the tree without the zero padding, from the chunks to the root.
+    layers: Vec<Vec<Root>>,
+    /// Root of an all-zero subtree at each level.
+    zero_hashes: Vec<Root>,
+    length: Option<usize>,
+}
+
+impl NodeTree {
+    fn new(node: &dyn MerkleNode) -> Self {
+        let mut layers = vec![node.chunks()];
+        let mut zero_hashes = vec![[0; 32]];
+        for level in 0..node.depth() {
+            let zero_hash = zero_hashes[level];
+            let layer = layers[level]
+                .chunks(2)
+                .map(|pair| hash32_concat(&pair[0], pair.get(1).unwrap_or(&zero_hash)))
+                .collect();
+            layers.push(layer);
+            zero_hashes.push(hash32_concat(&zero_hash, &zero_hash));
+        }
+        Self {
+            layers,
+            zero_hashes,
+            length: node.length(),
+        }
+    }
+
+    fn depth(&self) -> usize {
+        self.layers.len() - 1
+    }
+
+    /// Root of the tree, without the length mixed in.
+    fn data_root(&self) -> Root {
+        let depth = self.depth();
+        self.layers[depth]
+            .first()
+            .copied()
+            .unwrap_or(self.zero_hashes[depth])
+    }
+
+    fn root(&self) -> Root {
+        match self.length {
+            Some(length) => tree_hash::mix_in_length(&Hash256::from(self.data_root()), length).0,
+            None => self.data_root(),
+        }
+    }
+
+    /// Siblings of the nodes in the path from the chunk at `position` to the
+    /// root, deepest first.
+    fn branch(&self, position: usize) -> Vec<Root> {
+        (0..self.depth())
+            .map(|level| {
+                self.layers[level]
+                    .get((position >> level) ^ 1)
+                    .copied()
+                    .unwrap_or(self.zero_hashes[level])
+            })
+            .collect()
+    }
+}
+
+/// Generalized index of the node at `position` in the subtree of the given depth
+/// rooted at `gindex`.
+fn descend(gindex: u64, depth: usize, position: usize) -> NifResult<u64> {
+    if (gindex.leading_zeros() as usize) < depth {
+        return Err(invalid_path(
+            "The generalized index doesn't fit in 64 bits".to_owned(),
+        ));
+    }
+    Ok((gindex << depth) | position as u64)
+}
+
+/// Returns the proof of the value at each of `paths` inside `value`. Basic values
+/// packed with others in a chunk are proven through the chunk. The tree of each
+/// node is hashed once, however many of the paths go through it.
+pub(crate) fn prove(value: &dyn MerkleNode, paths: &[Vec<PathItem>]) -> NifResult<Vec<Proof>> {
+    // Nodes are identified by the path that leads to them
+    let mut trees = HashMap::new();
+    paths
+        .iter()
+        .map(|path| prove_path(value, path, &mut trees))
+        .collect()
+}
+
+fn prove_path<'p>(
+    value: &dyn MerkleNode,
+    path: &'p [PathItem],
+    trees: &mut HashMap<&'p [PathItem], NodeTree>,
+) -> NifResult<Proof> {
+    let mut proof = Proof {
+        gindex: 1,
+        leaf: [0; 32],
+        branch: vec![],
+    };
+    if path.is_empty() {
+        proof.leaf = trees
+            .entry(path)
+            .or_insert_with(|| NodeTree::new(value))
+            .root();
+        return Ok(proof);
+    }
+
+    let mut node = Some(value);
+    for (depth_in_path, item) in path.iter().enumerate() {
+        let current =
+            node.ok_or_else(|| invalid_path("The path goes past a basic value".to_owned()))?;
+        let tree = trees
+            .entry(&path[..depth_in_path])
+            .or_insert_with(|| NodeTree::new(current));
+
+        // Siblings from the new leaf up to the current node, deepest first
+        let mut levels = if let PathItem::Length = item {
+            let length = tree
+                .length
+                .ok_or_else(|| invalid_path("Only lists have a length".to_owned()))?;
+            proof.gindex = descend(proof.gindex, 1, 1)?;
+            proof.leaf = length_chunk(length);
+            node = None;
+            vec![tree.data_root()]
+        } else {
+            let child = current.child(item).map_err(invalid_path)?;
+            proof.leaf = tree.layers[0][child.position];
+            node = child.node;
+            let mut levels = tree.branch(child.position);
+            if let Some(length) = tree.length {
+                // The elements of a list are under the left child of its root
+                proof.gindex = descend(proof.gindex, 1, 0)?;
+                levels.push(length_chunk(length));
+            }
+            proof.gindex = descend(proof.gindex, tree.depth(), child.position)?;
+            levels
+        };
+        levels.append(&mut proof.branch);
+        proof.branch = levels;
+    }
+    Ok(proof)
+}
+
+/// Whether none of `indices` repeats or is an ancestor of another. Multiproofs
+/// are only defined for such sets of nodes.
+pub(crate) fn are_independent(indices: &[u64]) -> bool {
+    let unique: BTreeSet<u64> = indices.iter().copied().collect();
+    unique.len() == indices.len()
+        && indices.iter().all(|&index| {
+            let mut ancestor = index / 2;
+            while ancestor >= 1 {
+                if unique.contains(&ancestor) {
+                    return false;
+                }
+                ancestor /= 2;
+            }
+            true
+        })
+}
+
+/// This is `get_helper_indices` from the spec, in decreasing order.
+fn helper_indices(indices: &[u64]) -> Vec<u64> {
+    let mut helpers = BTreeSet::new();
+    let mut paths = BTreeSet::new();
+    for &index in indices {
+        let mut gindex = index;
+        while gindex > 1 {
+            helpers.insert(gindex ^ 1);
+            paths.insert(gindex);
+            gindex /= 2;
+        }
+    }
+    helpers.difference(&paths).rev().copied().collect()
+}
+
+/// Returns the nodes of the multiproof of the values of `proofs`, which are the
+/// ones at the helper indices of their generalized indices.
+pub(crate) fn multiproof(proofs: &[Proof]) -> Vec<Root> {
+    let mut nodes = BTreeMap::new();
+    for proof in proofs {
+        let mut gindex = proof.gindex;
+        for node in &proof.branch {
+            nodes.insert(gindex ^ 1, *node);
+            gindex /= 2;
+        }
+    }
+    let indices: Vec<u64> = proofs.iter().map(|proof| proof.gindex).collect();
+    helper_indices(&indices)
+        .iter()
+        .map(|gindex| nodes[gindex])
+        .collect()
+}
+
+/// This is `calculate_multi_merkle_root` from the spec. Returns `None` if the
+/// lengths of the leaves, proof and indices don't match, or if the indices
+/// aren't independent.
+fn multi_merkle_root(leaves: &[Root], proof: &[Root], indices: &[u64]) -> Option<Root> {
+    if leaves.len() != indices.len() || indices.contains(&0) || !are_independent(indices) {
+        return None;
+    }
+    let helpers = helper_indices(indices);
+    if proof.len() != helpers.len() {
+        return None;
+    }
+
+    let mut objects: BTreeMap<u64, Root> = indices
+        .iter()
+        .copied()
+        .zip(leaves.iter().copied())
+        .chain(helpers.into_iter().zip(proof.iter().copied()))
+        .collect();
+    let mut keys: Vec<u64> = objects.keys().rev().copied().collect();
+    let mut position = 0;
+    while position < keys.len() {
+        let gindex = keys[position];
+        if gindex > 1 && objects.contains_key(&(gindex ^ 1)) && !objects.contains_key(&(gindex / 2))
+        {
+            let parent = hash32_concat(&objects[&(gindex & !1)], &objects[&(gindex | 1)]);
+            objects.insert(gindex / 2, parent);
+            keys.push(gindex / 2);
+        }
+        position += 1;
+    }
+    objects.get(&1).copied()
+}
+
+pub(crate) fn encode_roots<'env>(env: Env<'env>, roots: &[Root]) -> Vec<Binary<'env>> {
+    roots
+        .iter()
+        .map(|root| bytes_to_binary(env, root))
+        .collect()
+}
+
+fn decode_root(root: Binary) -> NifResult<Root> {
+    FromElx::from(root).map_err(invalid_value)
+}
+
+fn decode_roots(roots: Vec<Binary>) -> NifResult<Vec<Root>> {
+    roots.into_iter().map(decode_root).collect()
+}
+
+#[rustler::nif]
+fn verify_merkle_proof<'env>(
+    env: Env<'env>,
+    leaf: Binary,
+    branch: Vec<Binary>,
+    gindex: u64,
+    root: Binary,
+) -> NifResult<Term<'env>> {
+    let (leaf, branch, root) = (
+        decode_root(leaf)?,
+        decode_roots(branch)?,
+        decode_root(root)?,
+    );
+    let valid = gindex.checked_ilog2().is_some_and(|depth| {
+        branch.len() == depth as usize && is_valid_merkle_branch(leaf, &branch, gindex, &root)
+    });
+    Ok((atoms::ok(), valid).encode(env))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_merkle_multiproof<'env>(
+    env: Env<'env>,
+    leaves: Vec<Binary>,
+    proof: Vec<Binary>,
+    gindices: Vec<u64>,
+    root: Binary,
+) -> NifResult<Term<'env>> {
+    let (leaves, proof, root) = (
+        decode_roots(leaves)?,
+        decode_roots(proof)?,
+        decode_root(root)?,
+    );
+    let valid = multi_merkle_root(&leaves, &proof, &gindices) == Some(root);
+    Ok((atoms::ok(), valid).encode(env))
+}
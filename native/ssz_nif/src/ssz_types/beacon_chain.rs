@@ -17,7 +17,7 @@ pub(crate) struct ForkData {
     pub(crate) genesis_validators_root: Root,
 }
 
-#[derive(Encode, Decode, TreeHash)]
+#[derive(Clone, Encode, Decode, TreeHash)]
 pub(crate) struct Checkpoint {
     pub(crate) epoch: Epoch,
     pub(crate) root: Root,
@@ -35,7 +35,7 @@ pub(crate) struct Validator {
     pub(crate) withdrawable_epoch: Epoch,
 }
 
-#[derive(Encode, Decode, TreeHash)]
+#[derive(Clone, Encode, Decode, TreeHash)]
 pub(crate) struct AttestationData {
     pub(crate) slot: Slot,
     pub(crate) index: CommitteeIndex,
@@ -122,7 +122,7 @@ pub(crate) struct BeaconBlock<C: Config> {
     pub(crate) body: BeaconBlockBody<C>,
 }
 
-#[derive(Encode, Decode, TreeHash)]
+#[derive(Clone, Encode, Decode, TreeHash)]
 pub(crate) struct BeaconBlockHeader {
     pub(crate) slot: Slot,
     pub(crate) proposer_index: ValidatorIndex,
@@ -137,7 +137,7 @@ pub(crate) struct SignedBeaconBlock<C: Config> {
     pub(crate) signature: BLSSignature,
 }
 
-#[derive(Encode, Decode, TreeHash)]
+#[derive(Clone, Encode, Decode, TreeHash)]
 pub(crate) struct SignedBeaconBlockHeader {
     pub(crate) message: BeaconBlockHeader,
     pub(crate) signature: BLSSignature,
@@ -0,0 +1,127 @@
+//! Capella containers that changed in Deneb, which added blob gas to execution
+//! payloads and KZG commitments to blocks.
+
+use super::{config::Config, *};
+use ssz_derive::{Decode, Encode};
+use ssz_types::BitVector;
+use tree_hash_derive::TreeHash;
+
+pub(crate) use super::deneb::{
+    AggregateAndProof, Attestation, AttesterSlashing, IndexedAttestation, SignedAggregateAndProof,
+};
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct ExecutionPayloadHeader<C: Config> {
+    pub(crate) parent_hash: Hash32,
+    pub(crate) fee_recipient: ExecutionAddress,
+    pub(crate) state_root: Root,
+    pub(crate) receipts_root: Root,
+    pub(crate) logs_bloom: FixedVector<u8, C::BytesPerLogsBloom>,
+    pub(crate) prev_randao: Bytes32,
+    pub(crate) block_number: u64,
+    pub(crate) gas_limit: u64,
+    pub(crate) gas_used: u64,
+    pub(crate) timestamp: u64,
+    pub(crate) extra_data: VariableList<u8, C::MaxExtraDataBytes>,
+    pub(crate) base_fee_per_gas: Uint256,
+    pub(crate) block_hash: Hash32,
+    pub(crate) transactions_root: Root,
+    pub(crate) withdrawals_root: Root,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct ExecutionPayload<C: Config> {
+    pub(crate) parent_hash: Hash32,
+    pub(crate) fee_recipient: ExecutionAddress,
+    pub(crate) state_root: Root,
+    pub(crate) receipts_root: Root,
+    pub(crate) logs_bloom: FixedVector<u8, C::BytesPerLogsBloom>,
+    pub(crate) prev_randao: Bytes32,
+    pub(crate) block_number: u64,
+    pub(crate) gas_limit: u64,
+    pub(crate) gas_used: u64,
+    pub(crate) timestamp: u64,
+    pub(crate) extra_data: VariableList<u8, C::MaxExtraDataBytes>,
+    pub(crate) base_fee_per_gas: Uint256,
+    pub(crate) block_hash: Hash32,
+    pub(crate) transactions: VariableList<Transaction, C::MaxTransactionsPerPayload>,
+    pub(crate) withdrawals: VariableList<Withdrawal, C::MaxWithdrawalsPerPayload>,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct BeaconBlockBody<C: Config> {
+    pub(crate) randao_reveal: BLSSignature,
+    pub(crate) eth1_data: Eth1Data,
+    pub(crate) graffiti: Bytes32,
+    pub(crate) proposer_slashings: VariableList<ProposerSlashing, C::MaxProposerSlashings>,
+    pub(crate) attester_slashings: VariableList<AttesterSlashing<C>, C::MaxAttesterSlashings>,
+    pub(crate) attestations: VariableList<Attestation<C>, C::MaxAttestations>,
+    pub(crate) deposits: VariableList<Deposit, C::MaxDeposits>,
+    pub(crate) voluntary_exits: VariableList<SignedVoluntaryExit, C::MaxVoluntaryExits>,
+    pub(crate) sync_aggregate: SyncAggregate<C>,
+    pub(crate) execution_payload: ExecutionPayload<C>,
+    pub(crate) bls_to_execution_changes:
+        VariableList<SignedBLSToExecutionChange, C::MaxBlsToExecutionChanges>,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct BeaconBlock<C: Config> {
+    pub(crate) slot: Slot,
+    pub(crate) proposer_index: ValidatorIndex,
+    pub(crate) parent_root: Root,
+    pub(crate) state_root: Root,
+    pub(crate) body: BeaconBlockBody<C>,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct SignedBeaconBlock<C: Config> {
+    pub(crate) message: BeaconBlock<C>,
+    pub(crate) signature: BLSSignature,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct BeaconState<C: Config> {
+    // Versioning
+    pub(crate) genesis_time: u64,
+    pub(crate) genesis_validators_root: Root,
+    pub(crate) slot: Slot,
+    pub(crate) fork: Fork,
+    // History
+    pub(crate) latest_block_header: BeaconBlockHeader,
+    pub(crate) block_roots: FixedVector<Root, C::SlotsPerHistoricalRoot>,
+    pub(crate) state_roots: FixedVector<Root, C::SlotsPerHistoricalRoot>,
+    pub(crate) historical_roots: VariableList<Root, C::HistoricalRootsLimit>,
+    // Eth1
+    pub(crate) eth1_data: Eth1Data,
+    pub(crate) eth1_data_votes: VariableList<Eth1Data, C::SlotsPerEth1VotingPeriod>,
+    pub(crate) eth1_deposit_index: u64,
+    // Registry
+    pub(crate) validators: VariableList<Validator, C::ValidatorRegistryLimit>,
+    pub(crate) balances: VariableList<Gwei, C::ValidatorRegistryLimit>,
+    // Randomness
+    pub(crate) randao_mixes: FixedVector<Bytes32, C::EpochsPerHistoricalVector>,
+    // Slashings
+    pub(crate) slashings: FixedVector<Gwei, C::EpochsPerSlashingsVector>,
+    // Participation
+    pub(crate) previous_epoch_participation:
+        VariableList<ParticipationFlags, C::ValidatorRegistryLimit>,
+    pub(crate) current_epoch_participation:
+        VariableList<ParticipationFlags, C::ValidatorRegistryLimit>,
+    // Finality
+    pub(crate) justification_bits: BitVector<C::JustificationBitsLength>,
+    pub(crate) previous_justified_checkpoint: Checkpoint,
+    pub(crate) current_justified_checkpoint: Checkpoint,
+    pub(crate) finalized_checkpoint: Checkpoint,
+    // Inactivity
+    pub(crate) inactivity_scores: VariableList<u64, C::ValidatorRegistryLimit>,
+    // Sync
+    pub(crate) current_sync_committee: SyncCommittee<C>,
+    pub(crate) next_sync_committee: SyncCommittee<C>,
+    // Execution
+    pub(crate) latest_execution_payload_header: ExecutionPayloadHeader<C>,
+    // Withdrawals
+    pub(crate) next_withdrawal_index: WithdrawalIndex,
+    pub(crate) next_withdrawal_validator_index: ValidatorIndex,
+    // Deep history valid from Capella onwards
+    pub(crate) historical_summaries: VariableList<HistoricalSummary, C::HistoricalRootsLimit>,
+}
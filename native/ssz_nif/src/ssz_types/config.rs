@@ -31,6 +31,7 @@ pub(crate) trait Config {
     type MaxVoluntaryExits: Unsigned;
     type SyncCommitteeSize: Unsigned;
     type SyncCommitteeSubnetCount: Unsigned;
+    type EpochsPerSyncCommitteePeriod: Unsigned;
     type AttestationSubnetCount: Unsigned;
     type MaxBytesPerTransaction: Unsigned;
     type MaxTransactionsPerPayload: Unsigned;
@@ -56,12 +57,18 @@ pub(crate) trait Config {
     type MaxAttesterSlashingsElectra: Unsigned;
     type MaxAttestationsElectra: Unsigned;
     type MaxValidatorsPerSlot: Unsigned;
+    // Fulu added fields
+    type NumberOfColumns: Unsigned;
+    type CellsPerExtBlob: Unsigned;
+    type FieldElementsPerCell: Unsigned;
+    type KzgCommitmentsInclusionProofDepth: Unsigned;
 
     // Derived constants. Ideally, this would be trait defaults.
     type SyncSubcommitteeSize: Unsigned; // SYNC_COMMITTEE_SIZE / SYNC_COMMITTEE_SUBNET_COUNT
     type MaxPendingAttestations: Unsigned; // MAX_ATTESTATIONS * SLOTS_PER_EPOCH
     type SlotsPerEth1VotingPeriod: Unsigned; // EPOCHS_PER_ETH1_VOTING_PERIOD * SLOTS_PER_EPOCH
     type BytesPerBlob: Unsigned; // FIELD_ELEMENTS_PER_BLOB * BYTES_PER_FIELD_ELEMENT
+    type BytesPerCell: Unsigned; // FIELD_ELEMENTS_PER_CELL * BYTES_PER_FIELD_ELEMENT
 }
 
 pub(crate) struct Mainnet;
@@ -85,6 +92,7 @@ impl Config for Mainnet {
     type MaxVoluntaryExits = U16;
     type SyncCommitteeSize = U512;
     type SyncCommitteeSubnetCount = U4;
+    type EpochsPerSyncCommitteePeriod = U256;
     type AttestationSubnetCount = U64;
     type MaxBytesPerTransaction = U1073741824; // 1,073,741,824
     type MaxTransactionsPerPayload = U1048576; // 1,048,576
@@ -111,6 +119,12 @@ impl Config for Mainnet {
     type MaxAttestationsElectra = U8;
     type MaxValidatorsPerSlot = U131072; // MaxValidatorsPerCommittee * MaxCommitteesPerSlot - 2048 * 64, this as the rest is fixed and we need to be really carefull about any change
 
+    // Fulu added fields
+    type NumberOfColumns = U128;
+    type CellsPerExtBlob = U128;
+    type FieldElementsPerCell = U64;
+    type KzgCommitmentsInclusionProofDepth = U4;
+
     // Derived constants. Ideally, this would be trait defaults.
     type SyncSubcommitteeSize =
         typenum::Quot<Self::SyncCommitteeSize, Self::SyncCommitteeSubnetCount>; // 512 committee size / 4 sync committee subnet count
@@ -118,6 +132,7 @@ impl Config for Mainnet {
     type SlotsPerEth1VotingPeriod =
         typenum::Prod<Self::EpochsPerEth1VotingPeriod, Self::SlotsPerEpoch>; // 64 epochs * 32 slots per epoch
     type BytesPerBlob = typenum::Prod<Self::FieldElementsPerBlob, Self::BytesPerFieldElement>;
+    type BytesPerCell = typenum::Prod<Self::FieldElementsPerCell, Self::BytesPerFieldElement>;
 }
 
 pub(crate) struct Minimal;
@@ -129,6 +144,7 @@ impl Config for Minimal {
     type EpochsPerHistoricalVector = U64;
     type EpochsPerSlashingsVector = U64;
     type SyncCommitteeSize = U32;
+    type EpochsPerSyncCommitteePeriod = U8;
     type MaxWithdrawalsPerPayload = U4;
     type FieldElementsPerBlob = U4096;
     type MaxBlobCommitmentsPerBlock = U32;
@@ -148,6 +164,7 @@ impl Config for Minimal {
     type SlotsPerEth1VotingPeriod =
         typenum::Prod<Self::EpochsPerEth1VotingPeriod, Self::SlotsPerEpoch>; // 4 epochs * 8 slots per epoch
     type BytesPerBlob = typenum::Prod<Self::FieldElementsPerBlob, Self::BytesPerFieldElement>;
+    type BytesPerCell = typenum::Prod<Self::FieldElementsPerCell, Self::BytesPerFieldElement>;
 
     inherit_from!(Mainnet {
         JustificationBitsLength,
@@ -175,7 +192,11 @@ impl Config for Minimal {
         MaxConsolidationRequestsPerPayload,
         PendingDepositsLimit,
         MaxAttesterSlashingsElectra,
-        MaxAttestationsElectra
+        MaxAttestationsElectra,
+        NumberOfColumns,
+        CellsPerExtBlob,
+        FieldElementsPerCell,
+        KzgCommitmentsInclusionProofDepth
     });
 }
 
@@ -200,6 +221,7 @@ impl Config for Gnosis {
     type MaxVoluntaryExits = U16;
     type SyncCommitteeSize = U512;
     type SyncCommitteeSubnetCount = U4;
+    type EpochsPerSyncCommitteePeriod = U512;
     type AttestationSubnetCount = U64;
     type MaxBytesPerTransaction = U1073741824; // 1,073,741,824
     type MaxTransactionsPerPayload = U1048576; // 1,048,576
@@ -226,6 +248,12 @@ impl Config for Gnosis {
     type MaxAttestationsElectra = U8;
     type MaxValidatorsPerSlot = U131072; // MaxValidatorsPerCommittee * MaxCommitteesPerSlot - 2048 * 64, this as the rest is fixed and we need to be really carefull about any change
 
+    // Fulu added fields
+    type NumberOfColumns = U128;
+    type CellsPerExtBlob = U128;
+    type FieldElementsPerCell = U64;
+    type KzgCommitmentsInclusionProofDepth = U4;
+
     // Derived constants. Ideally, this would be trait defaults.
     type SyncSubcommitteeSize =
         typenum::Quot<Self::SyncCommitteeSize, Self::SyncCommitteeSubnetCount>; // 512 committee size / 4 sync committee subnet count
@@ -233,4 +261,5 @@ impl Config for Gnosis {
     type SlotsPerEth1VotingPeriod =
         typenum::Prod<Self::EpochsPerEth1VotingPeriod, Self::SlotsPerEpoch>; // 64 epochs * 32 slots per epoch
     type BytesPerBlob = typenum::Prod<Self::FieldElementsPerBlob, Self::BytesPerFieldElement>;
+    type BytesPerCell = typenum::Prod<Self::FieldElementsPerCell, Self::BytesPerFieldElement>;
 }
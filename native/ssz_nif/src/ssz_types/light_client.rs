@@ -0,0 +1,50 @@
+use super::{config::Config, *};
+use ssz_derive::{Decode, Encode};
+use tree_hash_derive::TreeHash;
+
+// Branch depths are `floorlog2` of the Electra generalized indices
+pub(crate) type ExecutionBranch = FixedVector<Bytes32, typenum::U4>;
+pub(crate) type CurrentSyncCommitteeBranch = FixedVector<Bytes32, typenum::U6>;
+pub(crate) type NextSyncCommitteeBranch = FixedVector<Bytes32, typenum::U6>;
+pub(crate) type FinalityBranch = FixedVector<Bytes32, typenum::U7>;
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct LightClientHeader<C: Config> {
+    pub(crate) beacon: BeaconBlockHeader,
+    pub(crate) execution: ExecutionPayloadHeader<C>,
+    pub(crate) execution_branch: ExecutionBranch,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct LightClientBootstrap<C: Config> {
+    pub(crate) header: LightClientHeader<C>,
+    pub(crate) current_sync_committee: SyncCommittee<C>,
+    pub(crate) current_sync_committee_branch: CurrentSyncCommitteeBranch,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct LightClientUpdate<C: Config> {
+    pub(crate) attested_header: LightClientHeader<C>,
+    pub(crate) next_sync_committee: SyncCommittee<C>,
+    pub(crate) next_sync_committee_branch: NextSyncCommitteeBranch,
+    pub(crate) finalized_header: LightClientHeader<C>,
+    pub(crate) finality_branch: FinalityBranch,
+    pub(crate) sync_aggregate: SyncAggregate<C>,
+    pub(crate) signature_slot: Slot,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct LightClientFinalityUpdate<C: Config> {
+    pub(crate) attested_header: LightClientHeader<C>,
+    pub(crate) finalized_header: LightClientHeader<C>,
+    pub(crate) finality_branch: FinalityBranch,
+    pub(crate) sync_aggregate: SyncAggregate<C>,
+    pub(crate) signature_slot: Slot,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct LightClientOptimisticUpdate<C: Config> {
+    pub(crate) attested_header: LightClientHeader<C>,
+    pub(crate) sync_aggregate: SyncAggregate<C>,
+    pub(crate) signature_slot: Slot,
+}
@@ -1,17 +1,28 @@
 //! # SSZ Types
 //!
 //! Structs that implement [`ssz::Encode`] and [`ssz::Decode`].
+//!
+//! The containers at the root of the module are the Electra ones. Each of the
+//! fork modules only has the containers that changed in the fork after it.
 
 mod beacon_chain;
+mod light_client;
 mod p2p;
 mod validator;
 
 pub(crate) use beacon_chain::*;
+pub(crate) use light_client::*;
 pub(crate) use p2p::*;
 pub(crate) use validator::*;
 
 pub(crate) mod config;
 
+pub(crate) mod altair;
+pub(crate) mod bellatrix;
+pub(crate) mod capella;
+pub(crate) mod deneb;
+pub(crate) mod phase0;
+
 use ssz_derive::{Decode, Encode};
 use ssz_types::{typenum, FixedVector, VariableList};
 
@@ -48,6 +59,9 @@ type KZGCommitment = Bytes48;
 type KZGProof = Bytes48;
 type BlobIndex = u64;
 type Blob<C> = FixedVector<u8, <C as Config>::BytesPerBlob>;
+type ColumnIndex = u64;
+type RowIndex = u64;
+type Cell<C> = FixedVector<u8, <C as Config>::BytesPerCell>;
 
 // This type is a little-endian encoded uint256.
 // We use this to because of Erlang's NIF limitations.
@@ -42,3 +42,46 @@ pub(crate) struct BlobIdentifier {
     pub(crate) block_root: Root,
     pub(crate) index: BlobIndex,
 }
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct StatusMessageV2 {
+    pub(crate) fork_digest: ForkDigest,
+    pub(crate) finalized_root: Root,
+    pub(crate) finalized_epoch: Epoch,
+    pub(crate) head_root: Root,
+    pub(crate) head_slot: Slot,
+    pub(crate) earliest_available_slot: Slot,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct MetadataV3<C: Config> {
+    pub(crate) seq_number: u64,
+    pub(crate) attnets: BitVector<C::AttestationSubnetCount>,
+    pub(crate) syncnets: BitVector<C::SyncCommitteeSubnetCount>,
+    pub(crate) custody_group_count: u64,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct DataColumnSidecar<C: Config> {
+    pub(crate) index: ColumnIndex,
+    pub(crate) column: VariableList<Cell<C>, C::MaxBlobCommitmentsPerBlock>,
+    pub(crate) kzg_commitments: VariableList<KZGCommitment, C::MaxBlobCommitmentsPerBlock>,
+    pub(crate) kzg_proofs: VariableList<KZGProof, C::MaxBlobCommitmentsPerBlock>,
+    pub(crate) signed_block_header: SignedBeaconBlockHeader,
+    pub(crate) kzg_commitments_inclusion_proof:
+        FixedVector<Bytes32, C::KzgCommitmentsInclusionProofDepth>,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct DataColumnsByRootIdentifier<C: Config> {
+    pub(crate) block_root: Root,
+    pub(crate) columns: VariableList<ColumnIndex, C::NumberOfColumns>,
+}
+
+#[derive(Encode, Decode, TreeHash)]
+pub(crate) struct MatrixEntry<C: Config> {
+    pub(crate) cell: Cell<C>,
+    pub(crate) kzg_proof: KZGProof,
+    pub(crate) column_index: ColumnIndex,
+    pub(crate) row_index: RowIndex,
+}
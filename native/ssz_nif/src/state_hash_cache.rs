@@ -0,0 +1,335 @@
+//! Incremental hashing of encoded [`BeaconState`](crate::ssz_types::BeaconState)s.
+//! The cache keeps the encoding and the Merkle tree of each field of the last
+//! hashed state, and only rehashes the chunks whose bytes changed since then.
+//! Since chunks are compared byte for byte, any state can be given: an unrelated
+//! one is just hashed from scratch. Only the Electra layout is supported.
+
+use std::sync::{Mutex, PoisonError};
+
+use ethereum_hashing::hash32_concat;
+use rustler::{Atom, Binary, Encoder, Env, NifResult, ResourceArc, Term};
+use ssz::{Decode, DecodeError, SszDecoderBuilder};
+use ssz_types::{typenum::Unsigned, BitVector};
+use tree_hash::{Hash256, TreeHash, BYTES_PER_CHUNK};
+
+use crate::ssz_types::config::Config;
+use crate::ssz_types::{
+    BeaconBlockHeader, Checkpoint, Eth1Data, ExecutionPayloadHeader, Fork, HistoricalSummary,
+    PendingConsolidation, PendingDeposit, PendingPartialWithdrawal, Root, SyncCommittee, Validator,
+};
+use crate::utils::errors::invalid_ssz;
+use crate::utils::helpers::bytes_to_binary;
+use crate::utils::merkle_node::depth_of;
+use crate::utils::preset_match;
+
+mod atoms {
+    use rustler::atoms;
+
+    atoms! {
+        ok,
+    }
+}
+
+type HashFn = fn(&[u8]) -> Result<Root, DecodeError>;
+
+fn hash_encoded<T: Decode + TreeHash>(bytes: &[u8]) -> Result<Root, DecodeError> {
+    Ok(T::from_ssz_bytes(bytes)?.tree_hash_root().0)
+}
+
+fn pad_chunk(bytes: &[u8]) -> Result<Root, DecodeError> {
+    let mut chunk = [0; BYTES_PER_CHUNK];
+    chunk[..bytes.len()].copy_from_slice(bytes);
+    Ok(chunk)
+}
+
+enum Leaves {
+    /// The field is rehashed as a whole whenever its bytes change.
+    Whole(HashFn),
+    /// The chunks of a vector or list are each `chunk_size` bytes of its encoding.
+    /// Lists have their item size and limit, to check and mix in their length.
+    Chunks {
+        chunk_size: usize,
+        hash_chunk: HashFn,
+        depth: usize,
+        list: Option<(usize, usize)>,
+    },
+}
+
+struct Field {
+    fixed_length: Option<usize>,
+    leaves: Leaves,
+}
+
+fn whole<T: Decode + TreeHash>() -> Field {
+    Field {
+        fixed_length: T::is_ssz_fixed_len().then(T::ssz_fixed_len),
+        leaves: Leaves::Whole(hash_encoded::<T>),
+    }
+}
+
+/// A vector of basic values or roots, packed in chunks.
+fn packed_vector<T: Decode, N: Unsigned>() -> Field {
+    let length = N::to_usize() * T::ssz_fixed_len();
+    Field {
+        fixed_length: Some(length),
+        leaves: Leaves::Chunks {
+            chunk_size: BYTES_PER_CHUNK,
+            hash_chunk: pad_chunk,
+            depth: depth_of(length.div_ceil(BYTES_PER_CHUNK)),
+            list: None,
+        },
+    }
+}
+
+/// A list of basic values or roots, packed in chunks.
+fn packed_list<T: Decode, N: Unsigned>() -> Field {
+    let item_size = T::ssz_fixed_len();
+    Field {
+        fixed_length: None,
+        leaves: Leaves::Chunks {
+            chunk_size: BYTES_PER_CHUNK,
+            hash_chunk: pad_chunk,
+            depth: depth_of((N::to_usize() * item_size).div_ceil(BYTES_PER_CHUNK)),
+            list: Some((item_size, N::to_usize())),
+        },
+    }
+}
+
+/// A list of fixed-size containers, each hashed into a chunk.
+fn container_list<T: Decode + TreeHash, N: Unsigned>() -> Field {
+    let item_size = T::ssz_fixed_len();
+    Field {
+        fixed_length: None,
+        leaves: Leaves::Chunks {
+            chunk_size: item_size,
+            hash_chunk: hash_encoded::<T>,
+            depth: depth_of(N::to_usize()),
+            list: Some((item_size, N::to_usize())),
+        },
+    }
+}
+
+fn state_fields<C: Config>() -> Vec<Field> {
+    vec![
+        whole::<u64>(),
+        whole::<Root>(),
+        whole::<u64>(),
+        whole::<Fork>(),
+        whole::<BeaconBlockHeader>(),
+        packed_vector::<Root, C::SlotsPerHistoricalRoot>(),
+        packed_vector::<Root, C::SlotsPerHistoricalRoot>(),
+        packed_list::<Root, C::HistoricalRootsLimit>(),
+        whole::<Eth1Data>(),
+        container_list::<Eth1Data, C::SlotsPerEth1VotingPeriod>(),
+        whole::<u64>(),
+        container_list::<Validator, C::ValidatorRegistryLimit>(),
+        packed_list::<u64, C::ValidatorRegistryLimit>(),
+        packed_vector::<Root, C::EpochsPerHistoricalVector>(),
+        packed_vector::<u64, C::EpochsPerSlashingsVector>(),
+        packed_list::<u8, C::ValidatorRegistryLimit>(),
+        packed_list::<u8, C::ValidatorRegistryLimit>(),
+        whole::<BitVector<C::JustificationBitsLength>>(),
+        whole::<Checkpoint>(),
+        whole::<Checkpoint>(),
+        whole::<Checkpoint>(),
+        packed_list::<u64, C::ValidatorRegistryLimit>(),
+        whole::<SyncCommittee<C>>(),
+        whole::<SyncCommittee<C>>(),
+        whole::<ExecutionPayloadHeader<C>>(),
+        whole::<u64>(),
+        whole::<u64>(),
+        container_list::<HistoricalSummary, C::HistoricalRootsLimit>(),
+        whole::<u64>(),
+        whole::<u64>(),
+        whole::<u64>(),
+        whole::<u64>(),
+        whole::<u64>(),
+        whole::<u64>(),
+        container_list::<PendingDeposit, C::PendingDepositsLimit>(),
+        container_list::<PendingPartialWithdrawal, C::PendingPartialWithdrawalsLimit>(),
+        container_list::<PendingConsolidation, C::PendingConsolidationsLimit>(),
+    ]
+}
+
+/// Merkle tree of the chunks of a vector or list, updated along the paths of the
+/// chunks that change.
+#[derive(Default)]
+struct ChunkTree {
+    /// Nodes of each level covering the chunks, from the chunks up to the root.
+    /// The nodes to their right are zero hashes.
+    layers: Vec<Vec<Root>>,
+}
+
+impl ChunkTree {
+    fn depth(&self) -> Option<usize> {
+        self.layers.len().checked_sub(1)
+    }
+
+    /// Sets the chunks at the given positions, in increasing order, out of `count`
+    /// chunks, and returns the root of the tree of the given depth.
+    fn update(&mut self, depth: usize, count: usize, changed: Vec<(usize, Root)>) -> Root {
+        self.layers.resize_with(depth + 1, Vec::new);
+        self.layers[0].resize(count, [0; 32]);
+        let mut dirty = Vec::with_capacity(changed.len());
+        for (index, chunk) in changed {
+            self.layers[0][index] = chunk;
+            dirty.push(index);
+        }
+
+        let mut zero_hash = [0; 32];
+        for level in 0..depth {
+            let (lower, upper) = self.layers.split_at_mut(level + 1);
+            let (nodes, parents) = (&lower[level], &mut upper[0]);
+            parents.resize(nodes.len().div_ceil(2), [0; 32]);
+            dirty.iter_mut().for_each(|index| *index /= 2);
+            dirty.dedup();
+            for &parent in &dirty {
+                let right = nodes.get(2 * parent + 1).unwrap_or(&zero_hash);
+                parents[parent] = hash32_concat(&nodes[2 * parent], right);
+            }
+            zero_hash = hash32_concat(&zero_hash, &zero_hash);
+        }
+        self.layers[depth].first().copied().unwrap_or(zero_hash)
+    }
+}
+
+#[derive(Default)]
+struct FieldCache {
+    bytes: Vec<u8>,
+    root: Option<Root>,
+    tree: ChunkTree,
+}
+
+impl FieldCache {
+    fn root(&mut self, field: &Field, bytes: &[u8]) -> Result<Root, DecodeError> {
+        if let Some(root) = self.root.filter(|_| self.bytes == bytes) {
+            return Ok(root);
+        }
+        let root = match field.leaves {
+            Leaves::Whole(hash) => hash(bytes)?,
+            Leaves::Chunks {
+                chunk_size,
+                hash_chunk,
+                depth,
+                list,
+            } => {
+                let length = list
+                    .map(|(item_size, limit)| list_length(bytes, item_size, limit))
+                    .transpose()?;
+                let root = self.update_tree(bytes, chunk_size, hash_chunk, depth)?;
+                length.map_or(root, |length| mix_in_length(root, length))
+            }
+        };
+        self.bytes.clear();
+        self.bytes.extend_from_slice(bytes);
+        self.root = Some(root);
+        Ok(root)
+    }
+
+    fn update_tree(
+        &mut self,
+        bytes: &[u8],
+        chunk_size: usize,
+        hash_chunk: HashFn,
+        depth: usize,
+    ) -> Result<Root, DecodeError> {
+        // A shorter encoding may end in the middle of a chunk that looks unchanged
+        if bytes.len() < self.bytes.len() || self.tree.depth() != Some(depth) {
+            *self = Self::default();
+        }
+        let changed = bytes
+            .chunks(chunk_size)
+            .enumerate()
+            .filter(|(index, chunk)| {
+                let start = index * chunk_size;
+                self.bytes.get(start..start + chunk.len()) != Some(*chunk)
+            })
+            .map(|(index, chunk)| Ok((index, hash_chunk(chunk)?)))
+            .collect::<Result<Vec<_>, DecodeError>>()?;
+        let count = bytes.len().div_ceil(chunk_size);
+        Ok(self.tree.update(depth, count, changed))
+    }
+}
+
+fn list_length(bytes: &[u8], item_size: usize, limit: usize) -> Result<usize, DecodeError> {
+    let length = bytes.len() / item_size;
+    if bytes.len() % item_size != 0 || length > limit {
+        return Err(DecodeError::BytesInvalid(format!(
+            "List of {} bytes with items of {item_size} bytes and a limit of {limit}",
+            bytes.len()
+        )));
+    }
+    Ok(length)
+}
+
+fn mix_in_length(root: Root, length: usize) -> Root {
+    tree_hash::mix_in_length(&Hash256::from(root), length).0
+}
+
+#[derive(Default)]
+struct Cache {
+    config: String,
+    fields: Vec<FieldCache>,
+}
+
+impl Cache {
+    fn state_root<C: Config>(&mut self, bytes: &[u8]) -> Result<Root, DecodeError> {
+        let fields = state_fields::<C>();
+        let mut builder = SszDecoderBuilder::new(bytes);
+        for field in &fields {
+            builder.register_type_parameterized(
+                field.fixed_length.is_some(),
+                field.fixed_length.unwrap_or(ssz::BYTES_PER_LENGTH_OFFSET),
+            )?;
+        }
+        let mut decoder = builder.build()?;
+
+        self.fields.resize_with(fields.len(), FieldCache::default);
+        let mut leaves = Vec::with_capacity(fields.len());
+        for (field, cache) in fields.iter().zip(&mut self.fields) {
+            let field_bytes = decoder.decode_next_with(Ok)?;
+            leaves.push(cache.root(field, field_bytes)?);
+        }
+        Ok(tree_hash::merkle_root(&leaves.concat(), 0).0)
+    }
+}
+
+pub(crate) struct StateHashCache(Mutex<Cache>);
+
+#[allow(non_local_definitions)]
+pub(crate) fn load(env: Env) -> bool {
+    rustler::resource!(StateHashCache, env);
+    true
+}
+
+fn state_root<C: Config>(cache: &mut Cache, bytes: &[u8]) -> NifResult<Root> {
+    cache.state_root::<C>(bytes).map_err(invalid_ssz)
+}
+
+#[rustler::nif]
+fn state_hash_cache_new() -> ResourceArc<StateHashCache> {
+    ResourceArc::new(StateHashCache(Mutex::new(Cache::default())))
+}
+
+// Returns the hash tree root of an encoded state, reusing the hashes of the
+// previous state given to the cache.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn state_hash_cache_root<'env>(
+    env: Env<'env>,
+    cache: ResourceArc<StateHashCache>,
+    state: Binary,
+    config: Atom,
+) -> NifResult<Term<'env>> {
+    let config = config.to_term(env).atom_to_string()?;
+    // Each field's cache is only modified once its new root is known, so it's
+    // consistent even if a panic poisoned the lock
+    let mut cache = cache.0.lock().unwrap_or_else(PoisonError::into_inner);
+    if cache.config != config {
+        *cache = Cache {
+            config: config.clone(),
+            fields: vec![],
+        };
+    }
+    let root = preset_match!(config.as_str(), state_root(&mut cache, &state))?;
+    Ok((atoms::ok(), bytes_to_binary(env, &root)).encode(env))
+}
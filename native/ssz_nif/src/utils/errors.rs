@@ -0,0 +1,63 @@
+//! Errors returned by the NIFs as `{:error, reason}` tuples.
+//! See `docs/nif_errors.md` for the full list.
+
+use std::fmt::Debug;
+
+use super::from_elx::FromElxError;
+
+mod atoms {
+    use rustler::atoms;
+
+    atoms! {
+        unknown_schema,
+        unknown_config,
+        unknown_fork,
+        invalid_ssz,
+        invalid_value,
+        invalid_term,
+        invalid_signature_encoding,
+        invalid_transaction,
+        invalid_path,
+    }
+}
+
+pub(crate) fn unknown_schema(schema: &str) -> rustler::Error {
+    rustler::Error::Term(Box::new((atoms::unknown_schema(), schema.to_owned())))
+}
+
+pub(crate) fn unknown_config(config: &str) -> rustler::Error {
+    rustler::Error::Term(Box::new((atoms::unknown_config(), config.to_owned())))
+}
+
+pub(crate) fn unknown_fork(fork: &str) -> rustler::Error {
+    rustler::Error::Term(Box::new((atoms::unknown_fork(), fork.to_owned())))
+}
+
+pub(crate) fn invalid_ssz(error: impl Debug) -> rustler::Error {
+    rustler::Error::Term(Box::new((atoms::invalid_ssz(), format!("{error:?}"))))
+}
+
+pub(crate) fn invalid_value(error: FromElxError) -> rustler::Error {
+    rustler::Error::Term(Box::new((atoms::invalid_value(), error.to_string())))
+}
+
+/// The term doesn't have the shape of the schema's struct.
+pub(crate) fn invalid_term(_error: rustler::Error) -> rustler::Error {
+    rustler::Error::Term(Box::new(atoms::invalid_term()))
+}
+
+pub(crate) fn invalid_signature_encoding(error: impl Debug) -> rustler::Error {
+    rustler::Error::Term(Box::new((
+        atoms::invalid_signature_encoding(),
+        format!("{error:?}"),
+    )))
+}
+
+pub(crate) fn invalid_transaction(detail: String) -> rustler::Error {
+    rustler::Error::Term(Box::new((atoms::invalid_transaction(), detail)))
+}
+
+/// The path doesn't lead to a value inside the schema.
+pub(crate) fn invalid_path(detail: String) -> rustler::Error {
+    rustler::Error::Term(Box::new((atoms::invalid_path(), detail)))
+}
@@ -2,10 +2,13 @@ use crate::utils::from_ssz::FromSsz;
 use rustler::{Binary, Decoder, Encoder, Env, NewBinary, NifResult, Term};
 use ssz::{Decode, Encode};
 
-use std::{fmt::Debug, io::Write};
+use std::io::Write;
 use tree_hash::{Hash256, MerkleHasher, TreeHash, TreeHashType};
 
-use super::from_elx::{FromElx, FromElxError};
+use super::errors::{invalid_ssz, invalid_term, invalid_value};
+use super::from_elx::FromElx;
+use super::merkle_node::{MerkleNode, PathItem};
+use crate::merkle_proof::{prove, Proof};
 
 pub(crate) fn bytes_to_binary<'env>(env: Env<'env>, bytes: &[u8]) -> Binary<'env> {
     let mut binary = NewBinary::new(env, bytes.len());
@@ -20,33 +23,34 @@ where
     Ssz: Encode + FromElx<Elx>,
 {
     if value.is_list() {
-        let value_nif = Vec::<Elx>::decode(value)?;
+        let value_nif = Vec::<Elx>::decode(value).map_err(invalid_term)?;
         let value_ssz = value_nif
             .into_iter()
             .map(Ssz::from)
             .collect::<Result<Vec<_>, _>>()
-            .map_err(to_nif_result)?;
+            .map_err(invalid_value)?;
         return Ok(value_ssz.as_ssz_bytes());
     }
-    let value_nif = <Elx as Decoder>::decode(value)?;
-    let value_ssz = Ssz::from(value_nif).map_err(to_nif_result)?;
+    let value_nif = <Elx as Decoder>::decode(value).map_err(invalid_term)?;
+    let value_ssz = Ssz::from(value_nif).map_err(invalid_value)?;
     Ok(value_ssz.as_ssz_bytes())
 }
 
-fn to_nif_result(result: FromElxError) -> rustler::Error {
-    rustler::Error::Term(Box::new(result.to_string()))
-}
-
 pub(crate) fn decode_ssz<'a, Elx, Ssz>((bytes, env): (&[u8], Env<'a>)) -> NifResult<Term<'a>>
 where
     Elx: Encoder + FromSsz<'a, Ssz>,
     Ssz: Decode,
 {
-    let recovered_value = Ssz::from_ssz_bytes(bytes).map_err(debug_error_to_nif)?;
+    let recovered_value = Ssz::from_ssz_bytes(bytes).map_err(invalid_ssz)?;
     let checkpoint = Elx::from(recovered_value, env);
     Ok(checkpoint.encode(env))
 }
 
+/// Length of the schema's encoding, or `None` if it's variable.
+pub(crate) fn fixed_length<Elx, Ssz: Decode>(_: ()) -> NifResult<Option<usize>> {
+    Ok(Ssz::is_ssz_fixed_len().then(Ssz::ssz_fixed_len))
+}
+
 // TODO: this doesn't take into account the list's max size
 pub(crate) fn list_decode_ssz<'a, Elx, Ssz>(args: (&[u8], Env<'a>)) -> NifResult<Term<'a>>
 where
@@ -56,21 +60,30 @@ where
     decode_ssz::<Vec<Elx>, Vec<Ssz>>(args)
 }
 
-fn debug_error_to_nif(error: impl Debug) -> rustler::Error {
-    rustler::Error::Term(Box::new(format!("{error:?}")))
-}
-
 pub(crate) fn hash_tree_root<'a, Elx, Ssz>(value: Term<'a>) -> NifResult<[u8; 32]>
 where
     Elx: Decoder<'a>,
     Ssz: TreeHash + FromElx<Elx>,
 {
-    let value_nif = <Elx as Decoder>::decode(value)?;
-    let value_ssz = Ssz::from(value_nif).map_err(to_nif_result)?;
+    let value_nif = <Elx as Decoder>::decode(value).map_err(invalid_term)?;
+    let value_ssz = Ssz::from(value_nif).map_err(invalid_value)?;
     let hash = value_ssz.tree_hash_root();
     Ok(hash.0)
 }
 
+/// Returns the proof of the value at each path.
+pub(crate) fn merkle_proofs<'a, Elx, Ssz>(
+    (value, paths): (Term<'a>, &[Vec<PathItem>]),
+) -> NifResult<Vec<Proof>>
+where
+    Elx: Decoder<'a>,
+    Ssz: MerkleNode + FromElx<Elx>,
+{
+    let value_nif = <Elx as Decoder>::decode(value).map_err(invalid_term)?;
+    let value_ssz = Ssz::from(value_nif).map_err(invalid_value)?;
+    prove(&value_ssz, paths)
+}
+
 pub(crate) fn hash_list_tree_root<'a, Elx, Ssz>(
     (list, max_size): (Vec<Term<'a>>, usize),
 ) -> NifResult<[u8; 32]>
@@ -92,7 +105,7 @@ where
     Ssz: TreeHash + FromElx<Elx>,
 {
     let v: NifResult<Vec<Elx>> = list.into_iter().map(Elx::decode).collect();
-    let x: Vec<Ssz> = FromElx::from(v?).map_err(to_nif_result)?;
+    let x: Vec<Ssz> = FromElx::from(v.map_err(invalid_term)?).map_err(invalid_value)?;
     Ok(vec_tree_hash_root(&x, max_size))
 }
 
@@ -0,0 +1,249 @@
+//! Navigation of the Merkle trees of SSZ values, used to prove their contents.
+//! Each value is a node whose root is the root of a tree of chunks, with the
+//! length mixed in if it's a list. Containers implement [`MerkleNode`] through
+//! the [`gen_struct`](super::gen_struct) macros.
+
+use rustler::{Decoder, NifResult, Term};
+use ssz_types::{typenum::Unsigned, BitList, BitVector, FixedVector, VariableList};
+use tree_hash::{TreeHash, TreeHashType, BYTES_PER_CHUNK};
+
+use crate::ssz_types::{Root, Uint256};
+
+const BITS_PER_CHUNK: usize = BYTES_PER_CHUNK * 8;
+
+/// A step of the path to a value: a container field, an element of a vector or
+/// list, or the length of a list (`:__len__` in Elixir).
+#[derive(PartialEq, Eq, Hash)]
+pub(crate) enum PathItem {
+    Field(String),
+    Index(usize),
+    Length,
+}
+
+impl<'a> Decoder<'a> for PathItem {
+    fn decode(term: Term<'a>) -> NifResult<Self> {
+        if term.is_atom() {
+            let name = term.atom_to_string()?;
+            return Ok(match name.as_str() {
+                "__len__" => PathItem::Length,
+                _ => PathItem::Field(name),
+            });
+        }
+        Ok(PathItem::Index(term.decode()?))
+    }
+}
+
+/// The child of a node that a path item leads to.
+pub(crate) struct Child<'a> {
+    /// Position of the chunk with the child among the node's chunks.
+    pub(crate) position: usize,
+    /// The child itself, unless it's a basic value packed in the chunk.
+    pub(crate) node: Option<&'a dyn MerkleNode>,
+}
+
+pub(crate) trait MerkleNode {
+    /// Chunks of the node's tree, without the zero padding.
+    fn chunks(&self) -> Vec<Root>;
+
+    /// Depth of the node's tree, without the length mix-in.
+    fn depth(&self) -> usize;
+
+    /// Length mixed into the root, for lists.
+    fn length(&self) -> Option<usize> {
+        None
+    }
+
+    fn child(&self, item: &PathItem) -> Result<Child<'_>, String>;
+}
+
+/// Depth of a tree with room for `chunks` chunks.
+pub(crate) fn depth_of(chunks: usize) -> usize {
+    chunks.next_power_of_two().trailing_zeros() as usize
+}
+
+/// Returns the field of a container named by `item`. `fields` are in order.
+pub(crate) fn container_child<'a>(
+    item: &PathItem,
+    fields: &[(&str, &'a dyn MerkleNode)],
+) -> Result<Child<'a>, String> {
+    let PathItem::Field(name) = item else {
+        return Err("Containers can only be indexed by field name".to_owned());
+    };
+    fields
+        .iter()
+        .position(|(field, _)| field == name)
+        .map(|position| Child {
+            position,
+            node: Some(fields[position].1),
+        })
+        .ok_or_else(|| format!("No field named {name}"))
+}
+
+fn pack(bytes: &[u8]) -> Vec<Root> {
+    bytes
+        .chunks(BYTES_PER_CHUNK)
+        .map(|chunk| {
+            let mut root = [0; BYTES_PER_CHUNK];
+            root[..chunk.len()].copy_from_slice(chunk);
+            root
+        })
+        .collect()
+}
+
+fn sequence_chunks<T: TreeHash>(items: &[T]) -> Vec<Root> {
+    match T::tree_hash_type() {
+        TreeHashType::Basic => {
+            let bytes: Vec<u8> = items
+                .iter()
+                .flat_map(TreeHash::tree_hash_packed_encoding)
+                .collect();
+            pack(&bytes)
+        }
+        _ => items.iter().map(|item| item.tree_hash_root().0).collect(),
+    }
+}
+
+fn sequence_depth<T: TreeHash>(limit: usize) -> usize {
+    match T::tree_hash_type() {
+        TreeHashType::Basic => depth_of(limit.div_ceil(T::tree_hash_packing_factor())),
+        _ => depth_of(limit),
+    }
+}
+
+fn index_of(item: &PathItem, length: usize) -> Result<usize, String> {
+    match item {
+        PathItem::Index(index) if *index < length => Ok(*index),
+        PathItem::Index(index) => Err(format!(
+            "Index {index} is out of bounds for length {length}"
+        )),
+        _ => Err("Vectors and lists can only be indexed by position".to_owned()),
+    }
+}
+
+fn sequence_child<'a, T: TreeHash + MerkleNode>(
+    items: &'a [T],
+    item: &PathItem,
+) -> Result<Child<'a>, String> {
+    let index = index_of(item, items.len())?;
+    Ok(match T::tree_hash_type() {
+        TreeHashType::Basic => Child {
+            position: index / T::tree_hash_packing_factor(),
+            node: None,
+        },
+        _ => Child {
+            position: index,
+            node: Some(&items[index]),
+        },
+    })
+}
+
+fn bit_child(item: &PathItem, length: usize) -> Result<Child<'static>, String> {
+    let index = index_of(item, length)?;
+    Ok(Child {
+        position: index / BITS_PER_CHUNK,
+        node: None,
+    })
+}
+
+macro_rules! basic_impl {
+    ($t:ty) => {
+        impl MerkleNode for $t {
+            fn chunks(&self) -> Vec<Root> {
+                vec![self.tree_hash_root().0]
+            }
+
+            fn depth(&self) -> usize {
+                0
+            }
+
+            fn child(&self, _item: &PathItem) -> Result<Child<'_>, String> {
+                Err("Basic values have no children".to_owned())
+            }
+        }
+    };
+}
+
+basic_impl!(bool);
+basic_impl!(u8);
+basic_impl!(u16);
+basic_impl!(u32);
+basic_impl!(u64);
+basic_impl!(Uint256);
+
+impl<const N: usize> MerkleNode for [u8; N] {
+    fn chunks(&self) -> Vec<Root> {
+        pack(self)
+    }
+
+    fn depth(&self) -> usize {
+        sequence_depth::<u8>(N)
+    }
+
+    fn child(&self, item: &PathItem) -> Result<Child<'_>, String> {
+        sequence_child(self, item)
+    }
+}
+
+impl<T: TreeHash + MerkleNode, N: Unsigned> MerkleNode for FixedVector<T, N> {
+    fn chunks(&self) -> Vec<Root> {
+        sequence_chunks(self)
+    }
+
+    fn depth(&self) -> usize {
+        sequence_depth::<T>(N::to_usize())
+    }
+
+    fn child(&self, item: &PathItem) -> Result<Child<'_>, String> {
+        sequence_child(self, item)
+    }
+}
+
+impl<T: TreeHash + MerkleNode, N: Unsigned> MerkleNode for VariableList<T, N> {
+    fn chunks(&self) -> Vec<Root> {
+        sequence_chunks(self)
+    }
+
+    fn depth(&self) -> usize {
+        sequence_depth::<T>(N::to_usize())
+    }
+
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn child(&self, item: &PathItem) -> Result<Child<'_>, String> {
+        sequence_child(self, item)
+    }
+}
+
+impl<N: Unsigned> MerkleNode for BitVector<N> {
+    fn chunks(&self) -> Vec<Root> {
+        pack(self.as_slice())
+    }
+
+    fn depth(&self) -> usize {
+        depth_of(N::to_usize().div_ceil(BITS_PER_CHUNK))
+    }
+
+    fn child(&self, item: &PathItem) -> Result<Child<'_>, String> {
+        bit_child(item, self.len())
+    }
+}
+
+impl<N: Unsigned> MerkleNode for BitList<N> {
+    fn chunks(&self) -> Vec<Root> {
+        pack(self.as_slice())
+    }
+
+    fn depth(&self) -> usize {
+        depth_of(N::to_usize().div_ceil(BITS_PER_CHUNK))
+    }
+
+    fn length(&self) -> Option<usize> {
+        Some(self.len())
+    }
+
+    fn child(&self, item: &PathItem) -> Result<Child<'_>, String> {
+        bit_child(item, self.len())
+    }
+}
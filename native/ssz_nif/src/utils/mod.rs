@@ -1,6 +1,8 @@
+pub(crate) mod errors;
 pub(crate) mod from_elx;
 pub(crate) mod from_ssz;
 pub(crate) mod helpers;
+pub(crate) mod merkle_node;
 
 /// New containers should be added to this macro
 macro_rules! schema_match {
@@ -61,6 +63,16 @@ macro_rules! schema_match {
                 ConsolidationRequest,
                 ExecutionRequests<C>,
                 SingleAttestation,
+                StatusMessageV2,
+                MetadataV3<C>,
+                DataColumnSidecar<C>,
+                DataColumnsByRootIdentifier<C>,
+                MatrixEntry<C>,
+                LightClientHeader<C>,
+                LightClientBootstrap<C>,
+                LightClientUpdate<C>,
+                LightClientFinalityUpdate<C>,
+                LightClientOptimisticUpdate<C>,
             }
         )
     };
@@ -72,13 +84,123 @@ macro_rules! schema_match_impl {
             $(
                 stringify!($t) => $crate::utils::config_match!($config, $fun, $args, $t $(<$_c>)?),
             )*
-            _ => Err(rustler::Error::BadArg),
+            other => Err($crate::utils::errors::unknown_schema(other)),
+        }
+    };
+}
+
+/// Like [`schema_match`], for the containers of a fork. Containers that changed
+/// after a fork should be added to its list, and the rest use their Electra
+/// definition.
+macro_rules! fork_schema_match {
+    ($fork:expr, $schema:expr, $config:expr, $fun:ident, $args:tt) => {
+        match $fork {
+            "phase0" => $crate::utils::fork_schema_match_impl!(
+                phase0, ($schema, $config, $fun, $args) => {
+                    Metadata<C>,
+                    BeaconState<C>,
+                    BeaconBlockBody<C>,
+                    BeaconBlock<C>,
+                    SignedBeaconBlock<C>,
+                    Attestation<C>,
+                    IndexedAttestation<C>,
+                    AttesterSlashing<C>,
+                    AggregateAndProof<C>,
+                    SignedAggregateAndProof<C>,
+                }
+            ),
+            "altair" => $crate::utils::fork_schema_match_impl!(
+                altair, ($schema, $config, $fun, $args) => {
+                    BeaconState<C>,
+                    BeaconBlockBody<C>,
+                    BeaconBlock<C>,
+                    SignedBeaconBlock<C>,
+                    Attestation<C>,
+                    IndexedAttestation<C>,
+                    AttesterSlashing<C>,
+                    AggregateAndProof<C>,
+                    SignedAggregateAndProof<C>,
+                }
+            ),
+            "bellatrix" => $crate::utils::fork_schema_match_impl!(
+                bellatrix, ($schema, $config, $fun, $args) => {
+                    BeaconState<C>,
+                    BeaconBlockBody<C>,
+                    BeaconBlock<C>,
+                    SignedBeaconBlock<C>,
+                    ExecutionPayload<C>,
+                    ExecutionPayloadHeader<C>,
+                    Attestation<C>,
+                    IndexedAttestation<C>,
+                    AttesterSlashing<C>,
+                    AggregateAndProof<C>,
+                    SignedAggregateAndProof<C>,
+                }
+            ),
+            "capella" => $crate::utils::fork_schema_match_impl!(
+                capella, ($schema, $config, $fun, $args) => {
+                    BeaconState<C>,
+                    BeaconBlockBody<C>,
+                    BeaconBlock<C>,
+                    SignedBeaconBlock<C>,
+                    ExecutionPayload<C>,
+                    ExecutionPayloadHeader<C>,
+                    Attestation<C>,
+                    IndexedAttestation<C>,
+                    AttesterSlashing<C>,
+                    AggregateAndProof<C>,
+                    SignedAggregateAndProof<C>,
+                }
+            ),
+            "deneb" => $crate::utils::fork_schema_match_impl!(
+                deneb, ($schema, $config, $fun, $args) => {
+                    BeaconState<C>,
+                    BeaconBlockBody<C>,
+                    BeaconBlock<C>,
+                    SignedBeaconBlock<C>,
+                    Attestation<C>,
+                    IndexedAttestation<C>,
+                    AttesterSlashing<C>,
+                    AggregateAndProof<C>,
+                    SignedAggregateAndProof<C>,
+                }
+            ),
+            "electra" => $crate::utils::schema_match!($schema, $config, $fun, $args),
+            other => Err($crate::utils::errors::unknown_fork(other)),
+        }
+    };
+}
+
+macro_rules! fork_schema_match_impl {
+    ($fork:ident, ($schema:expr, $config:expr, $fun:ident, $args:tt) => { $($t:ident<C>),* $(,)? }) => {
+        match $schema {
+            $(
+                stringify!($t) => $crate::utils::config_match!($config, $fun, $args, $fork::$t<C>),
+            )*
+            _ => $crate::utils::schema_match!($schema, $config, $fun, $args),
         }
     };
 }
 
 /// New configs should be added to this macro
 macro_rules! config_match {
+    ($config:expr, $fun:ident, $args:tt, $fork:ident::$t:ident<C>) => {
+        match $config {
+            "mainnet" => $crate::utils::helpers::$fun::<
+                elx_types::$fork::$t,
+                ssz_types::$fork::$t<$crate::ssz_types::config::Mainnet>,
+            >($args),
+            "minimal" => $crate::utils::helpers::$fun::<
+                elx_types::$fork::$t,
+                ssz_types::$fork::$t<$crate::ssz_types::config::Minimal>,
+            >($args),
+            "gnosis" => $crate::utils::helpers::$fun::<
+                elx_types::$fork::$t,
+                ssz_types::$fork::$t<$crate::ssz_types::config::Gnosis>,
+            >($args),
+            other => Err($crate::utils::errors::unknown_config(other)),
+        }
+    };
     ($config:expr, $fun:ident, $args:tt, $t:ident<C>) => {
         match $config {
             "mainnet" => $crate::utils::helpers::$fun::<
@@ -93,7 +215,7 @@ macro_rules! config_match {
                 elx_types::$t,
                 ssz_types::$t<$crate::ssz_types::config::Gnosis>,
             >($args),
-            _ => Err(rustler::Error::BadArg),
+            other => Err($crate::utils::errors::unknown_config(other)),
         }
     };
     ($config:expr, $fun:ident, $args:tt, $t:ident) => {
@@ -101,14 +223,29 @@ macro_rules! config_match {
             "mainnet" | "minimal" | "gnosis" => {
                 $crate::utils::helpers::$fun::<elx_types::$t, ssz_types::$t>($args)
             }
-            _ => Err(rustler::Error::BadArg),
+            other => Err($crate::utils::errors::unknown_config(other)),
         }
     };
 }
 
+/// Calls a function generic over the config, for NIFs that don't work on a schema
+macro_rules! preset_match {
+    ($config:expr, $fun:ident($($arg:expr),*)) => {
+        match $config {
+            "mainnet" => $fun::<$crate::ssz_types::config::Mainnet>($($arg),*),
+            "minimal" => $fun::<$crate::ssz_types::config::Minimal>($($arg),*),
+            "gnosis" => $fun::<$crate::ssz_types::config::Gnosis>($($arg),*),
+            other => Err($crate::utils::errors::unknown_config(other)),
+        }
+    };
+}
+
+/// The SSZ type is the one with the same name in [`ssz_types`](crate::ssz_types),
+/// or in its `fork` module if the struct starts with `fork = <module>;`
 macro_rules! gen_struct_with_config {
     // Named-Struct
     (
+        $( fork = $fork:ident; )?
         $( #[$meta:meta] )*
     //  ^~~~attributes~~~~^
         $vis:vis struct $name:ident$(< $( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+ >)? {
@@ -128,8 +265,8 @@ macro_rules! gen_struct_with_config {
                 $field_vis $field_name : $field_ty
             ),*
         }
-        impl<'a, C: $crate::ssz_types::config::Config> $crate::utils::from_ssz::FromSsz<'a, $crate::ssz_types::$name<C>> for $name$(< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? {
-            fn from(ssz: $crate::ssz_types::$name<C>, env: ::rustler::Env<'a>) -> Self {
+        impl<'a, C: $crate::ssz_types::config::Config> $crate::utils::from_ssz::FromSsz<'a, $crate::ssz_types::$($fork::)?$name<C>> for $name$(< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? {
+            fn from(ssz: $crate::ssz_types::$($fork::)?$name<C>, env: ::rustler::Env<'a>) -> Self {
                 $(
                     let $field_name = $crate::utils::from_ssz::FromSsz::from(ssz.$field_name, env);
                 )*
@@ -140,7 +277,7 @@ macro_rules! gen_struct_with_config {
         }
 
         impl< $($( $lt $( : $clt $(+ $dlt )* )? ),+,)? C: $crate::ssz_types::config::Config>
-        $crate::utils::from_elx::FromElx<$name$(< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)?> for $crate::ssz_types::$name<C> {
+        $crate::utils::from_elx::FromElx<$name$(< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)?> for $crate::ssz_types::$($fork::)?$name<C> {
             fn from(elx: $name) -> Result<Self, $crate::utils::from_elx::FromElxError> {
                 $(
                     let $field_name = $crate::utils::from_elx::FromElx::from(elx.$field_name)?;
@@ -150,6 +287,12 @@ macro_rules! gen_struct_with_config {
                 })
             }
         }
+
+        $crate::utils::merkle_node_impl!(
+            impl<C: $crate::ssz_types::config::Config> $crate::ssz_types::$($fork::)?$name<C> {
+                $($field_name),*
+            }
+        );
     }
 }
 
@@ -197,10 +340,44 @@ macro_rules! gen_struct {
                 })
             }
         }
+
+        $crate::utils::merkle_node_impl!(impl<> $crate::ssz_types::$name { $($field_name),* });
     }
 }
 
+/// Implements [`MerkleNode`](merkle_node::MerkleNode) for a container with the given fields
+macro_rules! merkle_node_impl {
+    (impl<$($generic:ident: $bound:path)?> $t:ty { $($field_name:ident),* }) => {
+        impl<$($generic: $bound)?> $crate::utils::merkle_node::MerkleNode for $t {
+            fn chunks(&self) -> Vec<$crate::ssz_types::Root> {
+                vec![$(::tree_hash::TreeHash::tree_hash_root(&self.$field_name).0),*]
+            }
+
+            fn depth(&self) -> usize {
+                $crate::utils::merkle_node::depth_of([$(stringify!($field_name)),*].len())
+            }
+
+            fn child(
+                &self,
+                item: &$crate::utils::merkle_node::PathItem,
+            ) -> Result<$crate::utils::merkle_node::Child<'_>, String> {
+                $crate::utils::merkle_node::container_child(
+                    item,
+                    &[$((
+                        stringify!($field_name),
+                        &self.$field_name as &dyn $crate::utils::merkle_node::MerkleNode,
+                    )),*],
+                )
+            }
+        }
+    };
+}
+
 pub(crate) use config_match;
+pub(crate) use fork_schema_match;
+pub(crate) use fork_schema_match_impl;
+pub(crate) use merkle_node_impl;
+pub(crate) use preset_match;
 pub(crate) use schema_match;
 pub(crate) use schema_match_impl;
 
@@ -0,0 +1,174 @@
+//! Versioned hashes of KZG commitments, and their extraction from the EIP-4844
+//! transactions of an execution payload. Only the RLP needed to reach the
+//! `blob_versioned_hashes` field is decoded.
+
+use ethereum_hashing::hash_fixed;
+use rustler::{Binary, Encoder, Env, NifResult, Term};
+
+use crate::ssz_types::Root;
+use crate::utils::errors::{invalid_transaction, invalid_value};
+use crate::utils::helpers::bytes_to_binary;
+
+mod atoms {
+    use rustler::atoms;
+
+    atoms! {
+        ok,
+    }
+}
+
+const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+const BYTES_PER_COMMITMENT: usize = 48;
+const BLOB_TX_TYPE: u8 = 0x03;
+/// Fields of a blob transaction: `[chain_id, nonce, max_priority_fee_per_gas,
+/// max_fee_per_gas, gas_limit, to, value, data, access_list, max_fee_per_blob_gas,
+/// blob_versioned_hashes, y_parity, r, s]`.
+const BLOB_TX_FIELDS: usize = 14;
+const BLOB_VERSIONED_HASHES_INDEX: usize = 10;
+
+fn kzg_commitment_to_versioned_hash(commitment: &[u8]) -> Root {
+    let mut hash = hash_fixed(commitment);
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+enum Item<'a> {
+    Bytes(&'a [u8]),
+    List(&'a [u8]),
+}
+
+/// Splits the first RLP item off `input`, returning its payload and the rest.
+fn split_item(input: &[u8]) -> Result<(Item, &[u8]), String> {
+    let (&prefix, rest) = input.split_first().ok_or("Missing RLP item")?;
+    let (is_list, short_base, long_base) = match prefix {
+        0x00..=0x7f => return Ok((Item::Bytes(&input[..1]), rest)),
+        0x80..=0xbf => (false, 0x80, 0xb7),
+        0xc0..=0xff => (true, 0xc0, 0xf7),
+    };
+    let (length, rest) = if prefix <= long_base {
+        ((prefix - short_base) as usize, rest)
+    } else {
+        let length_of_length = (prefix - long_base) as usize;
+        if length_of_length > rest.len() || length_of_length > std::mem::size_of::<usize>() {
+            return Err(format!("Invalid length of length: {length_of_length}"));
+        }
+        let (length, rest) = rest.split_at(length_of_length);
+        if length[0] == 0 {
+            return Err("Length with leading zeros".to_owned());
+        }
+        let length = length
+            .iter()
+            .fold(0, |length, byte| (length << 8) | *byte as usize);
+        (length, rest)
+    };
+    if length > rest.len() {
+        return Err(format!("Item of {length} bytes, but {} left", rest.len()));
+    }
+    let (payload, rest) = rest.split_at(length);
+    let item = if is_list {
+        Item::List(payload)
+    } else {
+        Item::Bytes(payload)
+    };
+    Ok((item, rest))
+}
+
+fn list_items(mut payload: &[u8]) -> Result<Vec<Item>, String> {
+    let mut items = vec![];
+    while !payload.is_empty() {
+        let (item, rest) = split_item(payload)?;
+        items.push(item);
+        payload = rest;
+    }
+    Ok(items)
+}
+
+/// Returns the versioned hashes of a transaction, which has none unless it's a
+/// blob transaction: `0x03 || rlp(fields)`.
+fn tx_versioned_hashes(tx: &[u8]) -> Result<Vec<Root>, String> {
+    let payload = match tx.split_first() {
+        Some((&BLOB_TX_TYPE, payload)) => payload,
+        _ => return Ok(vec![]),
+    };
+    let (Item::List(fields), []) = split_item(payload)? else {
+        return Err("The transaction isn't a single RLP list".to_owned());
+    };
+    let fields = list_items(fields)?;
+    if fields.len() != BLOB_TX_FIELDS {
+        return Err(format!(
+            "Expected {BLOB_TX_FIELDS} fields, got {}",
+            fields.len()
+        ));
+    }
+    let Item::List(hashes) = fields[BLOB_VERSIONED_HASHES_INDEX] else {
+        return Err("blob_versioned_hashes isn't a list".to_owned());
+    };
+    list_items(hashes)?
+        .into_iter()
+        .map(|hash| match hash {
+            Item::Bytes(hash) => hash
+                .try_into()
+                .map_err(|_| format!("Versioned hash of {} bytes", hash.len())),
+            Item::List(_) => Err("Versioned hash isn't a byte string".to_owned()),
+        })
+        .collect()
+}
+
+/// Returns the versioned hashes of all the blob transactions, in order.
+fn payload_versioned_hashes(transactions: &[Binary]) -> NifResult<Vec<Root>> {
+    let mut hashes = vec![];
+    for (index, tx) in transactions.iter().enumerate() {
+        let tx_hashes = tx_versioned_hashes(tx)
+            .map_err(|detail| invalid_transaction(format!("Transaction {index}: {detail}")))?;
+        hashes.extend(tx_hashes);
+    }
+    Ok(hashes)
+}
+
+fn encode_hashes<'env>(env: Env<'env>, hashes: &[Root]) -> Term<'env> {
+    let hashes: Vec<Binary> = hashes
+        .iter()
+        .map(|hash| bytes_to_binary(env, hash))
+        .collect();
+    (atoms::ok(), hashes).encode(env)
+}
+
+#[rustler::nif]
+fn kzg_commitments_to_versioned_hashes<'env>(
+    env: Env<'env>,
+    kzg_commitments: Vec<Binary>,
+) -> NifResult<Term<'env>> {
+    let hashes = kzg_commitments
+        .iter()
+        .map(|commitment| match commitment.len() {
+            BYTES_PER_COMMITMENT => Ok(kzg_commitment_to_versioned_hash(commitment)),
+            length => Err(invalid_value(
+                format!("Commitment of {length} bytes, expected {BYTES_PER_COMMITMENT}").into(),
+            )),
+        })
+        .collect::<NifResult<Vec<_>>>()?;
+    Ok(encode_hashes(env, &hashes))
+}
+
+#[rustler::nif(schedule = "DirtyCpu")]
+fn blob_versioned_hashes<'env>(env: Env<'env>, transactions: Vec<Binary>) -> NifResult<Term<'env>> {
+    let hashes = payload_versioned_hashes(&transactions)?;
+    Ok(encode_hashes(env, &hashes))
+}
+
+// Returns whether the versioned hashes of the blob transactions are, in order,
+// the given ones. This is `is_valid_versioned_hashes` from the spec.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn verify_blob_versioned_hashes<'env>(
+    env: Env<'env>,
+    transactions: Vec<Binary>,
+    versioned_hashes: Vec<Binary>,
+) -> NifResult<Term<'env>> {
+    let hashes = payload_versioned_hashes(&transactions)?;
+    let valid = hashes.len() == versioned_hashes.len()
+        && hashes
+            .iter()
+            .zip(&versioned_hashes)
+            .all(|(hash, expected)| hash == expected.as_slice());
+    Ok((atoms::ok(), valid).encode(env))
+}
@@ -0,0 +1,68 @@
+//! Worker pool shared by the NIF libraries that run verification jobs off the
+//! BEAM schedulers. It knows nothing about rustler: each library wraps its jobs
+//! so that they send their result to the calling process.
+//!
+//! The queue is bounded, so a caller submitting faster than the workers keep
+//! up gets an error instead of piling up jobs in memory.
+
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+pub type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Jobs that may wait in the queue for each worker.
+pub const QUEUED_JOBS_PER_WORKER: usize = 64;
+
+/// Returned when a job is submitted to a full queue.
+#[derive(Debug)]
+pub struct QueueFull;
+
+pub struct Pool {
+    sender: SyncSender<Job>,
+}
+
+impl Pool {
+    /// Starts `workers` threads named after `name`, or one per core if it's
+    /// `None` or zero.
+    pub fn start(name: &str, workers: Option<usize>) -> io::Result<Self> {
+        let workers = workers
+            .filter(|&workers| workers > 0)
+            .or_else(|| thread::available_parallelism().ok().map(usize::from))
+            .unwrap_or(1);
+        let (sender, receiver) = mpsc::sync_channel::<Job>(workers * QUEUED_JOBS_PER_WORKER);
+        let receiver = Arc::new(Mutex::new(receiver));
+        for i in 0..workers {
+            let receiver = Arc::clone(&receiver);
+            thread::Builder::new()
+                .name(format!("{}_{}", name, i))
+                .spawn(move || loop {
+                    // The lock is released before running the job
+                    let job = match receiver.lock() {
+                        Ok(receiver) => receiver.recv(),
+                        Err(_) => return,
+                    };
+                    match job {
+                        // Jobs report their own panics, this only keeps the worker alive
+                        Ok(job) => {
+                            let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                        }
+                        Err(_) => return,
+                    }
+                })?;
+        }
+        Ok(Self { sender })
+    }
+
+    /// Queues `job`, unless the queue is full.
+    pub fn try_submit(&self, job: Job) -> Result<(), QueueFull> {
+        match self.sender.try_send(job) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(QueueFull),
+            // Workers never exit while the sender is alive
+            Err(TrySendError::Disconnected(_)) => unreachable!("The pool has no workers"),
+        }
+    }
+}
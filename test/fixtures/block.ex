@@ -224,10 +224,51 @@ defmodule Fixtures.Block do
     }
   end
 
+  # `beacon_state/0` with its vectors at their full length, so that it can be
+  # SSZ-encoded
+  @spec encodable_beacon_state :: BeaconState.t()
+  def encodable_beacon_state() do
+    roots = for _ <- 1..ChainSpec.get("SLOTS_PER_HISTORICAL_ROOT"), do: Random.root()
+    mixes = List.duplicate(<<0::256>>, ChainSpec.get("EPOCHS_PER_HISTORICAL_VECTOR"))
+    pubkeys = List.duplicate(<<1::384>>, ChainSpec.get("SYNC_COMMITTEE_SIZE"))
+    committee = %Types.SyncCommittee{pubkeys: pubkeys, aggregate_pubkey: <<2::384>>}
+    validators = for _ <- 1..10, do: validator()
+
+    %{
+      beacon_state()
+      | block_roots: roots,
+        state_roots: roots,
+        validators: Aja.Vector.new(validators),
+        balances: Aja.Vector.new(1..10),
+        randao_mixes: Aja.Vector.new(mixes),
+        slashings: List.duplicate(0, ChainSpec.get("EPOCHS_PER_SLASHINGS_VECTOR")),
+        previous_epoch_participation: Aja.Vector.new(List.duplicate(0, 10)),
+        current_epoch_participation: Aja.Vector.new(List.duplicate(7, 10)),
+        justification_bits: BitVector.new(4),
+        inactivity_scores: List.duplicate(0, 10),
+        current_sync_committee: committee,
+        next_sync_committee: %{committee | aggregate_pubkey: <<3::384>>}
+    }
+  end
+
+  @spec validator :: Types.Validator.t()
+  def validator() do
+    %Types.Validator{
+      pubkey: Random.binary(48),
+      withdrawal_credentials: Random.root(),
+      effective_balance: 32_000_000_000,
+      slashed: false,
+      activation_eligibility_epoch: 0,
+      activation_epoch: 0,
+      exit_epoch: Constants.far_future_epoch(),
+      withdrawable_epoch: Constants.far_future_epoch()
+    }
+  end
+
   def beacon_state_from_file() do
     {:ok, encoded} =
       File.read!("test/fixtures/validator/proposer/beacon_state.ssz_snappy")
-      |> :snappyer.decompress()
+      |> Snappy.decompress_raw()
 
     {:ok, decoded} = SszEx.decode(encoded, BeaconState)
     {:ok, state_info} = StateInfo.from_beacon_state(decoded)
@@ -20,11 +20,16 @@ defmodule BlsTestRunner do
   defp handle_case("sign", %{message: message, privkey: private_key}, output) do
     case output do
       nil ->
-        assert {result, _error_msg} = Bls.sign(private_key, message)
-        assert result == :error
+        result =
+          with {:ok, secret_key} <- Bls.secret_key_from_bytes(private_key) do
+            Bls.sign(secret_key, message)
+          end
+
+        assert {:error, _error_msg} = result
 
       output ->
-        assert {:ok, signature} = Bls.sign(private_key, message)
+        assert {:ok, secret_key} = Bls.secret_key_from_bytes(private_key)
+        assert {:ok, signature} = Bls.sign(secret_key, message)
         assert signature == output
     end
   end
@@ -66,7 +71,7 @@ defmodule BlsTestRunner do
         assert not output
 
       {:error, reason} ->
-        assert not output, reason
+        assert not output, inspect(reason)
     end
   end
 
@@ -83,7 +88,7 @@ defmodule BlsTestRunner do
         assert not output
 
       {:error, reason} ->
-        assert not output, reason
+        assert not output, inspect(reason)
     end
   end
 
@@ -100,7 +105,7 @@ defmodule BlsTestRunner do
         assert not output
 
       {:error, reason} ->
-        assert not output, reason
+        assert not output, inspect(reason)
     end
   end
 
@@ -117,7 +122,7 @@ defmodule BlsTestRunner do
         assert not output
 
       {:error, reason} ->
-        assert not output, reason
+        assert not output, inspect(reason)
     end
   end
 end
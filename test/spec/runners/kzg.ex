@@ -100,4 +100,51 @@ defmodule KzgTestRunner do
         assert status == output
     end
   end
+
+  defp handle_case("compute_cells", %{blob: blob}, output) do
+    case output do
+      nil ->
+        assert {:error, _error_msg} = Kzg.compute_cells(blob)
+
+      output ->
+        assert {:ok, cells} = Kzg.compute_cells(blob)
+        assert cells == output
+    end
+  end
+
+  defp handle_case("compute_cells_and_kzg_proofs", %{blob: blob}, output) do
+    case output do
+      nil ->
+        assert {:error, _error_msg} = Kzg.compute_cells_and_kzg_proofs(blob)
+
+      [cells, proofs] ->
+        assert {:ok, {^cells, ^proofs}} = Kzg.compute_cells_and_kzg_proofs(blob)
+    end
+  end
+
+  defp handle_case(
+         "verify_cell_kzg_proof_batch",
+         %{commitments: commitments, cell_indices: cell_indices, cells: cells, proofs: proofs},
+         output
+       ) do
+    result = Kzg.verify_cell_kzg_proof_batch(commitments, cell_indices, cells, proofs)
+
+    case output do
+      nil -> assert {:error, _error_msg} = result
+      output -> assert result == {:ok, output}
+    end
+  end
+
+  defp handle_case(
+         "recover_cells_and_kzg_proofs",
+         %{cell_indices: cell_indices, cells: cells},
+         output
+       ) do
+    result = Kzg.recover_cells_and_kzg_proofs(cell_indices, cells)
+
+    case output do
+      nil -> assert {:error, _error_msg} = result
+      [cells, proofs] -> assert result == {:ok, {cells, proofs}}
+    end
+  end
 end
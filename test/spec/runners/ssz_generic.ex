@@ -12,7 +12,7 @@ defmodule SszGenericTestRunner do
     schema = parse_type(testcase)
 
     compressed = File.read!(case_dir <> "/serialized.ssz_snappy")
-    assert {:ok, decompressed} = :snappyer.decompress(compressed)
+    assert {:ok, decompressed} = Snappy.decompress_raw(compressed)
 
     handle_case(testcase.suite, schema, decompressed, testcase)
   end
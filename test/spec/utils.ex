@@ -56,7 +56,7 @@ defmodule SpecTestUtils do
   def read_ssz_from_optional_file!(file_path, ssz_type) do
     if File.exists?(file_path) do
       compressed = File.read!(file_path)
-      {:ok, decompressed} = :snappyer.decompress(compressed)
+      {:ok, decompressed} = Snappy.decompress_raw(compressed)
       {:ok, ssz_object} = Ssz.from_ssz(decompressed, ssz_type)
       ssz_object
     else
@@ -68,7 +68,7 @@ defmodule SpecTestUtils do
   def read_ssz_ex_from_optional_file!(file_path, ssz_type) do
     if File.exists?(file_path) do
       compressed = File.read!(file_path)
-      {:ok, decompressed} = :snappyer.decompress(compressed)
+      {:ok, decompressed} = Snappy.decompress_raw(compressed)
       {:ok, ssz_object} = SszEx.decode(decompressed, ssz_type)
       ssz_object
     else
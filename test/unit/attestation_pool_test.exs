@@ -0,0 +1,108 @@
+defmodule Unit.AttestationPoolTest do
+  use ExUnit.Case
+
+  alias LambdaEthereumConsensus.Utils.BitList
+  alias LambdaEthereumConsensus.Utils.BitVector
+
+  @committee_length 8
+  @signing_root <<7::256>>
+
+  setup_all do
+    Application.fetch_env!(:lambda_ethereum_consensus, ChainSpec)
+    |> Keyword.put(:config, MainnetConfig)
+    |> then(&Application.put_env(:lambda_ethereum_consensus, ChainSpec, &1))
+  end
+
+  setup do
+    {:ok, pool: AttestationPool.new()}
+  end
+
+  defp attestation_data(slot) do
+    %Types.AttestationData{
+      slot: slot,
+      index: 0,
+      beacon_block_root: <<1::256>>,
+      source: %Types.Checkpoint{epoch: 0, root: <<2::256>>},
+      target: %Types.Checkpoint{epoch: 1, root: <<3::256>>}
+    }
+  end
+
+  defp signature(index) do
+    {:ok, privkey} = Bls.secret_key_from_bytes(<<index + 1::256>>)
+    {:ok, signature} = Bls.sign(privkey, @signing_root)
+    signature
+  end
+
+  defp attestation(data, indices) do
+    {:ok, signature} = indices |> Enum.map(&signature/1) |> Bls.aggregate()
+    committees = ChainSpec.get("MAX_COMMITTEES_PER_SLOT")
+
+    %Types.Attestation{
+      aggregation_bits: @committee_length |> BitList.zero() |> BitList.set(indices),
+      data: data,
+      signature: signature,
+      committee_bits: committees |> BitVector.new() |> BitVector.set(0)
+    }
+  end
+
+  defp single_attestation(data, index) do
+    %Types.SingleAttestation{
+      committee_index: 0,
+      attester_index: 100 + index,
+      data: data,
+      signature: signature(index)
+    }
+  end
+
+  test "merges disjoint attestations", %{pool: pool} do
+    data = attestation_data(10)
+    root = Ssz.hash_tree_root!(data)
+
+    assert {:ok, nil} = AttestationPool.get_aggregate(pool, root)
+    assert {:ok, :new} = AttestationPool.insert(pool, attestation(data, [0, 1]))
+    assert {:ok, :aggregated} = AttestationPool.insert(pool, attestation(data, [2]))
+    assert {:ok, :already_known} = AttestationPool.insert(pool, attestation(data, [1, 2]))
+    # Overlaps with the first aggregate without being contained in it
+    assert {:ok, :new} = AttestationPool.insert(pool, attestation(data, [2, 3]))
+
+    assert {:ok, aggregate} = AttestationPool.get_aggregate(pool, root)
+    assert aggregate == attestation(data, [0, 1, 2])
+  end
+
+  test "aggregates single attestations", %{pool: pool} do
+    data = attestation_data(10)
+    root = Ssz.hash_tree_root!(data)
+
+    for index <- [4, 6] do
+      single = single_attestation(data, index)
+      assert {:ok, _} = AttestationPool.insert_single(pool, single, index, @committee_length)
+    end
+
+    assert {:ok, aggregate} = AttestationPool.get_aggregate(pool, root)
+    assert aggregate == attestation(data, [4, 6])
+  end
+
+  test "rejects invalid attestations", %{pool: pool} do
+    data = attestation_data(10)
+    single = single_attestation(data, 0)
+
+    assert {:error, {:invalid_value, _}} =
+             AttestationPool.insert_single(pool, single, @committee_length, @committee_length)
+
+    invalid_signature = %{attestation(data, [0]) | signature: <<1::768>>}
+
+    assert {:error, {:invalid_signature_encoding, _}} =
+             AttestationPool.insert(pool, invalid_signature)
+  end
+
+  test "prunes old slots", %{pool: pool} do
+    assert {:ok, :new} = AttestationPool.insert(pool, attestation(attestation_data(9), [0]))
+    assert {:ok, :new} = AttestationPool.insert(pool, attestation(attestation_data(10), [0]))
+
+    assert AttestationPool.prune(pool, 10) == 1
+    root = Ssz.hash_tree_root!(attestation_data(9))
+    assert {:ok, nil} = AttestationPool.get_aggregate(pool, root)
+    root = Ssz.hash_tree_root!(attestation_data(10))
+    assert {:ok, %Types.Attestation{}} = AttestationPool.get_aggregate(pool, root)
+  end
+end
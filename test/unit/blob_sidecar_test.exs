@@ -0,0 +1,92 @@
+defmodule Unit.BlobSidecarTest do
+  use ExUnit.Case
+
+  alias Fixtures.Block
+  alias LambdaEthereumConsensus.Validator.BlockBuilder
+
+  setup_all do
+    Application.fetch_env!(:lambda_ethereum_consensus, ChainSpec)
+    |> Keyword.put(:config, MainnetConfig)
+    |> then(&Application.put_env(:lambda_ethereum_consensus, ChainSpec, &1))
+  end
+
+  setup do
+    blobs = for i <- 1..3, do: <<i::256, 0::size((131_072 - 32) * 8)>>
+    {:ok, bundle} = Kzg.blobs_bundle(blobs)
+    body = %{Block.beacon_block_body() | blob_kzg_commitments: bundle.commitments}
+    block = %{Block.signed_beacon_block() | message: %{Block.beacon_block() | body: body}}
+    {:ok, sidecars} = BlockBuilder.generate_sidecars(block, bundle)
+    header = hd(sidecars).signed_block_header.message
+
+    {:ok, sidecars: sidecars, block_root: Ssz.hash_tree_root!(header)}
+  end
+
+  defp decode(sidecar, fork \\ :electra) do
+    {:ok, bin} = Ssz.to_ssz(sidecar)
+
+    with {:ok, {_sidecar, block_root, valid?}} <- Ssz.decode_blob_sidecar(bin, fork) do
+      {:ok, {block_root, valid?}}
+    end
+  end
+
+  defp verify_kzg_proof(sidecar) do
+    {:ok, bin} = Ssz.to_ssz(sidecar)
+    Kzg.verify_blob_sidecar_kzg_proof(bin)
+  end
+
+  test "valid sidecars", %{sidecars: sidecars, block_root: block_root} do
+    for sidecar <- sidecars do
+      {:ok, bin} = Ssz.to_ssz(sidecar)
+      assert Ssz.decode_blob_sidecar(bin) == {:ok, {sidecar, block_root, true}}
+      assert verify_kzg_proof(sidecar) == {:ok, true}
+    end
+  end
+
+  test "invalid proofs", %{sidecars: [first, second | _], block_root: block_root} do
+    assert decode(%{first | index: 1}) == {:ok, {block_root, false}}
+
+    [node | rest] = first.kzg_commitment_inclusion_proof
+    tampered = %{first | kzg_commitment_inclusion_proof: [:crypto.exor(node, <<1::256>>) | rest]}
+    assert decode(tampered) == {:ok, {block_root, false}}
+
+    assert verify_kzg_proof(%{first | kzg_proof: second.kzg_proof}) == {:ok, false}
+    assert {:error, _} = verify_kzg_proof(%{first | kzg_proof: <<0xFF::384>>})
+  end
+
+  test "only forks with blobs have sidecars", %{sidecars: [sidecar | _], block_root: root} do
+    assert decode(sidecar, :deneb) == {:ok, {root, true}}
+    assert {:error, {:invalid_value, _}} = decode(sidecar, :capella)
+  end
+
+  test "computes missing KZG proofs", %{sidecars: sidecars} do
+    [sidecar | _] = sidecars
+    block = %{Block.signed_beacon_block() | signature: sidecar.signed_block_header.signature}
+    block = put_in(block.message.body.blob_kzg_commitments, [sidecar.kzg_commitment])
+    bundle = %Types.BlobsBundle{blobs: [sidecar.blob], commitments: [], proofs: []}
+
+    assert {:ok, [built]} = BlockBuilder.generate_sidecars(block, bundle)
+    assert built.kzg_proof == sidecar.kzg_proof
+  end
+
+  test "rejects bundles that don't match the block", %{sidecars: [sidecar | _]} do
+    bundle = %Types.BlobsBundle{blobs: [sidecar.blob], commitments: [], proofs: []}
+
+    assert {:error, {:invalid_value, _}} =
+             BlockBuilder.generate_sidecars(Block.signed_beacon_block(), bundle)
+
+    block = Block.signed_beacon_block()
+    block = put_in(block.message.body.blob_kzg_commitments, [sidecar.kzg_commitment])
+    short_blob = binary_part(sidecar.blob, 0, 32)
+    bundle = %{bundle | blobs: [short_blob], proofs: [sidecar.kzg_proof]}
+    assert {:error, {:invalid_value, _}} = BlockBuilder.generate_sidecars(block, bundle)
+  end
+
+  test "invalid encoding", %{sidecars: [sidecar | _]} do
+    assert {:error, {:invalid_ssz, _}} = Ssz.decode_blob_sidecar(<<0::80>>)
+    assert Kzg.verify_blob_sidecar_kzg_proof(<<0::80>>) == {:error, {:blob_length, 2, 131_072}}
+
+    {:ok, bin} = Ssz.to_ssz(sidecar)
+    truncated = binary_part(bin, 0, 8 + 131_072 + 48 + 10)
+    assert Kzg.verify_blob_sidecar_kzg_proof(truncated) == {:error, {:proof_length, 10, 48}}
+  end
+end
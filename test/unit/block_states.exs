@@ -46,7 +46,7 @@ defmodule BlockStatesTest do
   defp get_state() do
     {:ok, encoded} =
       File.read!("test/fixtures/validator/proposer/beacon_state.ssz_snappy")
-      |> :snappyer.decompress()
+      |> Snappy.decompress_raw()
 
     {:ok, decoded} = SszEx.decode(encoded, BeaconState)
     {encoded, decoded}
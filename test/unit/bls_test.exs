@@ -14,7 +14,8 @@ defmodule BlsTest do
 
     test "returns false for invalid public key" do
       invalid_public_key = <<0::384>>
-      assert Bls.key_validate(invalid_public_key) == {:error, "BlstError(BLST_BAD_ENCODING)"}
+      assert Bls.key_validate(invalid_public_key) ==
+               {:error, {:invalid_pubkey_encoding, "BLST_BAD_ENCODING"}}
     end
   end
 
@@ -31,7 +32,228 @@ defmodule BlsTest do
           case: :mixed
         )
 
-      assert Bls.derive_pubkey(private_key) == {:ok, valid_public_key}
+      {:ok, secret_key} = Bls.secret_key_from_bytes(private_key)
+      assert Bls.derive_pubkey(secret_key) == {:ok, valid_public_key}
+    end
+  end
+
+  describe "Secret key handles" do
+    test "round-trip through export" do
+      {:ok, secret_key} = Bls.secret_key_from_bytes(<<7::256>>)
+      assert is_reference(secret_key)
+      assert Bls.export_secret_key(secret_key) == <<7::256>>
+    end
+
+    test "reject invalid keys" do
+      assert Bls.secret_key_from_bytes(<<0::256>>) == {:error, :zero_secret_key}
+      assert Bls.secret_key_from_bytes(<<1::128>>) == {:error, {:secret_key_length, 16, 32}}
+    end
+  end
+
+  describe "Errors" do
+    setup do
+      {:ok, pubkey} = Bls.derive_pubkey(secret_key(1))
+      {:ok, signature} = Bls.sign(secret_key(1), <<1::256>>)
+      {:ok, pubkey: pubkey, signature: signature}
+    end
+
+    test "report the received and expected lengths", %{pubkey: pubkey, signature: signature} do
+      assert Bls.sign(secret_key(1), <<1::128>>) == {:error, {:message_length, 16, 32}}
+      assert Bls.verify(<<1::256>>, <<1::256>>, signature) == {:error, {:pubkey_length, 32, 48}}
+      assert Bls.verify(pubkey, <<1::256>>, <<1::256>>) == {:error, {:signature_length, 32, 96}}
+
+      assert Bls.aggregate_verify([pubkey], [<<1::128>>], signature) ==
+               {:error, {:message_length, 16, 32}}
+    end
+
+    test "reject the point at infinity as a public key", %{signature: signature} do
+      infinity = <<0xC0, 0::376>>
+      assert Bls.verify(infinity, <<1::256>>, signature) == {:error, :infinity_pubkey}
+      assert Bls.key_validate(infinity) == {:error, :infinity_pubkey}
+    end
+
+    test "reject invalid encodings", %{pubkey: pubkey} do
+      assert {:error, {:invalid_signature_encoding, _}} =
+               Bls.verify(pubkey, <<1::256>>, <<1::768>>)
+    end
+
+    test "reject empty lists" do
+      assert Bls.aggregate([]) == {:error, :empty_signatures}
+      assert Bls.eth_aggregate_pubkeys([]) == {:error, :empty_pubkeys}
+    end
+  end
+
+  describe "Asynchronous verification" do
+    test "sends the result to the caller" do
+      message = <<1::256>>
+      {:ok, pubkey} = Bls.derive_pubkey(secret_key(1))
+      {:ok, signature} = Bls.sign(secret_key(1), message)
+
+      job = Bls.verify_async(pubkey, message, signature)
+      assert is_reference(job)
+      assert VerificationPool.await(job) == {:ok, true}
+
+      job = Bls.fast_aggregate_verify_async([pubkey], <<2::256>>, signature)
+      assert VerificationPool.await(job) == {:ok, false}
+
+      job = Bls.verify_async(pubkey, <<1::128>>, signature)
+      assert VerificationPool.await(job) == {:error, {:message_length, 16, 32}}
+    end
+
+    test "runs jobs concurrently" do
+      sets = for i <- 1..8, do: signature_set(secret_key(i), <<i::256>>)
+
+      jobs =
+        for i <- 0..7 do
+          sets
+          |> List.update_at(i, &put_elem(&1, 1, <<0::256>>))
+          |> Bls.verify_signature_sets_async()
+        end
+
+      for {job, i} <- Enum.with_index(jobs) do
+        assert VerificationPool.await(job) == {:ok, {:invalid, i}}
+      end
+    end
+  end
+
+  defp secret_key(i) do
+    {:ok, secret_key} = Bls.secret_key_from_bytes(<<i::256>>)
+    secret_key
+  end
+
+  defp signature_set(private_key, message) do
+    {:ok, pubkey} = Bls.derive_pubkey(private_key)
+    {:ok, signature} = Bls.sign(private_key, message)
+    {[pubkey], message, signature}
+  end
+
+  describe "verify_signature_sets" do
+    setup do
+      sets =
+        for i <- 1..4 do
+          signature_set(secret_key(i), <<i + 100::256>>)
+        end
+
+      {:ok, sets: sets}
+    end
+
+    test "accepts a batch of valid sets", %{sets: sets} do
+      assert Bls.verify_signature_sets(sets) == {:ok, :valid}
+    end
+
+    test "accepts an empty batch" do
+      assert Bls.verify_signature_sets([]) == {:ok, :valid}
+    end
+
+    test "returns the index of the invalid set", %{sets: sets} do
+      {pubkeys, _message, signature} = Enum.at(sets, 2)
+      sets = List.replace_at(sets, 2, {pubkeys, <<0::256>>, signature})
+
+      assert Bls.verify_signature_sets(sets) == {:ok, {:invalid, 2}}
+    end
+  end
+
+  describe "pubkey cache" do
+    setup do
+      private_keys = for i <- 1..4, do: secret_key(i)
+      pubkeys = Enum.map(private_keys, &elem(Bls.derive_pubkey(&1), 1))
+      cache = Bls.pubkey_cache_new()
+      {:ok, 4} = Bls.pubkey_cache_extend(cache, 0, pubkeys)
+      {:ok, cache: cache, private_keys: private_keys, pubkeys: pubkeys}
+    end
+
+    test "looks up keys by index and index by key", %{cache: cache, pubkeys: pubkeys} do
+      assert Bls.pubkey_cache_get(cache, 2) == {:ok, Enum.at(pubkeys, 2)}
+      assert Bls.pubkey_cache_index_of(cache, Enum.at(pubkeys, 3)) == {:ok, 3}
+      assert Bls.pubkey_cache_index_of(cache, <<0::384>>) == {:ok, nil}
+    end
+
+    test "skips keys already present when extending", %{cache: cache, pubkeys: pubkeys} do
+      {:ok, pubkey} = Bls.derive_pubkey(secret_key(5))
+
+      assert Bls.pubkey_cache_extend(cache, 2, Enum.drop(pubkeys, 2) ++ [pubkey]) == {:ok, 5}
+      assert Bls.pubkey_cache_index_of(cache, pubkey) == {:ok, 4}
+      assert Bls.pubkey_cache_extend(cache, 6, [pubkey]) ==
+               {:error, {:non_contiguous_extend, 5, 6}}
+    end
+
+    test "verifies aggregate signatures by index", %{cache: cache, private_keys: private_keys} do
+      message = <<42::256>>
+
+      {:ok, signature} =
+        private_keys
+        |> Enum.take(3)
+        |> Enum.map(&elem(Bls.sign(&1, message), 1))
+        |> Bls.aggregate()
+
+      assert Bls.fast_aggregate_verify_indices(cache, [0, 1, 2], message, signature) ==
+               {:ok, true}
+
+      assert Bls.fast_aggregate_verify_indices(cache, [0, 1, 3], message, signature) ==
+               {:ok, false}
+
+      assert Bls.fast_aggregate_verify_indices(cache, [0, 9], message, signature) ==
+               {:error, {:unknown_validator_index, 9}}
+    end
+
+    test "verifies signature sets by index", %{cache: cache, private_keys: private_keys} do
+      sets =
+        for {indices, i} <- Enum.with_index([[0, 1], [2], [1, 3]]) do
+          message = <<i::256>>
+
+          {:ok, signature} =
+            indices
+            |> Enum.map(&elem(Bls.sign(Enum.at(private_keys, &1), message), 1))
+            |> Bls.aggregate()
+
+          {indices, message, signature}
+        end
+
+      assert Bls.verify_signature_sets_indices(cache, sets) == {:ok, :valid}
+
+      invalid_sets = List.update_at(sets, 1, fn {_, message, sig} -> {[3], message, sig} end)
+      assert Bls.verify_signature_sets_indices(cache, invalid_sets) == {:ok, {:invalid, 1}}
+
+      {_, message, signature} = hd(sets)
+
+      assert Bls.verify_signature_sets_indices(cache, [{[9], message, signature}]) ==
+               {:error, {:unknown_validator_index, 9}}
+    end
+  end
+
+  describe "EIP-2333 key derivation" do
+    # Test case 0 from EIP-2333
+    @seed Base.decode16!(
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            case: :lower
+          )
+    @master_sk 6_083_874_454_709_270_928_345_386_274_498_605_044_986_640_685_124_978_867_557_563_392_430_687_146_096
+    # Derived from the seed above along "m/12381/3600/0/0/0"
+    @path_sk 1_438_960_529_079_439_298_020_003_172_973_761_593_698_584_351_192_884_838_483_126_814_052_706_935_030
+
+    test "derives the master secret key from a seed" do
+      {:ok, master_sk} = Bls.derive_master_sk(@seed)
+      assert Bls.export_secret_key(master_sk) == <<@master_sk::256>>
+    end
+
+    test "derives child keys along a path" do
+      {:ok, {privkey, pubkey}} = Bls.derive_key_from_path(@seed, "m/12381/3600/0/0/0")
+      assert Bls.export_secret_key(privkey) == <<@path_sk::256>>
+      assert Bls.derive_pubkey(privkey) == {:ok, pubkey}
+    end
+
+    test "rejects short seeds and malformed paths" do
+      assert Bls.derive_master_sk(<<1::128>>) == {:error, {:seed_too_short, 16}}
+
+      assert {:error, {:invalid_derivation_path, _}} =
+               Bls.derive_key_from_path(@seed, "12381/3600/0/0/0")
+
+      assert {:error, {:invalid_derivation_path, _}} =
+               Bls.derive_key_from_path(@seed, "m/12381/3600/x")
+
+      assert {:error, {:invalid_derivation_path, _}} = Bls.derive_key_from_path(@seed, "m/0")
+      assert {:error, {:invalid_derivation_path, _}} = Bls.parse_derivation_path("m/44/60/0")
+      assert Bls.parse_derivation_path("m/12381/3600/1/0/0") == {:ok, [12_381, 3600, 1, 0, 0]}
     end
   end
 end
@@ -40,7 +40,7 @@ defmodule Unit.KeystoreTest do
         "version": 4
     })
 
-  # Taken from lighthouse, minus "path": "m/12381/60/0/0",
+  # Taken from lighthouse
   @pbkdf2_json ~s({
             "crypto": {
                 "kdf": {
@@ -68,6 +68,7 @@ defmodule Unit.KeystoreTest do
             },
             "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
             "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+            "path": "m/12381/60/0/0",
             "version": 4
         })
 
@@ -75,7 +76,7 @@ defmodule Unit.KeystoreTest do
     %Keystore{pubkey: pubkey, privkey: privkey, path: _path} =
       Keystore.decode_str!(@scrypt_json, @eip_password)
 
-    assert privkey == @eip_secret
+    assert Bls.export_secret_key(privkey) == @eip_secret
     assert pubkey == @pubkey
 
     digest = :crypto.hash(:sha256, "test message")
@@ -84,11 +85,12 @@ defmodule Unit.KeystoreTest do
   end
 
   test "eip pbkdf2 test vector" do
-    %Keystore{pubkey: pubkey, privkey: privkey, path: _path} =
+    %Keystore{pubkey: pubkey, privkey: privkey, path: path} =
       Keystore.decode_str!(@pbkdf2_json, @eip_password)
 
-    assert privkey == @eip_secret
+    assert Bls.export_secret_key(privkey) == @eip_secret
     assert pubkey == @pubkey
+    assert path == "m/12381/60/0/0"
 
     digest = :crypto.hash(:sha256, "test message")
     {:ok, signature} = Bls.sign(privkey, digest)
@@ -104,7 +106,7 @@ defmodule Unit.KeystoreTest do
     %Keystore{pubkey: pubkey, privkey: privkey, path: _path} =
       Keystore.decode_str!(scrypt_json, @eip_password)
 
-    assert privkey == @eip_secret
+    assert Bls.export_secret_key(privkey) == @eip_secret
     assert pubkey == @pubkey
 
     digest = :crypto.hash(:sha256, "test message")
@@ -121,11 +123,68 @@ defmodule Unit.KeystoreTest do
     %Keystore{pubkey: pubkey, privkey: privkey, path: _path} =
       Keystore.decode_str!(pbkdf2_json, @eip_password)
 
-    assert privkey == @eip_secret
+    assert Bls.export_secret_key(privkey) == @eip_secret
     assert pubkey == @pubkey
 
     digest = :crypto.hash(:sha256, "test message")
     {:ok, signature} = Bls.sign(privkey, digest)
     assert Bls.valid?(pubkey, digest, signature)
   end
+
+  test "keystores with invalid paths are rejected" do
+    json =
+      Jason.decode!(@scrypt_json)
+      |> Map.put("path", "m/44/60/0/0")
+      |> Jason.encode!()
+
+    assert_raise RuntimeError, ~r/invalid_path/, fn ->
+      Keystore.decode_str!(json, @eip_password)
+    end
+  end
+
+  test "wrong passwords are reported" do
+    assert Keystore.decode_str(@pbkdf2_json, "wrongpassword") == {:error, :invalid_password}
+  end
+
+  test "unsupported KDFs are reported" do
+    json =
+      Jason.decode!(@pbkdf2_json)
+      |> put_in(["crypto", "kdf", "function"], "argon2")
+      |> Jason.encode!()
+
+    assert Keystore.decode_str(json, @eip_password) == {:error, {:unsupported_kdf, "argon2"}}
+  end
+
+  test "malformed checksums are reported" do
+    json =
+      Jason.decode!(@pbkdf2_json)
+      |> put_in(["crypto", "checksum", "message"], "1234")
+      |> Jason.encode!()
+
+    assert Keystore.decode_str(json, @eip_password) == {:error, :invalid_checksum}
+  end
+
+  test "malformed keystores are reported" do
+    assert {:error, {:invalid_keystore, _}} = Keystore.decode_str("[4]", @eip_password)
+
+    json =
+      Jason.decode!(@pbkdf2_json)
+      |> Map.put("pubkey", "not hex")
+      |> Jason.encode!()
+
+    assert Keystore.decode_str(json, @eip_password) == {:error, :pubkey_mismatch}
+  end
+
+  test "encoded keystores can be decoded back" do
+    path = "m/12381/3600/0/0/0"
+    {:ok, secret_key} = Bls.secret_key_from_bytes(@eip_secret)
+    {:ok, json} = Keystore.encode(secret_key, @eip_password, path, {:pbkdf2, %{c: 16}})
+
+    assert %{"version" => 4, "path" => ^path} = Jason.decode!(json)
+
+    assert {:ok, %Keystore{pubkey: @pubkey, privkey: privkey, path: ^path}} =
+             Keystore.decode_str(json, @eip_password)
+
+    assert Bls.export_secret_key(privkey) == @eip_secret
+  end
 end
@@ -0,0 +1,173 @@
+defmodule Unit.KzgTest do
+  use ExUnit.Case
+  use ExUnitProperties
+
+  @blob <<0::size(131_072 * 8)>>
+  @commitment <<0xC0, 0::376>>
+
+  test "verifies proofs asynchronously" do
+    {:ok, commitment} = Kzg.blob_to_kzg_commitment(@blob)
+    {:ok, proof} = Kzg.compute_blob_kzg_proof(@blob, commitment)
+
+    job =
+      Kzg.verify_blob_kzg_proof_batch_async(
+        [@blob, @blob],
+        [commitment, commitment],
+        [proof, proof]
+      )
+
+    assert VerificationPool.await(job) == {:ok, true}
+
+    job = Kzg.verify_blob_kzg_proof_async(<<0::80>>, commitment, proof)
+    assert VerificationPool.await(job) == {:error, {:blob_length, 10, 131_072}}
+  end
+
+  test "builds blobs bundles in parallel" do
+    blobs = for i <- 1..6, do: <<i::256, 0::size((131_072 - 32) * 8)>>
+    assert {:ok, bundle} = Kzg.blobs_bundle(blobs)
+    assert bundle.blobs == blobs
+
+    for {blob, commitment, proof} <- Enum.zip([blobs, bundle.commitments, bundle.proofs]) do
+      assert Kzg.blob_to_kzg_commitment(blob) == {:ok, commitment}
+      assert Kzg.compute_blob_kzg_proof(blob, commitment) == {:ok, proof}
+    end
+
+    assert Kzg.verify_blob_kzg_proof_batch(blobs, bundle.commitments, bundle.proofs) ==
+             {:ok, true}
+
+    proofs = Enum.reverse(bundle.proofs)
+    assert Kzg.verify_blob_kzg_proof_batch(blobs, bundle.commitments, proofs) == {:ok, false}
+    assert {:error, {:blob_length, 10, 131_072}} = Kzg.blobs_bundle([<<0::80>> | blobs])
+  end
+
+  describe "trusted setup" do
+    @describetag :tmp_dir
+
+    test "can be loaded from a file" do
+      {:ok, commitment} = Kzg.blob_to_kzg_commitment(@blob)
+
+      assert Kzg.load_trusted_setup("native/kzg_nif/official_trusted_setup.txt") == :ok
+      assert Kzg.blob_to_kzg_commitment(@blob) == {:ok, commitment}
+    end
+
+    test "keeps the current one when loading fails", %{tmp_dir: tmp_dir} do
+      {:ok, commitment} = Kzg.blob_to_kzg_commitment(@blob)
+
+      assert {:error, {:invalid_trusted_setup, _}} =
+               Kzg.load_trusted_setup(Path.join(tmp_dir, "missing.txt"))
+
+      corrupt = Path.join(tmp_dir, "corrupt.txt")
+      File.write!(corrupt, "4096\n65\nnot a point\n")
+      assert {:error, {:invalid_trusted_setup, _}} = Kzg.load_trusted_setup(corrupt)
+
+      # Rejected before allocating room for the points
+      File.write!(corrupt, "18446744073709551615\n65\n")
+      assert {:error, {:invalid_trusted_setup, _}} = Kzg.load_trusted_setup(corrupt)
+
+      assert Kzg.blob_to_kzg_commitment(@blob) == {:ok, commitment}
+    end
+  end
+
+  describe "cells" do
+    test "are recovered from half of them" do
+      {:ok, commitment} = Kzg.blob_to_kzg_commitment(@blob)
+      {:ok, {cells, proofs}} = Kzg.compute_cells_and_kzg_proofs(@blob)
+      assert length(cells) == 128
+      assert Kzg.compute_cells(@blob) == {:ok, cells}
+
+      indices = Enum.to_list(0..127)
+      commitments = List.duplicate(commitment, 128)
+      assert Kzg.verify_cell_kzg_proof_batch(commitments, indices, cells, proofs) == {:ok, true}
+
+      {half_indices, half_cells} =
+        indices |> Enum.zip(cells) |> Enum.take_every(2) |> Enum.unzip()
+
+      assert Kzg.recover_cells_and_kzg_proofs(half_indices, half_cells) == {:ok, {cells, proofs}}
+    end
+
+    test "report invalid lengths" do
+      assert Kzg.recover_cells_and_kzg_proofs([0], [<<0::8>>]) ==
+               {:error, {:cell_length, 1, 2048}}
+
+      assert {:error, {:kzg_error, _}} = Kzg.recover_cells_and_kzg_proofs([0], [<<0::16_384>>])
+    end
+  end
+
+  describe "packing" do
+    # 4096 field elements of 31 bytes, minus the 8-byte length prefix
+    @blob_capacity 4096 * 31 - 8
+
+    property "unpacks the packed data" do
+      check all(data <- binary(max_length: 1000)) do
+        assert {:ok, [blob]} = Kzg.pack_blobs(data)
+        assert Kzg.unpack_blobs([blob]) == {:ok, data}
+      end
+    end
+
+    property "verifies proofs of packed blobs" do
+      check all(data <- binary(min_length: 1, max_length: 1000), max_runs: 10) do
+        {:ok, %{blobs: [blob], commitments: [commitment], proofs: [proof]}} =
+          Kzg.blobs_bundle_from_data(data)
+
+        assert Kzg.verify_blob_kzg_proof(blob, commitment, proof) == {:ok, true}
+
+        {:ok, [other_blob]} = Kzg.pack_blobs(data <> <<0>>)
+        assert Kzg.verify_blob_kzg_proof(other_blob, commitment, proof) == {:ok, false}
+      end
+    end
+
+    test "builds bundles of data spanning several blobs" do
+      data = :crypto.strong_rand_bytes(@blob_capacity + 1)
+      assert {:ok, bundle} = Kzg.blobs_bundle_from_data(data)
+      assert length(bundle.blobs) == 2
+      assert Kzg.verify_blob_kzg_proof_batch(bundle.blobs, bundle.commitments, bundle.proofs) ==
+               {:ok, true}
+
+      assert Kzg.unpack_blobs(bundle.blobs) == {:ok, data}
+      assert {:ok, [_]} = Kzg.pack_blobs(binary_part(data, 0, @blob_capacity))
+    end
+
+    test "rejects blobs that weren't packed" do
+      {:ok, blobs} = Kzg.pack_blobs("data")
+
+      not_canonical = <<1, 0::size((131_072 - 1) * 8)>>
+      assert {:error, {:invalid_blob_encoding, _}} = Kzg.unpack_blobs([not_canonical])
+
+      too_long = <<0, @blob_capacity + 1::64, 0::size((131_072 - 9) * 8)>>
+      assert {:error, {:invalid_blob_encoding, _}} = Kzg.unpack_blobs([too_long])
+
+      # The second field element only holds padding after "data"
+      <<head::binary-size(33), _, rest::binary>> = hd(blobs)
+      padded = <<head::binary, 1, rest::binary>>
+      assert {:error, {:invalid_blob_encoding, _}} = Kzg.unpack_blobs([padded])
+      assert {:error, {:invalid_blob_encoding, _}} = Kzg.unpack_blobs(blobs ++ [@blob])
+      assert {:error, {:invalid_blob_encoding, _}} = Kzg.unpack_blobs([])
+      assert {:error, {:blob_length, 10, 131_072}} = Kzg.unpack_blobs([<<0::80>>])
+    end
+  end
+
+  describe "errors" do
+    test "report the received and expected lengths" do
+      assert Kzg.blob_to_kzg_commitment(<<0::80>>) == {:error, {:blob_length, 10, 131_072}}
+
+      assert Kzg.compute_blob_kzg_proof(@blob, <<0::256>>) ==
+               {:error, {:commitment_length, 32, 48}}
+
+      assert Kzg.verify_blob_kzg_proof(@blob, @commitment, <<0>>) ==
+               {:error, {:proof_length, 1, 48}}
+
+      assert Kzg.compute_kzg_proof(@blob, <<0::128>>) ==
+               {:error, {:field_element_length, 16, 32}}
+    end
+
+    test "reject batches of different lengths" do
+      assert {:error, {:length_mismatch, _}} =
+               Kzg.verify_blob_kzg_proof_batch([@blob], [], [@commitment])
+    end
+
+    test "reject invalid points" do
+      assert {:error, {:kzg_error, _}} =
+               Kzg.verify_blob_kzg_proof(@blob, <<0xFF::384>>, @commitment)
+    end
+  end
+end
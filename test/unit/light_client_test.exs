@@ -0,0 +1,133 @@
+defmodule Unit.LightClientTest do
+  use ExUnit.Case
+
+  alias Fixtures.Block
+  alias Fixtures.Random
+  alias LambdaEthereumConsensus.Beacon.LightClient
+  alias LambdaEthereumConsensus.StateTransition.Predicates
+  alias Types.StateInfo
+
+  setup_all do
+    Application.fetch_env!(:lambda_ethereum_consensus, ChainSpec)
+    |> Keyword.put(:config, MainnetConfig)
+    |> then(&Application.put_env(:lambda_ethereum_consensus, ChainSpec, &1))
+  end
+
+  # Returns a block and its post-state
+  defp block_and_state(slot, parent_root, finalized_root \\ Random.root()) do
+    body = Block.beacon_block_body()
+
+    header = %Types.BeaconBlockHeader{
+      slot: slot,
+      proposer_index: 1,
+      parent_root: parent_root,
+      state_root: <<0::256>>,
+      body_root: Ssz.hash_tree_root!(body)
+    }
+
+    state = %{
+      Block.encodable_beacon_state()
+      | slot: slot,
+        latest_block_header: header,
+        finalized_checkpoint: %Types.Checkpoint{epoch: 1, root: finalized_root}
+    }
+
+    {:ok, state_info} = StateInfo.from_beacon_state(state)
+
+    block = %Types.BeaconBlock{
+      slot: slot,
+      proposer_index: header.proposer_index,
+      parent_root: parent_root,
+      state_root: state_info.root,
+      body: body
+    }
+
+    {%Types.SignedBeaconBlock{message: block, signature: Random.bls_signature()}, state_info}
+  end
+
+  defp valid_branch?(leaf, branch, gindex, root) do
+    depth = length(branch)
+    Predicates.valid_merkle_branch?(leaf, branch, depth, gindex - 2 ** depth, root)
+  end
+
+  test "bootstrap" do
+    {block, state_info} = block_and_state(100, Random.root())
+    assert {:ok, bootstrap} = LightClient.create_bootstrap(state_info, block)
+
+    header = bootstrap.header
+    assert Ssz.hash_tree_root!(header.beacon) == Ssz.hash_tree_root!(block.message)
+
+    assert valid_branch?(
+             Ssz.hash_tree_root!(header.execution),
+             header.execution_branch,
+             Constants.execution_payload_gindex(),
+             header.beacon.body_root
+           )
+
+    assert bootstrap.current_sync_committee == state_info.beacon_state.current_sync_committee
+
+    assert valid_branch?(
+             Ssz.hash_tree_root!(bootstrap.current_sync_committee),
+             bootstrap.current_sync_committee_branch,
+             Constants.current_sync_committee_gindex(),
+             state_info.root
+           )
+
+    {other_block, _} = block_and_state(100, Random.root())
+
+    assert {:error, {:invalid_value, _}} =
+             LightClient.create_bootstrap(state_info, other_block)
+  end
+
+  test "update" do
+    {finalized_block, _} = block_and_state(64, Random.root())
+    finalized_root = Ssz.hash_tree_root!(finalized_block.message)
+    {attested_block, attested_state} = block_and_state(100, Random.root(), finalized_root)
+    {block, state} = block_and_state(101, Ssz.hash_tree_root!(attested_block.message))
+
+    assert {:ok, update} =
+             LightClient.create_update(
+               state,
+               block,
+               attested_state,
+               attested_block,
+               finalized_block
+             )
+
+    assert update.signature_slot == 101
+    assert update.sync_aggregate == block.message.body.sync_aggregate
+    assert update.attested_header.beacon.state_root == attested_state.root
+    assert Ssz.hash_tree_root!(update.finalized_header.beacon) == finalized_root
+
+    assert valid_branch?(
+             Ssz.hash_tree_root!(update.next_sync_committee),
+             update.next_sync_committee_branch,
+             Constants.next_sync_committee_gindex(),
+             attested_state.root
+           )
+
+    assert valid_branch?(
+             finalized_root,
+             update.finality_branch,
+             Constants.finalized_root_gindex(),
+             attested_state.root
+           )
+
+    finality_update = LightClient.create_finality_update(update)
+    assert {:ok, _} = Ssz.to_ssz(finality_update)
+    assert finality_update.finality_branch == update.finality_branch
+
+    optimistic_update = LightClient.create_optimistic_update(update)
+    assert {:ok, _} = Ssz.to_ssz(optimistic_update)
+
+    # Without a finalized block, the update has no finality proof
+    assert {:ok, update} =
+             LightClient.create_update(state, block, attested_state, attested_block, nil)
+
+    assert update.finality_branch == List.duplicate(<<0::256>>, 7)
+
+    # The attested block must be the parent of the signature block
+    assert {:error, {:invalid_value, _}} =
+             LightClient.create_update(state, block, state, block, finalized_block)
+  end
+end
@@ -0,0 +1,122 @@
+defmodule Unit.MerkleProofTest do
+  use ExUnit.Case
+
+  alias Fixtures.Block
+  alias Fixtures.Random
+
+  setup_all do
+    Application.fetch_env!(:lambda_ethereum_consensus, ChainSpec)
+    |> Keyword.put(:config, MainnetConfig)
+    |> then(&Application.put_env(:lambda_ethereum_consensus, ChainSpec, &1))
+  end
+
+  test "proof of a container field" do
+    header = Block.beacon_block_header()
+    root = Ssz.hash_tree_root!(header)
+
+    assert {:ok, {11, leaf, branch}} = Ssz.merkle_proof(header, [:state_root])
+    assert leaf == header.state_root
+    assert length(branch) == 3
+    assert {:ok, true} = Ssz.verify_merkle_proof(leaf, branch, 11, root)
+    assert {:ok, false} = Ssz.verify_merkle_proof(leaf, branch, 10, root)
+    assert {:ok, false} = Ssz.verify_merkle_proof(Random.root(), branch, 11, root)
+
+    assert {:ok, {1, ^root, []}} = Ssz.merkle_proof(header, [])
+  end
+
+  test "proof of a nested value" do
+    commitments = for _ <- 1..3, do: Random.binary(48)
+    body = %{Block.beacon_block_body() | blob_kzg_commitments: commitments}
+    root = Ssz.hash_tree_root!(body)
+
+    # Same as the inclusion proof of a blob sidecar
+    assert {:ok, {gindex, leaf, branch}} = Ssz.merkle_proof(body, [:blob_kzg_commitments, 2])
+    assert gindex == Bitwise.bsl(27 * 2, 12) + 2
+    assert length(branch) == ChainSpec.get("KZG_COMMITMENT_INCLUSION_PROOF_DEPTH")
+    assert {:ok, true} = Ssz.verify_merkle_proof(leaf, branch, gindex, root)
+
+    path = [:execution_payload, :block_hash]
+    assert {:ok, {gindex, leaf, branch}} = Ssz.merkle_proof(body, path)
+    assert leaf == body.execution_payload.block_hash
+    assert {:ok, true} = Ssz.verify_merkle_proof(leaf, branch, gindex, root)
+  end
+
+  test "proof of packed values and list lengths" do
+    attestation = %Types.IndexedAttestation{
+      attesting_indices: Enum.to_list(0..9),
+      data: %Types.AttestationData{
+        slot: 1,
+        index: 2,
+        beacon_block_root: Random.root(),
+        source: Block.checkpoint(),
+        target: Block.checkpoint()
+      },
+      signature: Random.bls_signature()
+    }
+
+    root = Ssz.hash_tree_root!(attestation)
+
+    # Four indices fit in a chunk, and the list has room for 2^15 chunks
+    assert {:ok, {gindex, leaf, branch}} = Ssz.merkle_proof(attestation, [:attesting_indices, 5])
+    assert gindex == 8 * 2 ** 15 + 1
+    assert leaf == for(i <- 4..7, into: <<>>, do: <<i::little-64>>)
+    assert {:ok, true} = Ssz.verify_merkle_proof(leaf, branch, gindex, root)
+
+    path = [:attesting_indices, :__len__]
+    assert {:ok, {9, <<10::little-256>> = leaf, branch}} = Ssz.merkle_proof(attestation, path)
+    assert {:ok, true} = Ssz.verify_merkle_proof(leaf, branch, 9, root)
+  end
+
+  test "multiproofs" do
+    header = Block.beacon_block_header()
+    root = Ssz.hash_tree_root!(header)
+    paths = [[:slot], [:state_root], [:body_root]]
+
+    assert {:ok, {gindices, leaves, proof}} = Ssz.merkle_multiproof(header, paths)
+    assert gindices == [8, 11, 12]
+    assert leaves == [<<header.slot::little-256>>, header.state_root, header.body_root]
+    # Nodes 13, 10, 9 and 7, the ones that can't be computed from the leaves
+    assert length(proof) == 4
+    assert {:ok, true} = Ssz.verify_merkle_multiproof(leaves, proof, gindices, root)
+
+    tampered = List.replace_at(leaves, 1, Random.root())
+    assert {:ok, false} = Ssz.verify_merkle_multiproof(tampered, proof, gindices, root)
+    assert {:ok, false} = Ssz.verify_merkle_multiproof(leaves, tl(proof), gindices, root)
+  end
+
+  test "multiproofs of nodes inside others" do
+    body = Block.beacon_block_body()
+    root = Ssz.hash_tree_root!(body)
+    paths = [[:eth1_data], [:eth1_data, :deposit_count]]
+
+    assert {:error, {:invalid_path, _}} = Ssz.merkle_multiproof(body, paths)
+    assert {:error, {:invalid_path, _}} = Ssz.merkle_multiproof(body, [[:graffiti], [:graffiti]])
+
+    # Leaves that repeat, or that can be computed from others, prove nothing more
+    {:ok, {[gindex], [leaf], proof}} = Ssz.merkle_multiproof(body, [[:graffiti]])
+    assert {:ok, true} = Ssz.verify_merkle_multiproof([leaf], proof, [gindex], root)
+
+    assert {:ok, false} =
+             Ssz.verify_merkle_multiproof([leaf, leaf], proof, [gindex, gindex], root)
+
+    {:ok, {_, [eth1_data_root], _}} = Ssz.merkle_multiproof(body, [[:eth1_data]])
+    path = [:eth1_data, :deposit_count]
+    {:ok, {gindices, leaves, proof}} = Ssz.merkle_multiproof(body, [path])
+    gindices = [div(hd(gindices), 4) | gindices]
+
+    assert {:ok, false} =
+             Ssz.verify_merkle_multiproof([eth1_data_root | leaves], proof, gindices, root)
+  end
+
+  test "invalid paths" do
+    header = Block.beacon_block_header()
+
+    assert {:error, {:invalid_path, _}} = Ssz.merkle_proof(header, [:unknown])
+    assert {:error, {:invalid_path, _}} = Ssz.merkle_proof(header, [0])
+    assert {:error, {:invalid_path, _}} = Ssz.merkle_proof(header, [:slot, 0])
+    assert {:error, {:invalid_path, _}} = Ssz.merkle_proof(header, [:state_root, :__len__])
+
+    body = Block.beacon_block_body()
+    assert {:error, {:invalid_path, _}} = Ssz.merkle_proof(body, [:blob_kzg_commitments, 0])
+  end
+end
@@ -58,7 +58,7 @@ defmodule Unit.OperationsCollectorTest do
 
   defp compress(data) do
     {:ok, encoded} = SszEx.encode(data)
-    {:ok, compressed} = :snappyer.compress(encoded)
+    {:ok, compressed} = Snappy.compress_raw(encoded)
     compressed
   end
 
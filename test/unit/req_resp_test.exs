@@ -1,6 +1,7 @@
 defmodule Unit.ReqRespTest do
   alias Fixtures.Block
   alias LambdaEthereumConsensus.ForkChoice
+  alias LambdaEthereumConsensus.P2P
   alias LambdaEthereumConsensus.P2P.ReqResp
   alias LambdaEthereumConsensus.Utils.BitVector
   alias Types.BeaconBlocksByRangeRequest
@@ -119,6 +120,77 @@ defmodule Unit.ReqRespTest do
     )
   end
 
+  test "decoding is bounded by the length prefix" do
+    {:ok, compressed} = Snappy.compress(<<0::128>>)
+
+    # The frames hold more than the prefix announces
+    chunk = P2P.Utils.encode_varint(8) <> compressed
+    assert {:error, {:corrupt_input, _}} = ReqResp.decode_request(chunk, TypeAliases.uint64())
+
+    chunk = P2P.Utils.encode_varint(40) <> compressed
+    assert {:error, {:corrupt_input, _}} = ReqResp.decode_request(chunk, Types.Checkpoint)
+
+    # Fixed-size types only accept their own length
+    chunk = P2P.Utils.encode_varint(16) <> compressed
+
+    assert ReqResp.decode_request(chunk, Types.Checkpoint) ==
+             {:error, {:invalid_length_prefix, 16, {40, 40}}}
+
+    max_size = ChainSpec.get("MAX_PAYLOAD_SIZE")
+    too_large = P2P.Utils.encode_varint(max_size + 1) <> compressed
+    schema = TypeAliases.beacon_blocks_by_root_request()
+    max_roots = 32 * ChainSpec.get("MAX_REQUEST_BLOCKS")
+
+    assert ReqResp.decode_request(too_large, schema) ==
+             {:error, {:invalid_length_prefix, max_size + 1, {0, max_roots}}}
+
+    # Variable-size types are bounded by their own maximum length
+    too_many = P2P.Utils.encode_varint(max_roots + 32) <> compressed
+
+    assert ReqResp.decode_request(too_many, schema) ==
+             {:error, {:invalid_length_prefix, max_roots + 32, {0, max_roots}}}
+  end
+
+  test "responses with several chunks" do
+    patch(ForkChoice, :get_fork_digest, "abcd")
+    checkpoint = %Types.Checkpoint{epoch: 1, root: <<1::256>>}
+
+    response =
+      ReqResp.encode_response([
+        {:ok, {checkpoint, "abcd"}},
+        {:ok, {%{checkpoint | epoch: 2}, "abcd"}},
+        {:error, {3, "Resource unavailable"}}
+      ])
+
+    assert ReqResp.decode_response(response, Types.Checkpoint) ==
+             {:ok, [checkpoint, %{checkpoint | epoch: 2}]}
+
+    error = ReqResp.encode_error(3, "Resource unavailable")
+
+    assert ReqResp.decode_response(error, Types.Checkpoint) ==
+             {:error, %ReqResp.Error{code: 3, message: "Resource unavailable"}}
+  end
+
+  test "chunks that fail to decode are skipped" do
+    patch(ForkChoice, :get_fork_digest, "abcd")
+    checkpoint = %Types.Checkpoint{epoch: 1, root: <<1::256>>}
+
+    response =
+      ReqResp.encode_response([
+        {:ok, {checkpoint, "abcd"}},
+        {:ok, {%{checkpoint | epoch: 2}, "dcba"}},
+        {:ok, {%{checkpoint | epoch: 3}, "abcd"}}
+      ])
+
+    assert ReqResp.decode_response(response, Types.Checkpoint) ==
+             {:ok, [checkpoint, %{checkpoint | epoch: 3}]}
+
+    response = ReqResp.encode_response([{:ok, {checkpoint, "dcba"}}])
+
+    assert ReqResp.decode_response(response, Types.Checkpoint) ==
+             {:error, "all blocks decoding failed"}
+  end
+
   test "BlobSidecarsByRange round trip" do
     count = 1
     request = %BeaconBlocksByRangeRequest{start_slot: 15_125, count: count}
@@ -66,6 +66,14 @@ defmodule Unit.SnappyTest do
     assert got == {:ok, expected}
   end
 
+  test "decompress rejects corrupt input" do
+    assert {:error, {:corrupt_input, _}} = Snappy.decompress("not snappy")
+
+    {:ok, compressed} = Snappy.compress(:binary.copy(<<1>>, 100))
+    truncated = binary_part(compressed, 0, byte_size(compressed) - 1)
+    assert {:error, {:corrupt_input, _}} = Snappy.decompress(truncated)
+  end
+
   property "compress(decompress(x)) == x" do
     check all(bin <- binary()) do
       assert {:ok, compressed} = Snappy.compress(bin)
@@ -74,6 +82,133 @@ defmodule Unit.SnappyTest do
     end
   end
 
+  test "bounded decompression stops at the maximum length" do
+    bin = :binary.copy(<<1>>, 1_000_000)
+    {:ok, compressed} = Snappy.compress(bin)
+
+    assert Snappy.decompress(compressed, 1_000_000) == {:ok, bin}
+    assert Snappy.decompress(compressed, 999_999) == {:error, {:max_length_exceeded, 999_999}}
+    assert Snappy.decompress(compressed, 0) == {:error, {:max_length_exceeded, 0}}
+  end
+
+  test "bounded decompression accepts the largest maximum length" do
+    bin = :binary.copy(<<1>>, 1000)
+    {:ok, compressed} = Snappy.compress(bin)
+    max_length = 2 ** 64 - 1
+
+    assert Snappy.decompress(compressed, max_length) == {:ok, bin}
+
+    decoder = Snappy.decoder_new(max_length)
+    assert Snappy.decoder_feed(decoder, compressed) == {:ok, bin}
+    assert Snappy.decoder_finish(decoder) == {:ok, 1000}
+  end
+
+  property "incremental decoding matches decompress/1" do
+    check all(bin <- binary(), piece_length <- integer(1..64)) do
+      {:ok, compressed} = Snappy.compress(bin)
+      decoder = Snappy.decoder_new(byte_size(bin))
+
+      decompressed =
+        for <<piece::binary-size(piece_length) <- compressed>>, into: <<>> do
+          {:ok, data} = Snappy.decoder_feed(decoder, piece)
+          data
+        end
+
+      tail_length = rem(byte_size(compressed), piece_length)
+      tail = binary_part(compressed, byte_size(compressed) - tail_length, tail_length)
+      assert {:ok, data} = Snappy.decoder_feed(decoder, tail)
+
+      assert decompressed <> data == bin
+      assert Snappy.decoder_finish(decoder) == {:ok, byte_size(bin)}
+    end
+  end
+
+  test "incremental decoding fails on bombs and truncated streams" do
+    {:ok, compressed} = Snappy.compress(:binary.copy(<<1>>, 100_000))
+    decoder = Snappy.decoder_new(1000)
+    assert Snappy.decoder_feed(decoder, compressed) == {:error, {:max_length_exceeded, 1000}}
+    # The error is kept
+    assert Snappy.decoder_feed(decoder, "") == {:error, {:max_length_exceeded, 1000}}
+
+    decoder = Snappy.decoder_new(100_000)
+    truncated = binary_part(compressed, 0, byte_size(compressed) - 1)
+    assert {:ok, _} = Snappy.decoder_feed(decoder, truncated)
+    assert {:error, {:corrupt_input, _}} = Snappy.decoder_finish(decoder)
+  end
+
+  property "decompress_raw(compress_raw(x)) == x" do
+    check all(bin <- binary()) do
+      assert {:ok, compressed} = Snappy.compress_raw(bin)
+      assert Snappy.decompress_raw(compressed) == {:ok, bin}
+      assert Snappy.decompress_raw(compressed, byte_size(bin)) == {:ok, bin}
+    end
+  end
+
+  test "raw decompression checks the length before decompressing" do
+    {:ok, compressed} = Snappy.compress_raw(:binary.copy(<<1>>, 1000))
+
+    assert Snappy.decompress_raw(compressed, 999) == {:error, {:max_length_exceeded, 999}}
+    # The header claims 2^32 - 1 bytes
+    bomb = <<0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0>>
+    assert Snappy.decompress_raw(bomb, 1000) == {:error, {:max_length_exceeded, 1000}}
+
+    truncated = binary_part(compressed, 0, byte_size(compressed) - 1)
+    assert {:error, {:corrupt_input, _}} = Snappy.decompress_raw(truncated)
+  end
+
+  property "Req/Resp chunks round trip" do
+    check all(
+            chunks <- list_of({integer(0..3), binary(length: 4), binary(max_length: 256)}),
+            request <- binary()
+          ) do
+      # Error chunks have no context bytes
+      chunks =
+        Enum.map(chunks, fn
+          {0, context, ssz} -> {0, context, ssz}
+          {code, _, message} -> {code, "", message}
+        end)
+
+      assert {:ok, encoded} = Snappy.encode_response_chunks(chunks)
+      assert Snappy.decode_response_chunks(encoded, 4, 0, 256) == {:ok, chunks}
+
+      assert {:ok, encoded} = Snappy.encode_request(request)
+      size = byte_size(request)
+      assert Snappy.decode_request(encoded, size, size) == {:ok, request}
+    end
+  end
+
+  test "Req/Resp decoding enforces the spec limits" do
+    {:ok, request} = Snappy.encode_request(<<0::128>>)
+
+    assert Snappy.decode_request(request, 0, 15) ==
+             {:error, {:invalid_length_prefix, 16, {0, 15}}}
+
+    assert Snappy.decode_request(request, 17, 32) ==
+             {:error, {:invalid_length_prefix, 16, {17, 32}}}
+
+    assert {:error, {:corrupt_input, _}} = Snappy.decode_request(request <> <<0>>, 0, 16)
+
+    # Overlong and non-canonical varints
+    overlong = :binary.copy(<<0xFF>>, 11)
+    assert {:error, {:corrupt_input, _}} = Snappy.decode_request(overlong, 0, 16)
+    <<16>> <> frames = request
+    assert {:error, {:corrupt_input, _}} = Snappy.decode_request(<<0x90, 0>> <> frames, 0, 16)
+
+    # A prefix longer than the frames, and frames longer than the prefix
+    assert {:error, {:corrupt_input, _}} = Snappy.decode_request(<<17>> <> frames, 0, 32)
+    assert {:error, {:corrupt_input, _}} = Snappy.decode_request(<<15>> <> frames, 0, 32)
+
+    # Error messages are at most 256 bytes long, whatever the bounds of the type
+    {:ok, chunk} = Snappy.encode_response_chunks([{1, "", :binary.copy(<<1>>, 257)}])
+
+    assert Snappy.decode_response_chunks(chunk, 4, 0, 1000) ==
+             {:error, {:invalid_length_prefix, 257, {0, 256}}}
+
+    {:ok, chunk} = Snappy.encode_response_chunks([{0, "abcd", <<1>>}])
+    truncated = binary_part(chunk, 0, 3)
+    assert {:error, {:corrupt_input, _}} = Snappy.decode_response_chunks(truncated, 4, 0, 1)
+  end
+
   test "snappy block decompression" do
     expected =
       "E400000011766D0000000000000000000000000018F41F47CD8EBF7FF17CED045954D1894D24CEC72361696FEC121C6D3EF99510AF6B0300000000006FEFAA78066FBFE3763C971204CBAAB0E6BC14A9643A28900AF7DAB9353A2988B06B03000000000012E2B17EA473B5EA28338C129976BFDA58A3AA7244EF01B7456B1A8DEC2C72ABACFF3B742DDF85EF0CCE1C60394244C4EED37EBEB6E7BDF917CBDA90984F70B3DD4A1220B9D164DFFAB4521BB39CB5A10F82D4C910325DCE7899485EA39B29A02C2138B6A29A39F65FF453E233DBF7B4F49FC7B9BD53455EAA7411CFB3A3560700000000000000000000000000000800000000000000000000000000000000000000000000000000000000000080"
@@ -81,7 +216,7 @@ defmodule Unit.SnappyTest do
     got =
       "92021CE400000011766D002E01008818F41F47CD8EBF7FF17CED045954D1894D24CEC72361696FEC121C6D3EF99510AF6B03052F806FEFAA78066FBFE3763C971204CBAAB0E6BC14A9643A28900AF7DAB9353A2988B00D28F08112E2B17EA473B5EA28338C129976BFDA58A3AA7244EF01B7456B1A8DEC2C72ABACFF3B742DDF85EF0CCE1C60394244C4EED37EBEB6E7BDF917CBDA90984F70B3DD4A1220B9D164DFFAB4521BB39CB5A10F82D4C910325DCE7899485EA39B29A02C2138B6A29A39F65FF453E233DBF7B4F49FC7B9BD53455EAA7411CFB3A35607000005AF0D0100080D085A01000080"
       |> Base.decode16!()
-      |> :snappyer.decompress()
+      |> Snappy.decompress_raw()
       |> then(fn {:ok, b} -> Base.encode16(b) end)
 
     assert got == expected
@@ -34,7 +34,7 @@ defmodule Unit.SnappyExTest do
     data = "some compressed data"
     checksum = <<SnappyEx.compute_checksum(data)::little-size(32)>>
 
-    {:ok, compressed_data} = :snappyer.compress(data)
+    {:ok, compressed_data} = Snappy.compress_raw(data)
     size = byte_size(compressed_data) + byte_size(checksum)
 
     header = <<0x00, size::little-size(24)>>
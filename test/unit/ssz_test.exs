@@ -1,5 +1,6 @@
 defmodule Unit.SSZTests do
   alias Fixtures.Block
+  alias LambdaEthereumConsensus.Utils.BitList
   alias LambdaEthereumConsensus.Utils.BitVector
   use ExUnit.Case
 
@@ -96,6 +97,39 @@ defmodule Unit.SSZTests do
     )
   end
 
+  test "Metadata and Attestation of earlier forks" do
+    metadata = %Types.Metadata{
+      seq_number: 6_483_425,
+      attnets: Base.decode16!("9989AFAE2372EC4C") |> BitVector.new(64),
+      syncnets: nil
+    }
+
+    serialized = Base.decode16!("E1ED6200000000009989AFAE2372EC4C")
+    assert {:ok, ^serialized} = Ssz.to_ssz_typed(metadata, Types.Metadata, :phase0)
+    assert {:ok, ^metadata} = Ssz.from_ssz(serialized, Types.Metadata, :phase0)
+
+    attestation = %Types.Attestation{
+      aggregation_bits: BitList.new(<<0b101>>),
+      data: %Types.AttestationData{
+        slot: 1,
+        index: 2,
+        beacon_block_root: <<1::256>>,
+        source: %Types.Checkpoint{epoch: 3, root: <<2::256>>},
+        target: %Types.Checkpoint{epoch: 4, root: <<3::256>>}
+      },
+      signature: <<4::768>>,
+      committee_bits: nil
+    }
+
+    assert {:ok, serialized} = Ssz.to_ssz_typed(attestation, Types.Attestation, :deneb)
+    assert {:ok, ^attestation} = Ssz.from_ssz(serialized, Types.Attestation, :deneb)
+
+    {:ok, deneb_root} = Ssz.hash_tree_root(attestation, Types.Attestation, :deneb)
+    electra = %{attestation | committee_bits: BitVector.new(1, 64)}
+    assert {:ok, electra_root} = Ssz.hash_tree_root(electra, Types.Attestation)
+    assert deneb_root != electra_root
+  end
+
   test "serialize and hash list of VoluntaryExit" do
     deserialized = [
       %Types.VoluntaryExit{
@@ -193,6 +227,68 @@ defmodule Unit.SSZTests do
     assert {:ok, ^sidecar} = Ssz.from_ssz(encoded, Types.BlobSidecar)
   end
 
+  test "Fulu StatusMessage and Metadata" do
+    status = %Types.StatusMessageV2{
+      fork_digest: <<1, 2, 3, 4>>,
+      finalized_root: <<5::256>>,
+      finalized_epoch: 228_168,
+      head_root: <<6::256>>,
+      head_slot: 7_301_450,
+      earliest_available_slot: 7_000_000
+    }
+
+    {:ok, encoded} = Ssz.to_ssz(status)
+    assert byte_size(encoded) == 92
+    assert binary_part(encoded, 84, 8) == <<7_000_000::little-64>>
+    assert {:ok, ^status} = Ssz.from_ssz(encoded, Types.StatusMessageV2)
+
+    assert_roundtrip(
+      "E1ED6200000000009989AFAE2372EC4C070400000000000000",
+      %Types.MetadataV3{
+        seq_number: 6_483_425,
+        attnets: Base.decode16!("9989AFAE2372EC4C") |> BitVector.new(64),
+        syncnets: Base.decode16!("07") |> BitVector.new(4),
+        custody_group_count: 4
+      }
+    )
+  end
+
+  test "DataColumnSidecar" do
+    # seed RNG
+    :rand.seed(:default, 0)
+    header = Block.signed_beacon_block_header()
+
+    sidecar = %Types.DataColumnSidecar{
+      index: 3,
+      column: [<<1::(64*32)*8>>, <<2::(64*32)*8>>],
+      kzg_commitments: [<<57_888::48*8>>, <<57_889::48*8>>],
+      kzg_proofs: [<<6122::48*8>>, <<6123::48*8>>],
+      signed_block_header: header,
+      kzg_commitments_inclusion_proof: [<<1551::32*8>>] |> Stream.cycle() |> Enum.take(4)
+    }
+
+    assert {:ok, hash} = Ssz.hash_tree_root(sidecar)
+    assert hash == SszEx.hash_tree_root!(sidecar)
+    {:ok, encoded} = Ssz.to_ssz(sidecar)
+    assert {:ok, ^encoded} = SszEx.encode(sidecar)
+    assert {:ok, ^sidecar} = Ssz.from_ssz(encoded, Types.DataColumnSidecar)
+
+    identifier = %Types.DataColumnsByRootIdentifier{block_root: <<1::256>>, columns: [0, 3, 127]}
+    {:ok, encoded} = Ssz.to_ssz(identifier)
+    assert {:ok, ^identifier} = Ssz.from_ssz(encoded, Types.DataColumnsByRootIdentifier)
+
+    entry = %Types.MatrixEntry{
+      cell: <<7::(64*32)*8>>,
+      kzg_proof: <<6122::48*8>>,
+      column_index: 3,
+      row_index: 1
+    }
+
+    {:ok, encoded} = Ssz.to_ssz(entry)
+    assert byte_size(encoded) == 64 * 32 + 48 + 16
+    assert {:ok, ^entry} = Ssz.from_ssz(encoded, Types.MatrixEntry)
+  end
+
   test "SignedBeaconBlock" do
     # seed RNG
     :rand.seed(:default, 0)
@@ -234,4 +330,130 @@ defmodule Unit.SSZTests do
     {:ok, encoded} = Ssz.to_ssz(deneb_block)
     assert {:ok, ^deneb_block} = Ssz.from_ssz(encoded, Types.SignedBeaconBlock)
   end
+
+  describe "states and blocks of earlier forks" do
+    for fork <- [:phase0, :altair, :capella] do
+      test "#{fork} BeaconState" do
+        fork = unquote(fork)
+        electra_state = Block.encodable_beacon_state()
+        state = to_fork(electra_state, fork)
+
+        assert {:ok, encoded} = Ssz.to_ssz_typed(state, Types.BeaconState, fork)
+        assert {:ok, ^state} = Ssz.from_ssz(encoded, Types.BeaconState, fork)
+        assert {:ok, ^encoded} = Ssz.to_ssz_typed(state, Types.BeaconState, fork)
+
+        {:ok, root} = Ssz.hash_tree_root(state, Types.BeaconState, fork)
+        assert root != Ssz.hash_tree_root!(electra_state)
+      end
+
+      test "#{fork} SignedBeaconBlock" do
+        fork = unquote(fork)
+        body = to_fork(Block.beacon_block_body(), fork)
+        block = %{Block.signed_beacon_block() | message: %{Block.beacon_block() | body: body}}
+
+        assert {:ok, encoded} = Ssz.to_ssz_typed(block, Types.SignedBeaconBlock, fork)
+        assert {:ok, ^block} = Ssz.from_ssz(encoded, Types.SignedBeaconBlock, fork)
+
+        # A block has the root of its header, which commits to the body
+        {:ok, body_root} = Ssz.hash_tree_root(body, Types.BeaconBlockBody, fork)
+        assert body_root != Ssz.hash_tree_root!(Block.beacon_block_body())
+
+        header = %Types.BeaconBlockHeader{
+          slot: block.message.slot,
+          proposer_index: block.message.proposer_index,
+          parent_root: block.message.parent_root,
+          state_root: block.message.state_root,
+          body_root: body_root
+        }
+
+        assert Ssz.hash_tree_root(block.message, Types.BeaconBlock, fork) ==
+                 Ssz.hash_tree_root(header)
+      end
+    end
+
+    # Values of earlier forks have `nil` in the fields added after them
+    defp to_fork(%Types.BeaconState{} = state, :phase0) do
+      attestation = %Types.PendingAttestation{
+        aggregation_bits: BitList.new(<<0b101>>),
+        data: %Types.AttestationData{
+          slot: 1,
+          index: 2,
+          beacon_block_root: <<1::256>>,
+          source: %Types.Checkpoint{epoch: 3, root: <<2::256>>},
+          target: %Types.Checkpoint{epoch: 4, root: <<3::256>>}
+        },
+        inclusion_delay: 1,
+        proposer_index: 5
+      }
+
+      %{
+        to_fork(state, :altair)
+        | previous_epoch_participation: nil,
+          current_epoch_participation: nil,
+          inactivity_scores: nil,
+          current_sync_committee: nil,
+          next_sync_committee: nil,
+          previous_epoch_attestations: [attestation],
+          current_epoch_attestations: []
+      }
+    end
+
+    defp to_fork(%Types.BeaconState{} = state, :altair) do
+      %{
+        to_fork(state, :capella)
+        | latest_execution_payload_header: nil,
+          next_withdrawal_index: nil,
+          next_withdrawal_validator_index: nil,
+          historical_summaries: nil
+      }
+    end
+
+    defp to_fork(%Types.BeaconState{} = state, :capella) do
+      header = state.latest_execution_payload_header
+
+      %{
+        state
+        | latest_execution_payload_header: %{header | blob_gas_used: nil, excess_blob_gas: nil},
+          deposit_requests_start_index: nil,
+          deposit_balance_to_consume: nil,
+          exit_balance_to_consume: nil,
+          earliest_exit_epoch: nil,
+          consolidation_balance_to_consume: nil,
+          earliest_consolidation_epoch: nil,
+          pending_deposits: nil,
+          pending_partial_withdrawals: nil,
+          pending_consolidations: nil
+      }
+    end
+
+    defp to_fork(%Types.BeaconBlockBody{} = body, :phase0),
+      do: %{to_fork(body, :altair) | sync_aggregate: nil}
+
+    defp to_fork(%Types.BeaconBlockBody{} = body, :altair),
+      do: %{to_fork(body, :capella) | execution_payload: nil, bls_to_execution_changes: nil}
+
+    defp to_fork(%Types.BeaconBlockBody{} = body, :capella) do
+      payload = %{body.execution_payload | blob_gas_used: nil, excess_blob_gas: nil}
+      %{body | execution_payload: payload, blob_kzg_commitments: nil, execution_requests: nil}
+    end
+  end
+
+  describe "errors" do
+    test "unknown schemas and configs" do
+      assert Ssz.from_ssz_rs(<<>>, Types.Unknown) == {:error, {:unknown_schema, "Unknown"}}
+      assert Ssz.from_ssz_rs(<<>>, Enum) == {:error, {:unknown_schema, "Elixir.Enum"}}
+
+      assert Ssz.from_ssz_rs(<<>>, Types.Checkpoint, :unknown) ==
+               {:error, {:unknown_config, "unknown"}}
+
+      assert Ssz.from_ssz(<<>>, Types.Checkpoint, :unknown) ==
+               {:error, {:unknown_fork, "unknown"}}
+    end
+
+    test "invalid inputs" do
+      assert {:error, {:invalid_ssz, _}} = Ssz.from_ssz(<<1, 2>>, Types.Checkpoint)
+      assert {:error, {:invalid_value, _}} = Ssz.to_ssz(%Types.Checkpoint{epoch: 1, root: <<1>>})
+      assert Ssz.to_ssz_rs(%{epoch: 1}, Types.Checkpoint) == {:error, :invalid_term}
+    end
+  end
 end
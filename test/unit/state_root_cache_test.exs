@@ -0,0 +1,94 @@
+defmodule Unit.StateRootCacheTest do
+  use ExUnit.Case
+
+  alias Fixtures.Block
+  alias Fixtures.Random
+  alias LambdaEthereumConsensus.StateTransition.StateRootCache
+  alias Types.StateInfo
+
+  setup_all do
+    Application.fetch_env!(:lambda_ethereum_consensus, ChainSpec)
+    |> Keyword.put(:config, MainnetConfig)
+    |> then(&Application.put_env(:lambda_ethereum_consensus, ChainSpec, &1))
+  end
+
+  defp assert_cached_root(cache, state) do
+    {:ok, encoded} = Ssz.to_ssz(state)
+    assert {:ok, root} = Ssz.state_hash_cache_root(cache, encoded)
+    assert root == Ssz.hash_tree_root!(state)
+  end
+
+  test "roots of consecutive states" do
+    cache = Ssz.state_hash_cache_new()
+    state = Block.encodable_beacon_state()
+    assert_cached_root(cache, state)
+    # Unchanged
+    assert_cached_root(cache, state)
+
+    state = %{
+      state
+      | slot: state.slot + 1,
+        block_roots: List.replace_at(state.block_roots, 5, Random.root()),
+        balances: Aja.Vector.replace_at!(state.balances, 3, 42)
+    }
+
+    assert_cached_root(cache, state)
+
+    # Growing lists, which may go over a power of two
+    new_validators = for _ <- 1..7, do: Block.validator()
+
+    state = %{
+      state
+      | validators: Aja.Vector.concat(state.validators, new_validators),
+        balances: Aja.Vector.concat(state.balances, 11..17),
+        previous_epoch_participation: Aja.Vector.concat(state.previous_epoch_participation, [1]),
+        pending_consolidations: [%Types.PendingConsolidation{source_index: 1, target_index: 2}]
+    }
+
+    assert_cached_root(cache, state)
+
+    # Shrinking lists
+    state = %{
+      state
+      | balances: Aja.Vector.slice(state.balances, 0, 4),
+        pending_consolidations: []
+    }
+
+    assert_cached_root(cache, state)
+  end
+
+  test "roots of unrelated states" do
+    cache = Ssz.state_hash_cache_new()
+    assert_cached_root(cache, Block.encodable_beacon_state())
+    assert_cached_root(cache, Block.encodable_beacon_state())
+
+    empty = %{
+      Block.encodable_beacon_state()
+      | validators: Aja.Vector.new(),
+        balances: Aja.Vector.new()
+    }
+
+    assert_cached_root(cache, empty)
+  end
+
+  test "invalid states" do
+    cache = Ssz.state_hash_cache_new()
+    state = Block.encodable_beacon_state()
+    {:ok, encoded} = Ssz.to_ssz(state)
+
+    assert {:error, {:invalid_ssz, _}} = Ssz.state_hash_cache_root(cache, <<0, 1, 2>>)
+
+    truncated = binary_part(encoded, 0, byte_size(encoded) - 1)
+    assert {:error, {:invalid_ssz, _}} = Ssz.state_hash_cache_root(cache, truncated)
+
+    # The cache is still usable after an error
+    assert_cached_root(cache, state)
+  end
+
+  test "state info roots" do
+    state = Block.encodable_beacon_state()
+    assert {:ok, state_info} = StateInfo.from_beacon_state(state)
+    assert state_info.root == Ssz.hash_tree_root!(state)
+    assert StateRootCache.root!(state_info.encoded) == state_info.root
+  end
+end
@@ -20,7 +20,7 @@ defmodule Unit.Store.StateInfoByRoot do
   defp get_state_info() do
     {:ok, encoded} =
       File.read!("test/fixtures/validator/proposer/beacon_state.ssz_snappy")
-      |> :snappyer.decompress()
+      |> Snappy.decompress_raw()
 
     {:ok, decoded} = SszEx.decode(encoded, BeaconState)
     {:ok, state_info} = StateInfo.from_beacon_state(decoded)
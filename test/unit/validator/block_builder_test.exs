@@ -5,8 +5,10 @@ defmodule Unit.Validator.BlockBuilderTest do
   alias LambdaEthereumConsensus.StateTransition.Predicates
   alias LambdaEthereumConsensus.Validator.BlockBuilder
   alias LambdaEthereumConsensus.Validator.BuildBlockRequest
+  alias LambdaEthereumConsensus.Validator.SlashingProtection
   alias Types.BeaconBlockBody
   alias Types.BeaconState
+  alias Types.BlobsBundle
   alias Types.BlockInfo
   alias Types.SignedBeaconBlock
 
@@ -19,13 +21,20 @@ defmodule Unit.Validator.BlockBuilderTest do
   end
 
   @tag :skip
-  test "construct block" do
+  @tag :tmp_dir
+  test "construct block", %{tmp_dir: tmp_dir} do
     pre_state =
       SpecTestUtils.read_ssz_from_file!(
         "test/fixtures/validator/proposer/beacon_state.ssz_snappy",
         BeaconState
       )
 
+    :ok =
+      SlashingProtection.open(
+        Path.join(tmp_dir, "slashing_protection.jsonl"),
+        pre_state.genesis_validators_root
+      )
+
     spec_block =
       SpecTestUtils.read_ssz_from_file!(
         "test/fixtures/validator/proposer/empty_signed_beacon_block.ssz_snappy",
@@ -33,7 +42,7 @@ defmodule Unit.Validator.BlockBuilderTest do
       )
 
     # This private key is taken from the spec test vectors
-    privkey = <<0::248, 64>>
+    {:ok, privkey} = Bls.secret_key_from_bytes(<<0::248, 64>>)
 
     proposed_slot = pre_state.slot + 1
 
@@ -77,8 +86,17 @@ defmodule Unit.Validator.BlockBuilderTest do
     commitment = <<0::384>>
     body = %{spec_block.message.body | blob_kzg_commitments: [commitment]}
     body_root = SszEx.hash_tree_root!(body, BeaconBlockBody)
+    signed_block = %{spec_block | message: %{spec_block.message | body: body}}
+
+    bundle = %BlobsBundle{
+      blobs: [<<0::size(131_072 * 8)>>],
+      commitments: [commitment],
+      proofs: [<<0::384>>]
+    }
 
-    [proof] = BlockBuilder.compute_inclusion_proofs(body)
+    {:ok, [sidecar]} = BlockBuilder.generate_sidecars(signed_block, bundle)
+    assert sidecar.signed_block_header.message.body_root == body_root
+    proof = sidecar.kzg_commitment_inclusion_proof
 
     assert length(proof) == 10
 
@@ -0,0 +1,173 @@
+defmodule Unit.Validator.SlashingProtectionTest do
+  use ExUnit.Case
+
+  alias LambdaEthereumConsensus.Validator.SlashingProtection
+
+  @moduletag :tmp_dir
+
+  @genesis_validators_root <<4::256>>
+  @vectors_dir "test/fixtures/validator/slashing_protection"
+
+  setup %{tmp_dir: tmp_dir} do
+    path = Path.join(tmp_dir, "slashing_protection.jsonl")
+    :ok = SlashingProtection.open(path, @genesis_validators_root)
+    {:ok, privkey} = Bls.secret_key_from_bytes(<<1::256>>)
+    {:ok, pubkey} = Bls.derive_pubkey(privkey)
+    {:ok, path: path, privkey: privkey, pubkey: pubkey}
+  end
+
+  # Interchange test vectors, in the format of the EIP-3076 test suite
+  for path <- Path.wildcard("#{@vectors_dir}/*.json") do
+    @external_resource path
+    @vector_path path
+
+    test "interchange vector #{Path.basename(path, ".json")}", %{tmp_dir: tmp_dir} do
+      run_vector(@vector_path, tmp_dir)
+    end
+  end
+
+  defp run_vector(path, tmp_dir) do
+    vector = path |> File.read!() |> Jason.decode!()
+    root = decode_hex(vector["genesis_validators_root"])
+    {:ok, db} = Bls.slashing_protection_open(Path.join(tmp_dir, "db.jsonl"), root)
+
+    for step <- vector["steps"] do
+      result = Bls.slashing_protection_import(db, Jason.encode!(step["interchange"]))
+      assert match?({:ok, _}, result) == step["should_succeed"], inspect(result)
+
+      for block <- step["blocks"] do
+        result =
+          Bls.slashing_protection_check_block(
+            db,
+            decode_hex(block["pubkey"]),
+            String.to_integer(block["slot"]),
+            decode_hex(block["signing_root"])
+          )
+
+        assert match?({:ok, _}, result) == block["should_succeed"],
+               "#{inspect(block)}: #{inspect(result)}"
+      end
+
+      for attestation <- step["attestations"] do
+        result =
+          Bls.slashing_protection_check_attestation(
+            db,
+            decode_hex(attestation["pubkey"]),
+            String.to_integer(attestation["source_epoch"]),
+            String.to_integer(attestation["target_epoch"]),
+            decode_hex(attestation["signing_root"])
+          )
+
+        assert match?({:ok, _}, result) == attestation["should_succeed"],
+               "#{inspect(attestation)}: #{inspect(result)}"
+      end
+    end
+  end
+
+  defp decode_hex("0x" <> hex), do: Base.decode16!(hex, case: :mixed)
+
+  defp attestation_data(source_epoch, target_epoch) do
+    %Types.AttestationData{
+      slot: 0,
+      index: 0,
+      beacon_block_root: <<0::256>>,
+      source: %Types.Checkpoint{epoch: source_epoch, root: <<0::256>>},
+      target: %Types.Checkpoint{epoch: target_epoch, root: <<0::256>>}
+    }
+  end
+
+  test "signs only non-slashable messages", %{privkey: privkey, pubkey: pubkey} do
+    assert {:ok, signature} = SlashingProtection.sign_block(privkey, 10, <<1::256>>)
+    assert Bls.valid?(pubkey, <<1::256>>, signature)
+    assert {:ok, ^signature} = SlashingProtection.sign_block(privkey, 10, <<1::256>>)
+    assert SlashingProtection.sign_block(privkey, 10, <<2::256>>) ==
+             {:error, :double_block_proposal}
+
+    assert {:ok, _} =
+             SlashingProtection.sign_attestation(privkey, attestation_data(2, 5), <<1::256>>)
+
+    assert SlashingProtection.sign_attestation(privkey, attestation_data(2, 5), <<2::256>>) ==
+             {:error, :double_vote}
+
+    assert SlashingProtection.sign_attestation(privkey, attestation_data(1, 6), <<3::256>>) ==
+             {:error, :surrounding_vote}
+
+    assert SlashingProtection.sign_attestation(privkey, attestation_data(3, 4), <<4::256>>) ==
+             {:error, :surrounded_vote}
+  end
+
+  test "keeps the history across restarts", %{path: path, privkey: privkey} do
+    assert {:ok, _} = SlashingProtection.sign_block(privkey, 10, <<1::256>>)
+
+    # Simulates a crash in the middle of a write
+    File.write!(path, ~s({"pubkey":"0x), [:append])
+
+    assert {:error, :genesis_validators_root_mismatch} =
+             SlashingProtection.open(path, <<5::256>>)
+
+    assert :ok = SlashingProtection.open(path, @genesis_validators_root)
+    assert SlashingProtection.sign_block(privkey, 10, <<2::256>>) ==
+             {:error, :double_block_proposal}
+
+    assert {:ok, _} = SlashingProtection.sign_block(privkey, 11, <<2::256>>)
+  end
+
+  test "exported interchanges can be imported back", %{tmp_dir: tmp_dir, privkey: privkey} do
+    assert {:ok, _} = SlashingProtection.sign_block(privkey, 10, <<1::256>>)
+
+    assert {:ok, _} =
+             SlashingProtection.sign_attestation(privkey, attestation_data(2, 5), <<1::256>>)
+
+    {:ok, json} = SlashingProtection.export_interchange()
+
+    assert %{
+             "metadata" => %{"interchange_format_version" => "5"},
+             "data" => [%{"signed_blocks" => [%{"slot" => "10"}]}]
+           } = Jason.decode!(json)
+
+    :ok = SlashingProtection.open(Path.join(tmp_dir, "imported.jsonl"), @genesis_validators_root)
+    assert SlashingProtection.import_interchange(json) == {:ok, 1}
+    assert SlashingProtection.export_interchange() == {:ok, json}
+    assert SlashingProtection.sign_block(privkey, 9, <<2::256>>) ==
+             {:error, :block_slot_too_low}
+  end
+
+  test "rejected interchanges aren't partly imported", %{pubkey: pubkey} do
+    {:ok, before} = SlashingProtection.export_interchange()
+
+    json =
+      interchange([
+        %{"pubkey" => hex(pubkey), "signed_blocks" => [%{"slot" => "10"}]},
+        %{"pubkey" => hex(<<0::384>>), "signed_blocks" => [%{"slot" => "10"}]}
+      ])
+
+    assert {:error, {:invalid_interchange, _}} = SlashingProtection.import_interchange(json)
+    assert SlashingProtection.export_interchange() == {:ok, before}
+  end
+
+  test "repeated votes are checked against imported surround votes", %{
+    privkey: privkey,
+    pubkey: pubkey
+  } do
+    assert {:ok, _} =
+             SlashingProtection.sign_attestation(privkey, attestation_data(2, 5), <<1::256>>)
+
+    attestation = %{"source_epoch" => "3", "target_epoch" => "4"}
+    json = interchange([%{"pubkey" => hex(pubkey), "signed_attestations" => [attestation]}])
+    assert SlashingProtection.import_interchange(json) == {:ok, 1}
+
+    assert SlashingProtection.sign_attestation(privkey, attestation_data(2, 5), <<1::256>>) ==
+             {:error, :surrounding_vote}
+  end
+
+  defp interchange(data) do
+    metadata = %{
+      "interchange_format_version" => "5",
+      "genesis_validators_root" => hex(@genesis_validators_root)
+    }
+
+    Jason.encode!(%{"metadata" => metadata, "data" => data})
+  end
+
+  defp hex(bytes), do: "0x" <> Base.encode16(bytes, case: :lower)
+end
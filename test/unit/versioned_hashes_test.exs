@@ -0,0 +1,72 @@
+defmodule Unit.VersionedHashesTest do
+  use ExUnit.Case
+
+  alias LambdaEthereumConsensus.Execution.ExecutionClient
+  alias LambdaEthereumConsensus.StateTransition.Misc
+  alias Types.NewPayloadRequest
+
+  @commitments [<<1::384>>, <<2::384>>, <<3::384>>]
+
+  defp rlp(items) when is_list(items) do
+    items |> Enum.map_join(&rlp/1) |> with_length(0xC0)
+  end
+
+  defp rlp(<<byte>>) when byte < 0x80, do: <<byte>>
+  defp rlp(bytes), do: with_length(bytes, 0x80)
+
+  defp with_length(payload, offset) when byte_size(payload) < 56,
+    do: <<offset + byte_size(payload)>> <> payload
+
+  defp with_length(payload, offset) do
+    length = :binary.encode_unsigned(byte_size(payload))
+    <<offset + 55 + byte_size(length)>> <> length <> payload
+  end
+
+  defp blob_tx(versioned_hashes) do
+    fields = [<<1>>, "", "", "", <<0x52, 0x08>>, <<0::160>>, "", "", [], <<1>>]
+    <<3>> <> rlp(fields ++ [versioned_hashes, "", <<1::256>>, <<2::256>>])
+  end
+
+  setup do
+    {:ok, hashes} = Ssz.kzg_commitments_to_versioned_hashes(@commitments)
+    {:ok, hashes: hashes}
+  end
+
+  test "versioned hashes of commitments", %{hashes: hashes} do
+    assert hashes == Enum.map(@commitments, &Misc.kzg_commitment_to_versioned_hash/1)
+
+    assert {:error, {:invalid_value, _}} =
+             Ssz.kzg_commitments_to_versioned_hashes([<<1::256>>])
+  end
+
+  test "versioned hashes of blob transactions", %{hashes: [first | rest] = hashes} do
+    # Non-blob transactions are skipped
+    transactions = [blob_tx([first]), <<2>> <> rlp([<<1>>]), blob_tx(rest)]
+
+    assert Ssz.blob_versioned_hashes(transactions) == {:ok, hashes}
+    assert Ssz.verify_blob_versioned_hashes(transactions, hashes) == {:ok, true}
+    assert Ssz.verify_blob_versioned_hashes(transactions, rest) == {:ok, false}
+    assert Ssz.verify_blob_versioned_hashes(transactions, Enum.reverse(hashes)) == {:ok, false}
+  end
+
+  test "malformed blob transactions", %{hashes: hashes} do
+    truncated = binary_part(blob_tx(hashes), 0, 100)
+    assert {:error, {:invalid_transaction, _}} = Ssz.blob_versioned_hashes([truncated])
+
+    too_few_fields = <<3>> <> rlp([<<1>>, hashes])
+    assert {:error, {:invalid_transaction, _}} = Ssz.blob_versioned_hashes([too_few_fields])
+
+    short_hash = blob_tx([<<1::248>>])
+    assert {:error, {:invalid_transaction, _}} = Ssz.blob_versioned_hashes([short_hash])
+  end
+
+  test "new payload requests", %{hashes: hashes} do
+    payload = %Types.ExecutionPayload{transactions: [blob_tx(hashes)]}
+    request = %NewPayloadRequest{execution_payload: payload, versioned_hashes: hashes}
+
+    assert ExecutionClient.valid_versioned_hashes?(request) == {:ok, :valid}
+
+    request = %{request | versioned_hashes: tl(hashes)}
+    assert ExecutionClient.valid_versioned_hashes?(request) == {:ok, :invalid}
+  end
+end